@@ -0,0 +1,349 @@
+//! An explicit application context that owns every service trailerfin needs.
+//!
+//! An [App] can be built any number of times, from any [AppConfig], with any of its services
+//! swapped out. It is also the entry point for embedding trailerfin in other services: see
+//! [App::scan], [App::refresh_path], [App::resolve] and [App::subscribe].
+//!
+//! The free `initialize_*` and `get_*` functions in [crate::caching], [crate::request_clients]
+//! and [crate::scrapers] are thin wrappers over a process-wide default [App], see
+//! [initialize_default_app].
+
+use std::path::Path;
+use std::sync::Arc;
+use anyhow::anyhow;
+use once_cell::sync::OnceCell;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+use crate::caching::title_match_cache::TitleMatchCache;
+use crate::caching::tmdb_to_imdb_cache::TmdbToImdbCache;
+use crate::caching::{create_database, create_title_match_cache, create_tmdb_to_imdb_cache};
+use crate::configuration::configuration_provider::{AppConfig, DataSource, LibraryProfile};
+use crate::request_clients::imdb_client::imdb_request_client::ImdbRequestClient;
+use crate::request_clients::tmdb_client::tmdb_request_client::TmdbRequestClient;
+use crate::request_clients::{create_imdb_request_client, create_tmdb_request_client};
+use crate::schedulers::scraping_scheduler::{app_scan_fn, ScrapingScheduler};
+use crate::scrapers::events::{ScanEvent, ScanEventSender};
+use crate::scrapers::folder_tagger::FolderTagger;
+use crate::scrapers::imdb_trailers::{ImdbTrailerScraper, ResolvedTrailer};
+use crate::scrapers::media_directories::{locate_title, process_media_folders, process_title, FolderType};
+use crate::scrapers::outcomes::{RunSummary, TitleOutcome};
+use crate::scrapers::scrape_error::{ScrapeError, ScrapeErrorKind};
+use crate::scrapers::title_resolver::TitleResolver;
+use crate::scrapers::tmdb_trailers::TmdbTrailerScraper;
+use crate::scrapers::traits::TrailerScraper;
+
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+static DEFAULT_APP: OnceCell<Arc<App>> = OnceCell::new();
+
+/// Builds the process-wide default [App] from `config` with [App::from_config], unless it has
+/// already been built, and returns it.
+pub fn initialize_default_app(config: Arc<AppConfig>) -> anyhow::Result<Arc<App>> {
+    DEFAULT_APP.get_or_try_init(|| App::from_config(config).map(Arc::new)).cloned()
+}
+
+/// The process-wide default [App].
+///
+/// # Panics
+/// If [initialize_default_app] hasn't been called.
+pub fn default_app() -> Arc<App> {
+    DEFAULT_APP.get().expect("Default app not initialized").clone()
+}
+
+/// A title to resolve a trailer for. TMDB IDs are only unique per media type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TitleId {
+    Imdb(String),
+    TmdbMovie(String),
+    TmdbTv(String),
+}
+
+pub struct App {
+    config: Arc<AppConfig>,
+    tmdb_to_imdb_cache: Arc<TmdbToImdbCache>,
+    title_match_cache: Arc<TitleMatchCache>,
+    imdb_client: Arc<ImdbRequestClient>,
+    tmdb_client: Option<Arc<TmdbRequestClient>>,
+    imdb_scraper: Arc<dyn TrailerScraper>,
+    tmdb_scraper: Option<Arc<dyn TrailerScraper>>,
+    imdb_trailer_scraper: Arc<ImdbTrailerScraper>,
+    tmdb_trailer_scraper: Option<Arc<TmdbTrailerScraper>>,
+    scheduler: Arc<ScrapingScheduler>,
+    events: ScanEventSender,
+}
+
+impl std::fmt::Debug for App {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(stringify!(App))
+            .field("config", &self.config)
+            .field("imdb_client", &self.imdb_client)
+            .field("tmdb_client", &self.tmdb_client)
+            .finish()
+    }
+}
+
+pub struct AppBuilder {
+    config: Arc<AppConfig>,
+    tmdb_to_imdb_cache: Option<Arc<TmdbToImdbCache>>,
+    title_match_cache: Option<Arc<TitleMatchCache>>,
+    imdb_client: Option<Arc<ImdbRequestClient>>,
+    tmdb_client: Option<Arc<TmdbRequestClient>>,
+    imdb_scraper: Option<Arc<dyn TrailerScraper>>,
+    tmdb_scraper: Option<Arc<dyn TrailerScraper>>,
+}
+
+impl AppBuilder {
+    pub fn with_tmdb_to_imdb_cache(mut self, cache: Arc<TmdbToImdbCache>) -> Self {
+        self.tmdb_to_imdb_cache = Some(cache);
+        self
+    }
+
+    /// Replaces the cache of TMDB matches for untagged folders, which is otherwise kept in the
+    /// database of the TMDB to IMDb cache.
+    pub fn with_title_match_cache(mut self, cache: Arc<TitleMatchCache>) -> Self {
+        self.title_match_cache = Some(cache);
+        self
+    }
+
+    pub fn with_imdb_client(mut self, client: Arc<ImdbRequestClient>) -> Self {
+        self.imdb_client = Some(client);
+        self
+    }
+
+    pub fn with_tmdb_client(mut self, client: Arc<TmdbRequestClient>) -> Self {
+        self.tmdb_client = Some(client);
+        self
+    }
+
+    /// Replaces the scraper used for libraries with the given data source.
+    pub fn with_scraper(mut self, data_source: DataSource, scraper: Arc<dyn TrailerScraper>) -> Self {
+        match data_source {
+            DataSource::Imdb => self.imdb_scraper = Some(scraper),
+            DataSource::Tmdb => self.tmdb_scraper = Some(scraper),
+        }
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<App> {
+        let config = self.config;
+        let uses_tmdb = config.uses_data_source(&DataSource::Tmdb);
+
+        let tmdb_to_imdb_cache = match self.tmdb_to_imdb_cache {
+            Some(cache) => cache,
+            None => create_tmdb_to_imdb_cache(create_database(&config)?)?,
+        };
+
+        let title_match_cache = match self.title_match_cache {
+            Some(cache) => cache,
+            None => create_title_match_cache(tmdb_to_imdb_cache.database().clone())?,
+        };
+
+        let imdb_client = match self.imdb_client {
+            Some(client) => client,
+            None => create_imdb_request_client(&config)?,
+        };
+
+        let tmdb_client = match self.tmdb_client {
+            Some(client) => Some(client),
+            None if (uses_tmdb && self.tmdb_scraper.is_none()) || config.resolves_untagged() => Some(create_tmdb_request_client(&config)?),
+            None => None,
+        };
+
+        let mut imdb_trailer_scraper = ImdbTrailerScraper::new(imdb_client.clone());
+        if let (Some(tmdb_client), true) = (&tmdb_client, config.resolves_untagged()) {
+            imdb_trailer_scraper = imdb_trailer_scraper.with_title_resolver(Arc::new(
+                TitleResolver::new(tmdb_client.clone(), tmdb_to_imdb_cache.clone(), title_match_cache.clone())
+            ));
+        }
+        if config.tags_folders() {
+            imdb_trailer_scraper = imdb_trailer_scraper.with_folder_tagger(Arc::new(FolderTagger::new(&config.cache_path)));
+        }
+        let imdb_trailer_scraper = Arc::new(imdb_trailer_scraper);
+
+        let tmdb_trailer_scraper = tmdb_client.as_ref().map(|tmdb_client| {
+            Arc::new(TmdbTrailerScraper::new(
+                imdb_trailer_scraper.clone(),
+                tmdb_to_imdb_cache.clone(),
+                tmdb_client.clone(),
+            ))
+        });
+
+        let imdb_scraper = self
+            .imdb_scraper
+            .unwrap_or_else(|| imdb_trailer_scraper.clone());
+
+        let tmdb_scraper = self
+            .tmdb_scraper
+            .or_else(|| tmdb_trailer_scraper.clone().map(|s| s as Arc<dyn TrailerScraper>));
+
+        debug!("Application context built");
+
+        Ok(App {
+            config,
+            tmdb_to_imdb_cache,
+            title_match_cache,
+            imdb_client,
+            tmdb_client,
+            imdb_scraper,
+            tmdb_scraper,
+            imdb_trailer_scraper,
+            tmdb_trailer_scraper,
+            scheduler: Arc::new(ScrapingScheduler {}),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+        })
+    }
+}
+
+impl App {
+    pub fn builder(config: Arc<AppConfig>) -> AppBuilder {
+        AppBuilder {
+            config,
+            tmdb_to_imdb_cache: None,
+            title_match_cache: None,
+            imdb_client: None,
+            tmdb_client: None,
+            imdb_scraper: None,
+            tmdb_scraper: None,
+        }
+    }
+
+    /// Builds an application context with the default services for `config`.
+    pub fn from_config(config: Arc<AppConfig>) -> anyhow::Result<App> {
+        Self::builder(config).build()
+    }
+
+    pub fn config(&self) -> &Arc<AppConfig> {
+        &self.config
+    }
+
+    pub fn tmdb_to_imdb_cache(&self) -> &Arc<TmdbToImdbCache> {
+        &self.tmdb_to_imdb_cache
+    }
+
+    pub fn title_match_cache(&self) -> &Arc<TitleMatchCache> {
+        &self.title_match_cache
+    }
+
+    pub fn imdb_client(&self) -> &Arc<ImdbRequestClient> {
+        &self.imdb_client
+    }
+
+    pub fn tmdb_client(&self) -> Option<&Arc<TmdbRequestClient>> {
+        self.tmdb_client.as_ref()
+    }
+
+    pub fn scraper(&self, data_source: &DataSource) -> anyhow::Result<Arc<dyn TrailerScraper>> {
+        match data_source {
+            DataSource::Imdb => Ok(self.imdb_scraper.clone()),
+            DataSource::Tmdb => self
+                .tmdb_scraper
+                .clone()
+                .ok_or_else(|| anyhow!("No TMDB scraper configured: no library uses the TMDB data source")),
+        }
+    }
+
+    /// Checks the TMDB credential against TMDB's `/authentication` endpoint, when a TMDB client is
+    /// configured. Only a rejected credential is an error: if TMDB can't be reached, that is
+    /// logged and left to the scans to deal with.
+    pub async fn check_tmdb_credential(&self) -> Result<(), ScrapeError> {
+        let Some(client) = &self.tmdb_client else {
+            return Ok(());
+        };
+        let credential = client.credential();
+
+        match client.authentication().validate().await {
+            Ok(status) if status.success => {
+                info!("TMDB accepted the {}", credential);
+                Ok(())
+            }
+            Ok(status) => Err(ScrapeError::new(
+                ScrapeErrorKind::TmdbAuthInvalid,
+                format!("TMDB rejected the {}: {}", credential, status.status_message.unwrap_or_default()),
+            )),
+            Err(e) if e.is_auth_rejected() => Err(ScrapeError::new(
+                ScrapeErrorKind::TmdbAuthInvalid,
+                format!("TMDB rejected the {}", credential),
+            )
+            .with_source(e)),
+            Err(e) => {
+                warn!("Could not check the {} with TMDB: {}", credential, e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Receives [ScanEvent]s for every scan and refresh started through this context.
+    pub fn subscribe(&self) -> broadcast::Receiver<ScanEvent> {
+        self.events.subscribe()
+    }
+
+    /// Scans every configured library in turn and returns the combined outcome of every title.
+    /// A failing library does not stop the others, and is listed in [RunSummary::failed_libraries].
+    pub async fn scan(&self) -> RunSummary {
+        let mut summary = RunSummary::default();
+
+        for library in &self.config.libraries {
+            match self.scan_library(library).await {
+                Ok(library_summary) => summary.merge(library_summary),
+                Err(e) => {
+                    tracing::error!("Trailer scan failed for library '{}': {e}", library.name);
+                    summary.failed_libraries.push((library.name.clone(), format!("{e:#}")));
+                }
+            }
+        }
+
+        if self.config.libraries.len() > 1 {
+            summary.log("Scan of all libraries");
+        }
+
+        summary
+    }
+
+    pub async fn scan_library(&self, library: &Arc<LibraryProfile>) -> anyhow::Result<RunSummary> {
+        tracing::info!("Scanning library '{}' using data source {:?}", library.name, library.data_source);
+        let scraper = self.scraper(&library.data_source)?;
+        let summary = process_media_folders(&self.config, library, scraper, Some(&self.events)).await;
+        // Both data sources extract their trailers from IMDb pages.
+        self.imdb_client.extraction_stats().log();
+        summary
+    }
+
+    /// Refreshes the trailer of a single title folder inside one of the configured libraries.
+    pub async fn refresh_path(&self, path: &Path) -> anyhow::Result<TitleOutcome> {
+        let (library, folder_type) = locate_title(&self.config.libraries, path)
+            .ok_or_else(|| anyhow!("{:?} is not a title folder in any configured library", path))?;
+
+        let scraper = self.scraper(&library.data_source)?;
+        Ok(process_title(scraper.as_ref(), path.to_path_buf(), library, folder_type, Some(&self.events)).await)
+    }
+
+    /// Looks up the current trailer for a title without writing anything to disk.
+    pub async fn resolve(&self, id: &TitleId) -> anyhow::Result<Option<ResolvedTrailer>> {
+        let imdb_id = match id {
+            TitleId::Imdb(imdb_id) => imdb_id.clone(),
+            TitleId::TmdbMovie(tmdb_id) | TitleId::TmdbTv(tmdb_id) => {
+                let tmdb_scraper = self
+                    .tmdb_trailer_scraper
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("Resolving TMDB IDs requires a TMDB API key"))?;
+                let folder_type = match id {
+                    TitleId::TmdbTv(_) => FolderType::TvShow,
+                    _ => FolderType::Movie,
+                };
+                match tmdb_scraper.get_imdb_id(tmdb_id, folder_type).await? {
+                    Some(imdb_id) => imdb_id,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        Ok(self.imdb_trailer_scraper.resolve_trailer(&imdb_id).await?)
+    }
+
+    /// Runs the scheduler with this context's services until ctrl-c.
+    pub async fn start_scheduler(self: &Arc<Self>) -> anyhow::Result<()> {
+        self.scheduler
+            .start_scheduler_with_scan_fn(self.config.clone(), Some(app_scan_fn(Arc::clone(self))))
+            .await
+    }
+}
@@ -1,36 +1,45 @@
 use std::sync::Arc;
-use once_cell::sync::OnceCell;
-use tracing::{debug};
+use anyhow::Context;
+use tracing::debug;
+use crate::app::{default_app, initialize_default_app};
 use crate::caching::redb_database::RedbDatabase;
+use crate::caching::title_match_cache::TitleMatchCache;
 use crate::caching::tmdb_to_imdb_cache::TmdbToImdbCache;
 use crate::configuration::configuration_provider::AppConfig;
 
 pub mod tmdb_to_imdb_cache;
-mod redb_database;
-
-pub static REDB_INSTANCE: OnceCell<Arc<RedbDatabase>> = OnceCell::new();
-pub static TMDB_TO_IMDB_CACHE: OnceCell<Arc<TmdbToImdbCache>> = OnceCell::new();
+pub mod title_match_cache;
+pub mod redb_database;
 
+/// Opens the caches of the default [crate::app::App], building it from `app_config` if needed.
 pub fn initialize_caching(app_config: Arc<AppConfig>) {
-    let db = init_database(app_config);
-    init_tmdb_to_imdb_cache(db);
+    initialize_default_app(app_config).expect("Failed to initialize caching");
     debug!("Initialized caching");
 }
 
 pub fn get_tmdb_to_imdb_cache() -> Arc<TmdbToImdbCache> {
-    TMDB_TO_IMDB_CACHE
-        .get()
-        .expect("Tmdb to imdb cache not initialized")
-        .clone()
+    default_app().tmdb_to_imdb_cache().clone()
+}
+
+pub fn get_title_match_cache() -> Arc<TitleMatchCache> {
+    default_app().title_match_cache().clone()
 }
 
-fn init_database(app_config: Arc<AppConfig>) -> Arc<RedbDatabase> {
-    let redb_path = std::path::Path::new(&app_config.cache_path).canonicalize().unwrap().join("caches.redb");
-    let db = RedbDatabase::new(&redb_path).expect("Failed to initialize redb database");
-    REDB_INSTANCE.get_or_init(|| Arc::new(db)).clone()
+/// Opens (or creates) the embedded cache database in the configured cache directory.
+pub fn create_database(app_config: &AppConfig) -> anyhow::Result<Arc<RedbDatabase>> {
+    let redb_path = std::path::Path::new(&app_config.cache_path)
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve cache path {:?}", app_config.cache_path))?
+        .join("caches.redb");
+    Ok(Arc::new(RedbDatabase::new(&redb_path)?))
 }
 
-fn init_tmdb_to_imdb_cache(db: Arc<RedbDatabase>) {
-    let cache = TmdbToImdbCache::new(db).expect("Failed to initialize TMDB to IMDB cache");
-    TMDB_TO_IMDB_CACHE.get_or_init(|| Arc::new(cache));
-}
\ No newline at end of file
+pub fn create_tmdb_to_imdb_cache(db: Arc<RedbDatabase>) -> anyhow::Result<Arc<TmdbToImdbCache>> {
+    let cache = TmdbToImdbCache::new(db).context("Failed to initialize TMDB to IMDB cache")?;
+    Ok(Arc::new(cache))
+}
+
+pub fn create_title_match_cache(db: Arc<RedbDatabase>) -> anyhow::Result<Arc<TitleMatchCache>> {
+    let cache = TitleMatchCache::new(db).context("Failed to initialize title match cache")?;
+    Ok(Arc::new(cache))
+}
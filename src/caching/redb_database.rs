@@ -1,4 +1,5 @@
 use std::path::Path;
+use anyhow::Context;
 use redb::Database;
 
 #[derive(Debug)]
@@ -9,10 +10,9 @@ pub struct RedbDatabase {
 impl RedbDatabase {
     pub fn new(path: &Path) -> anyhow::Result<Self> {
         let db = if !path.exists() {
-            Database::create(path).expect("Failed to create new database file.")
+            Database::create(path).with_context(|| format!("Failed to create new database file {:?}", path))?
         } else {
-            
-            Database::open(path).expect("Failed to open existing database file.")
+            Database::open(path).with_context(|| format!("Failed to open existing database file {:?}", path))?
         };
 
         Ok(Self { db })
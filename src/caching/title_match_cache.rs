@@ -0,0 +1,72 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use redb::TableDefinition;
+use serde::{Deserialize, Serialize};
+use crate::caching::redb_database::RedbDatabase;
+
+/// What a search for an untagged folder's title found on TMDB.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum TitleMatch {
+    /// Exactly one TMDB title has the folder's title and year.
+    Matched { tmdb_id: u64 },
+    /// Several TMDB titles do, so the folder has to be tagged by hand.
+    Ambiguous { candidates: Vec<u64> },
+    /// None does.
+    NoMatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedTitleMatch {
+    #[serde(flatten)]
+    pub title_match: TitleMatch,
+    /// Unix time of the search.
+    pub decided_at: u64,
+}
+
+impl CachedTitleMatch {
+    pub fn age_secs(&self) -> u64 {
+        now_secs().saturating_sub(self.decided_at)
+    }
+}
+
+/// Remembers the decision for each searched title, so a folder is only searched for once.
+#[derive(Debug)]
+pub struct TitleMatchCache {
+    redb: Arc<RedbDatabase>,
+}
+
+const TITLE_MATCH_TABLE: TableDefinition<&str, &str> = TableDefinition::new("title_matches");
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+impl TitleMatchCache {
+    pub fn new(redb: Arc<RedbDatabase>) -> anyhow::Result<Self> {
+        let txn = redb.db().begin_write()?;
+        _ = txn.open_table(TITLE_MATCH_TABLE)?;
+        txn.commit()?;
+        Ok(Self { redb })
+    }
+
+    pub fn get(&self, key: &str) -> anyhow::Result<Option<CachedTitleMatch>> {
+        let txn = self.redb.db().begin_read()?;
+        let table = txn.open_table(TITLE_MATCH_TABLE)?;
+        let Some(value) = table.get(key)? else {
+            return Ok(None);
+        };
+        Ok(Some(serde_json::from_str(value.value())?))
+    }
+
+    pub fn add(&self, key: &str, title_match: TitleMatch) -> anyhow::Result<()> {
+        let value = serde_json::to_string(&CachedTitleMatch { title_match, decided_at: now_secs() })?;
+        let txn = self.redb.db().begin_write()?;
+        {
+            let mut table = txn.open_table(TITLE_MATCH_TABLE)?;
+            table.insert(key, value.as_str())?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+}
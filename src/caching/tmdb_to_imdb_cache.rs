@@ -1,7 +1,19 @@
 use std::sync::Arc;
 use redb::{TableDefinition};
+use serde::{Deserialize, Serialize};
 use crate::caching::redb_database::RedbDatabase;
 
+/// The IDs TMDB lists for a title on other sites.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExternalTitleIds {
+    pub imdb_id: Option<String>,
+    /// Only shows have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tvdb_id: Option<u64>,
+}
+
+/// The external IDs of TMDB titles. Movies and shows have separate TMDB ID spaces, so entries are
+/// keyed by media type as well, e.g. `movie:603` and `tv:1399`.
 #[derive(Debug)]
 pub struct TmdbToImdbCache {
     redb: Arc<RedbDatabase>,
@@ -9,28 +21,49 @@ pub struct TmdbToImdbCache {
 
 const TMDB_TO_IMDB_TABLE: TableDefinition<&str, &str> = TableDefinition::new("tmdb_to_imdb");
 
+fn key(media_type: &str, tmdb_id: &str) -> String {
+    format!("{}:{}", media_type, tmdb_id)
+}
+
 impl TmdbToImdbCache {
+    /// Opens the cache, dropping entries from older versions that were keyed by the TMDB ID alone
+    /// and can't tell a movie from a show.
     pub fn new(redb: Arc<RedbDatabase>) -> anyhow::Result<Self> {
         let txn = redb.db().begin_write()?;
-        _ = txn.open_table(TMDB_TO_IMDB_TABLE).expect("Failed to open TMDB to IMDB table.");
-        txn.commit().expect("Failed to commit transaction.");
+        {
+            let mut table = txn.open_table(TMDB_TO_IMDB_TABLE)?;
+            table.retain(|key, _| key.contains(':'))?;
+        }
+        txn.commit()?;
         Ok(Self { redb })
     }
 
-    pub fn try_get_imdb_id(&self, tmdb_id: &str) -> anyhow::Result<Option<String>> {
+    /// The database the cache is kept in, which other caches can share.
+    pub fn database(&self) -> &Arc<RedbDatabase> {
+        &self.redb
+    }
+
+    /// The cached IDs of the TMDB `movie` or `tv` title `tmdb_id`. An entry that can't be read is
+    /// treated as missing, so it is looked up again.
+    pub fn try_get(&self, media_type: &str, tmdb_id: &str) -> anyhow::Result<Option<ExternalTitleIds>> {
         let txn = self.redb.db().begin_read()?;
         let table = txn.open_table(TMDB_TO_IMDB_TABLE)?;
-        let value = table.get(tmdb_id)?;
-        Ok(value.map(|v| v.value().to_owned()))
+        let value = table.get(key(media_type, tmdb_id).as_str())?;
+        Ok(value.and_then(|v| serde_json::from_str(v.value()).ok()))
     }
 
-    pub fn add(&self, tmdb_id: &str, imdb_id: &str) -> anyhow::Result<()> {
+    pub fn try_get_imdb_id(&self, media_type: &str, tmdb_id: &str) -> anyhow::Result<Option<String>> {
+        Ok(self.try_get(media_type, tmdb_id)?.and_then(|ids| ids.imdb_id))
+    }
+
+    pub fn add(&self, media_type: &str, tmdb_id: &str, ids: &ExternalTitleIds) -> anyhow::Result<()> {
+        let value = serde_json::to_string(ids)?;
         let txn = self.redb.db().begin_write()?;
         {
             let mut table = txn.open_table(TMDB_TO_IMDB_TABLE)?;
-            table.insert(tmdb_id, imdb_id)?;
+            table.insert(key(media_type, tmdb_id).as_str(), value.as_str())?;
         }
         txn.commit()?;
         Ok(())
     }
-}
\ No newline at end of file
+}
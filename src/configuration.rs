@@ -1 +1,2 @@
-pub mod configuration_provider;
\ No newline at end of file
+pub mod configuration_provider;
+pub mod id_patterns;
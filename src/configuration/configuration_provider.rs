@@ -1,26 +1,161 @@
 use anyhow::{anyhow, Context};
 use std::{path::PathBuf, sync::Arc};
+use std::collections::HashSet;
 use std::path::Path;
-use config::Config;
+use config::builder::DefaultState;
+use config::{Config, ConfigBuilder, ConfigError};
+use once_cell::sync::{Lazy, OnceCell};
 use tracing::{info};
 use serde::de::{self, Deserializer};
 use serde::Deserialize;
 use regex::Regex;
+use url::Url;
+use crate::configuration::id_patterns::{IdKind, IdPatterns, IdPreset, LibraryIdPatterns};
+use crate::request_clients::imdb_client::imdb_request_client;
+use crate::request_clients::tmdb_client::tmdb_request_client;
+use crate::request_clients::circuit_breaker::{DEFAULT_CIRCUIT_BREAKER_COOLDOWN_SECS, DEFAULT_CIRCUIT_BREAKER_THRESHOLD};
+use crate::request_clients::client_settings::{RedactedUrl, Secret, UserAgentRotation, DEFAULT_ACCEPT, DEFAULT_ACCEPT_LANGUAGE, DEFAULT_CONNECT_TIMEOUT_SECS, DEFAULT_REQUEST_TIMEOUT_SECS};
+use crate::request_clients::response_cache::DEFAULT_IMDB_GALLERY_CACHE_TTL_SECS;
+use crate::request_clients::retry_policy::{DEFAULT_MAX_BACKOFF_MS, DEFAULT_MAX_RETRIES, DEFAULT_RETRY_BACKOFF_MS, DEFAULT_RETRY_STATUSES};
 
 const DATASOURCES: [&str; 2] = ["IMDB", "TMDB"];
+const CONFIG_FILE_ENV: &str = "TRAILERFIN_CONFIG_FILE";
+const DEFAULT_LIBRARY_NAME: &str = "default";
+const PROXY_SCHEMES: [&str; 4] = ["http", "https", "socks5", "socks5h"];
+const SECRETS_DIR_ENV: &str = "TRAILERFIN_SECRETS_DIR";
+static TMDB_LANGUAGE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z]{2}(-[A-Z]{2})?$").expect("Invalid TMDB language regex"));
+static TMDB_REGION_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Z]{2}$").expect("Invalid TMDB region regex"));
+
+/// Settings that can hold credentials. Besides its environment variable, each can be read from the
+/// file named by its `_FILE` variable, e.g. `TRAILERFIN_TMDB_API_KEY_FILE`, or from a Docker secret
+/// such as `/run/secrets/trailerfin_tmdb_api_key`.
+pub const SECRET_SETTINGS: [&str; 3] = ["tmdb_api_key", "imdb_proxy", "tmdb_proxy"];
+/// Where Docker and Kubernetes mount secrets. `TRAILERFIN_SECRETS_DIR` overrides it.
+pub const DEFAULT_SECRETS_DIR: &str = "/run/secrets";
+
+pub const DEFAULT_CACHE_PATH: &str = "/config";
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 Chrome/124.0.0.0";
+pub const DEFAULT_VIDEO_FILENAME: &str = "video1.strm";
+pub const DEFAULT_TRAILER_FOLDER: &str = "backdrops";
+pub const DEFAULT_SCAN_DEPTH: usize = 1;
+pub const DEFAULT_IMDB_RATE_LIMIT: &str = "30/minute";
+pub const DEFAULT_TMDB_RATE_LIMIT: &str = "50/second";
+pub const DEFAULT_IMDB_ID_REGEX: &str = r"\{imdb-(tt\d+)}";
+pub const DEFAULT_TMDB_ID_REGEX: &str = r"\{tmdb-(\d+)}";
+pub const DEFAULT_IMDB_BASE_URL: &str = imdb_request_client::BASE_URL;
+pub const DEFAULT_TMDB_BASE_URL: &str = tmdb_request_client::BASE_URL;
+
+fn parse_datasource<E: de::Error>(s: &str) -> Result<DataSource, E> {
+    match s.to_lowercase().as_str() {
+        "imdb" => Ok(DataSource::Imdb),
+        "tmdb" => Ok(DataSource::Tmdb),
+        other => Err(E::custom(format!("invalid TRAILERFIN_DATA_SOURCE: {}. Must be one of: {:?}", other, DATASOURCES)))
+    }
+}
 
 fn case_insensitive_datasource<'de, D>(deserializer: D) -> Result<DataSource, D::Error>
 where
     D: Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    match s.to_lowercase().as_str() {
-        "imdb" => Ok(DataSource::Imdb),
-        "tmdb" => Ok(DataSource::Tmdb),
-        other => Err(de::Error::custom(format!("invalid TRAILERFIN_DATA_SOURCE: {}. Must be one of: {:?}", other, DATASOURCES)))
+    parse_datasource(&s)
+}
+
+fn case_insensitive_optional_datasource<'de, D>(deserializer: D) -> Result<Option<DataSource>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| parse_datasource(&s))
+        .transpose()
+}
+
+fn parse_folder_tagging<E: de::Error>(s: &str) -> Result<FolderTagging, E> {
+    match s.trim().to_lowercase().as_str() {
+        "off" | "false" | "" => Ok(FolderTagging::Off),
+        "sidecar" => Ok(FolderTagging::Sidecar),
+        "rename" => Ok(FolderTagging::Rename),
+        other => Err(E::custom(format!("invalid TRAILERFIN_TAG_FOLDERS: {}. Must be one of: off, sidecar, rename", other))),
+    }
+}
+
+fn deserialize_folder_tagging<'de, D>(deserializer: D) -> Result<FolderTagging, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    parse_folder_tagging(&s)
+}
+
+fn deserialize_optional_folder_tagging<'de, D>(deserializer: D) -> Result<Option<FolderTagging>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| parse_folder_tagging(&s))
+        .transpose()
+}
+
+/// A setting given as a single value, e.g. in an environment variable, or as a list in the config file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn into_vec(self, separator: char) -> Vec<String> {
+        match self {
+            OneOrMany::One(s) => split_trimmed(&s, separator),
+            OneOrMany::Many(values) => values,
+        }
     }
 }
 
+/// Regexes can contain commas, so several patterns in one variable are put on separate lines.
+fn deserialize_patterns<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(OneOrMany::deserialize(deserializer)?.into_vec('\n'))
+}
+
+fn deserialize_optional_patterns<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<OneOrMany>::deserialize(deserializer)?.map(|patterns| patterns.into_vec('\n')))
+}
+
+fn parse_id_presets<E: de::Error>(names: Vec<String>) -> Result<Vec<IdPreset>, E> {
+    names
+        .iter()
+        .map(|name| {
+            IdPreset::parse(name).ok_or_else(|| {
+                let known: Vec<&str> = IdPreset::ALL.iter().map(IdPreset::name).collect();
+                E::custom(format!("invalid TRAILERFIN_ID_PRESETS: {}. Must be one of: {:?}", name, known))
+            })
+        })
+        .collect()
+}
+
+fn deserialize_id_presets<'de, D>(deserializer: D) -> Result<Vec<IdPreset>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    parse_id_presets(OneOrMany::deserialize(deserializer)?.into_vec(','))
+}
+
+fn deserialize_optional_id_presets<'de, D>(deserializer: D) -> Result<Option<Vec<IdPreset>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<OneOrMany>::deserialize(deserializer)?
+        .map(|names| parse_id_presets(names.into_vec(',')))
+        .transpose()
+}
+
 fn validate_path(path: &str, name: &str) -> anyhow::Result<PathBuf> {
     let path_buf = PathBuf::from(path);
     if !path_buf.exists() || !path_buf.is_dir() {
@@ -33,24 +168,137 @@ fn validate_path(path: &str, name: &str) -> anyhow::Result<PathBuf> {
     path_buf.canonicalize().with_context(|| format!("Failed to canonicalize path for {}: {:?}", name, path_buf))
 }
 
+/// Accepts a percentage either as a number (`10`, `2.5`) or with a trailing percent sign (`"10%"`).
+fn deserialize_percentage<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Percentage {
+        Number(f64),
+        Text(String),
+    }
+
+    match Percentage::deserialize(deserializer)? {
+        Percentage::Number(n) => Ok(n),
+        Percentage::Text(s) => s
+            .trim()
+            .trim_end_matches('%')
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| de::Error::custom(format!("invalid TRAILERFIN_FAILURE_THRESHOLD: {}. Expected a percentage such as 10%", s))),
+    }
+}
+
+fn validate_proxy(proxy: &Option<Secret>, name: &str) -> anyhow::Result<()> {
+    let Some(proxy) = proxy else {
+        return Ok(());
+    };
+
+    let url = Url::parse(proxy.expose()).map_err(|e| anyhow!("{} is not a valid URL: {}", name, e))?;
+    if !PROXY_SCHEMES.contains(&url.scheme()) {
+        return Err(anyhow!("{} must use one of the schemes {:?}, got '{}'", name, PROXY_SCHEMES, url.scheme()));
+    }
+    if url.host_str().is_none() {
+        return Err(anyhow!("{} must include a host", name));
+    }
+    Ok(())
+}
+
+fn validate_base_url(url: &RedactedUrl, name: &str) -> anyhow::Result<()> {
+    let url = url.as_str();
+    let parsed = Url::parse(url).map_err(|e| anyhow!("{} contains an invalid URL '{}': {}", name, url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(anyhow!("{} must use http or https, got '{}'", name, url));
+    }
+    if parsed.host_str().is_none() || parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err(anyhow!("{} must be a base URL with a host and without query or fragment, got '{}'", name, url));
+    }
+    Ok(())
+}
+
+fn validate_file(path: &Option<String>, name: &str) -> anyhow::Result<()> {
+    match path {
+        Some(path) if !Path::new(path).is_file() => Err(anyhow!("Provided file for {} does not exist: {:?}", name, path)),
+        _ => Ok(()),
+    }
+}
+
+fn deserialize_status_csv<'de, D>(deserializer: D) -> Result<Vec<u16>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_trimmed_csv(deserializer)?
+        .iter()
+        .map(|v| v.parse::<u16>().map_err(|_| de::Error::custom(format!("invalid HTTP status code: {}", v))))
+        .collect()
+}
+
 fn deserialize_trimmed_csv<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
 where
     D: Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    Ok(s.split(',')
+    Ok(split_trimmed(&s, ','))
+}
+
+fn deserialize_url_csv<'de, D>(deserializer: D) -> Result<Vec<RedactedUrl>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(deserialize_trimmed_csv(deserializer)?.into_iter().map(RedactedUrl::from).collect())
+}
+
+/// User agents contain commas, so the pool is separated with `|`.
+fn deserialize_user_agents<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Ok(split_trimmed(&s, '|'))
+}
+
+fn split_trimmed(s: &str, separator: char) -> Vec<String> {
+    s.split(separator)
         .map(|v| v.trim().to_string())
         .filter(|v| !v.is_empty())
-        .collect())
+        .collect()
 }
 
-#[derive(Debug, Default, serde::Deserialize, PartialEq)]
+fn deserialize_user_agent_rotation<'de, D>(deserializer: D) -> Result<UserAgentRotation, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    match s.trim().to_lowercase().replace('-', "_").as_str() {
+        "per_session" => Ok(UserAgentRotation::PerSession),
+        "per_request" => Ok(UserAgentRotation::PerRequest),
+        other => Err(de::Error::custom(format!(
+            "invalid TRAILERFIN_USER_AGENT_ROTATION: {}. Must be one of: per_session, per_request",
+            other
+        ))),
+    }
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize, PartialEq)]
 pub enum DataSource {
     #[default]
     Imdb,
     Tmdb,
 }
 
+/// How IDs that were resolved for untagged folders are written back to the library.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum FolderTagging {
+    #[default]
+    Off,
+    /// Write the IDs to a `.trailerfin.json` file in the folder.
+    Sidecar,
+    /// Append an ID tag such as `{imdb-tt0133093}` to the folder name.
+    Rename,
+}
+
 #[derive(Debug, Default, serde::Deserialize, PartialEq)]
 pub struct AppConfig {
     pub scan_path: String,
@@ -67,17 +315,537 @@ pub struct AppConfig {
 
     pub imdb_rate_limit: String,
     pub tmdb_rate_limit: String,
-    pub tmdb_api_key: Option<String>,
+    pub tmdb_api_key: Option<Secret>,
 
+    /// Language (`en-US`) and region (`US`) that TMDB titles, search results and release dates
+    /// are localized for.
+    #[serde(default)]
+    pub tmdb_language: Option<String>,
+    #[serde(default)]
+    pub tmdb_region: Option<String>,
+
+    /// Patterns that find IDs in folder names. The single `_regex` pattern is tried first, then
+    /// the `_regexes` list and the patterns of the presets.
     #[serde(default)]
     pub imdb_id_regex: String,
     #[serde(default)]
     pub tmdb_id_regex: String,
+    #[serde(default, deserialize_with = "deserialize_patterns")]
+    pub imdb_id_regexes: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_patterns")]
+    pub tmdb_id_regexes: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_id_presets")]
+    pub id_presets: Vec<IdPreset>,
 
     #[serde(default, deserialize_with = "deserialize_trimmed_csv")]
     pub tv_folders: Vec<String>,
     #[serde(default, deserialize_with = "deserialize_trimmed_csv")]
     pub movie_folders: Vec<String>,
+
+    #[serde(default)]
+    pub trailer_folder: String,
+
+    /// How many levels below each TV and movie folder to look for title folders.
+    #[serde(default)]
+    pub scan_depth: usize,
+
+    /// Look up folders without an ID tag on TMDB by their `Title (Year)` name. Needs a TMDB API key.
+    #[serde(default)]
+    pub resolve_untagged: bool,
+
+    /// Write the IDs of resolved folders back to the library, so later scans don't have to
+    /// resolve them again, or only log what would be written. Changes are kept in an undo log.
+    #[serde(default, deserialize_with = "deserialize_folder_tagging")]
+    pub tag_folders: FolderTagging,
+    #[serde(default)]
+    pub tag_dry_run: bool,
+
+    /// Percentage of titles that may fail in a run-once scan before the process exits with a
+    /// partial-failure code. `0` means any failed title fails the run.
+    #[serde(default, deserialize_with = "deserialize_percentage")]
+    pub failure_threshold: f64,
+
+    /// How often a failed IMDb or TMDB request is retried, and which statuses are retried.
+    #[serde(default)]
+    pub http_max_retries: u32,
+    #[serde(default)]
+    pub http_retry_backoff_ms: u64,
+    #[serde(default)]
+    pub http_max_backoff_ms: u64,
+    #[serde(default, deserialize_with = "deserialize_status_csv")]
+    pub http_retry_statuses: Vec<u16>,
+
+    /// Lower the request rate below the configured limits while IMDb or TMDB are throttling.
+    #[serde(default)]
+    pub adaptive_rate_limit: bool,
+
+    /// Consecutive failed IMDb requests after which IMDb traffic is paused for the cool-down.
+    /// `0` disables the circuit breaker.
+    #[serde(default)]
+    pub imdb_circuit_breaker_threshold: u32,
+    #[serde(default)]
+    pub imdb_circuit_breaker_cooldown_secs: u64,
+
+    /// Where IMDb and TMDB are reached, e.g. through a caching reverse proxy, and mirrors that are
+    /// tried in turn when a base URL can't be connected to.
+    #[serde(default)]
+    pub imdb_base_url: RedactedUrl,
+    #[serde(default, deserialize_with = "deserialize_url_csv")]
+    pub imdb_mirrors: Vec<RedactedUrl>,
+    #[serde(default)]
+    pub tmdb_base_url: RedactedUrl,
+    #[serde(default, deserialize_with = "deserialize_url_csv")]
+    pub tmdb_mirrors: Vec<RedactedUrl>,
+
+    /// Proxies for the IMDb and TMDB clients (`http`, `https`, `socks5` or `socks5h` URLs, optionally
+    /// with credentials), hosts that bypass them, and PEM files with extra root certificates.
+    #[serde(default)]
+    pub imdb_proxy: Option<Secret>,
+    #[serde(default, deserialize_with = "deserialize_trimmed_csv")]
+    pub imdb_no_proxy: Vec<String>,
+    #[serde(default)]
+    pub imdb_ca_certificate: Option<String>,
+    #[serde(default)]
+    pub tmdb_proxy: Option<Secret>,
+    #[serde(default, deserialize_with = "deserialize_trimmed_csv")]
+    pub tmdb_no_proxy: Vec<String>,
+    #[serde(default)]
+    pub tmdb_ca_certificate: Option<String>,
+
+    /// A pool of user agents for IMDb requests, used instead of `user_agent` when set, and whether
+    /// one is picked per run or the pool is cycled through per request.
+    #[serde(default, deserialize_with = "deserialize_user_agents")]
+    pub user_agents: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_user_agent_rotation")]
+    pub user_agent_rotation: UserAgentRotation,
+
+    /// `Accept-Language` and `Accept` headers sent to IMDb. An empty value leaves the header out.
+    #[serde(default)]
+    pub imdb_accept_language: String,
+    #[serde(default)]
+    pub imdb_accept: String,
+
+    /// Keep IMDb cookies in the cache directory across runs.
+    #[serde(default)]
+    pub imdb_cookie_jar: bool,
+
+    /// Cache IMDb video galleries on disk. Within its TTL a cached response
+    /// is used without a request; after that it is revalidated with its ETag or Last-Modified date.
+    #[serde(default)]
+    pub http_cache: bool,
+    #[serde(default)]
+    pub imdb_gallery_cache_ttl_secs: u64,
+
+    /// Record every IMDb and TMDB response to this directory, or answer requests from a directory
+    /// recorded earlier instead of the network, to reproduce a scan exactly.
+    #[serde(default)]
+    pub record_fixtures: Option<String>,
+    #[serde(default)]
+    pub replay_fixtures: Option<String>,
+
+    /// Connect and whole-request timeouts for IMDb and TMDB requests. `0` disables a timeout.
+    #[serde(default)]
+    pub http_connect_timeout_secs: u64,
+    #[serde(default)]
+    pub http_request_timeout_secs: u64,
+
+    /// Resolved library profiles. Populated from the `libraries` section of the config file,
+    /// or from the top-level settings as a single `default` library when none are configured.
+    #[serde(skip)]
+    pub libraries: Vec<Arc<LibraryProfile>>,
+}
+
+impl AppConfig {
+    /// Creates a configuration with the same defaults as the environment loader and no libraries.
+    /// Add libraries with [AppConfig::with_library] and validate the result with
+    /// [ConfigurationProvider::validate_config] before building an [crate::app::App] from it.
+    pub fn new(cache_path: impl Into<String>) -> Self {
+        Self {
+            scan_path: String::new(),
+            video_filename: DEFAULT_VIDEO_FILENAME.to_string(),
+            should_schedule: false,
+            schedule: None,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            threads: 1,
+            cache_path: cache_path.into(),
+            data_source: DataSource::Imdb,
+            imdb_rate_limit: DEFAULT_IMDB_RATE_LIMIT.to_string(),
+            tmdb_rate_limit: DEFAULT_TMDB_RATE_LIMIT.to_string(),
+            tmdb_api_key: None,
+            tmdb_language: None,
+            tmdb_region: None,
+            imdb_id_regex: DEFAULT_IMDB_ID_REGEX.to_string(),
+            tmdb_id_regex: DEFAULT_TMDB_ID_REGEX.to_string(),
+            imdb_id_regexes: vec![],
+            tmdb_id_regexes: vec![],
+            id_presets: vec![],
+            tv_folders: vec![],
+            movie_folders: vec![],
+            trailer_folder: DEFAULT_TRAILER_FOLDER.to_string(),
+            scan_depth: DEFAULT_SCAN_DEPTH,
+            resolve_untagged: false,
+            tag_folders: FolderTagging::Off,
+            tag_dry_run: false,
+            failure_threshold: 0.0,
+            adaptive_rate_limit: true,
+            imdb_circuit_breaker_threshold: DEFAULT_CIRCUIT_BREAKER_THRESHOLD,
+            imdb_circuit_breaker_cooldown_secs: DEFAULT_CIRCUIT_BREAKER_COOLDOWN_SECS,
+            imdb_base_url: DEFAULT_IMDB_BASE_URL.into(),
+            imdb_mirrors: vec![],
+            tmdb_base_url: DEFAULT_TMDB_BASE_URL.into(),
+            tmdb_mirrors: vec![],
+            imdb_proxy: None,
+            imdb_no_proxy: vec![],
+            imdb_ca_certificate: None,
+            tmdb_proxy: None,
+            tmdb_no_proxy: vec![],
+            tmdb_ca_certificate: None,
+            http_max_retries: DEFAULT_MAX_RETRIES,
+            http_retry_backoff_ms: DEFAULT_RETRY_BACKOFF_MS,
+            http_max_backoff_ms: DEFAULT_MAX_BACKOFF_MS,
+            http_retry_statuses: DEFAULT_RETRY_STATUSES.to_vec(),
+            user_agents: vec![],
+            user_agent_rotation: UserAgentRotation::PerSession,
+            imdb_accept_language: DEFAULT_ACCEPT_LANGUAGE.to_string(),
+            imdb_accept: DEFAULT_ACCEPT.to_string(),
+            imdb_cookie_jar: true,
+            http_cache: true,
+            record_fixtures: None,
+            replay_fixtures: None,
+            imdb_gallery_cache_ttl_secs: DEFAULT_IMDB_GALLERY_CACHE_TTL_SECS,
+            http_connect_timeout_secs: DEFAULT_CONNECT_TIMEOUT_SECS,
+            http_request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+            libraries: vec![],
+        }
+    }
+
+    pub fn with_library(mut self, library: LibraryProfile) -> Self {
+        self.libraries.push(Arc::new(library));
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn with_tmdb_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.tmdb_api_key = Some(Secret::new(api_key));
+        self
+    }
+
+    pub fn with_rate_limits(mut self, imdb_rate_limit: impl Into<String>, tmdb_rate_limit: impl Into<String>) -> Self {
+        self.imdb_rate_limit = imdb_rate_limit.into();
+        self.tmdb_rate_limit = tmdb_rate_limit.into();
+        self
+    }
+
+    /// Sets the percentage of titles (0-100) that may fail before a run counts as failed.
+    pub fn with_failure_threshold(mut self, percent: f64) -> Self {
+        self.failure_threshold = percent;
+        self
+    }
+
+    pub fn uses_data_source(&self, data_source: &DataSource) -> bool {
+        self.libraries.iter().any(|l| &l.data_source == data_source)
+    }
+
+    /// Whether any library looks up untagged folders on TMDB.
+    pub fn resolves_untagged(&self) -> bool {
+        self.libraries.iter().any(|l| l.resolve_untagged)
+    }
+
+    /// Whether any library writes IDs back to its folders.
+    pub fn tags_folders(&self) -> bool {
+        self.libraries.iter().any(|l| l.tag_folders != FolderTagging::Off)
+    }
+
+    /// Whether a TMDB client is needed, for a TMDB library or to resolve untagged folders.
+    pub fn needs_tmdb(&self) -> bool {
+        self.uses_data_source(&DataSource::Tmdb) || self.resolves_untagged()
+    }
+
+    /// Builds the implicit `default` library from the top-level settings.
+    pub fn default_library(&self) -> LibraryProfile {
+        LibraryProfile {
+            name: DEFAULT_LIBRARY_NAME.to_string(),
+            scan_path: self.scan_path.clone(),
+            video_filename: self.video_filename.clone(),
+            trailer_folder: self.trailer_folder.clone(),
+            schedule: self.schedule.clone(),
+            data_source: self.data_source.clone(),
+            imdb_id_regex: self.imdb_id_regex.clone(),
+            tmdb_id_regex: self.tmdb_id_regex.clone(),
+            imdb_id_regexes: self.imdb_id_regexes.clone(),
+            tmdb_id_regexes: self.tmdb_id_regexes.clone(),
+            id_presets: self.id_presets.clone(),
+            tv_folders: self.tv_folders.clone(),
+            movie_folders: self.movie_folders.clone(),
+            scan_depth: self.scan_depth,
+            resolve_untagged: self.resolve_untagged,
+            tag_folders: self.tag_folders,
+            tag_dry_run: self.tag_dry_run,
+            id_patterns: CompiledIdPatterns::default(),
+        }
+    }
+}
+
+/// The compiled ID patterns of a [LibraryProfile]. They follow from its settings, so they are
+/// left out when profiles are compared.
+#[derive(Debug, Default, Clone)]
+struct CompiledIdPatterns(OnceCell<Result<LibraryIdPatterns, String>>);
+
+impl PartialEq for CompiledIdPatterns {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// A media library scanned by this process. Each library has its own root, folders,
+/// ID patterns, output layout and schedule, while sharing clients and caches with the others.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LibraryProfile {
+    pub name: String,
+    pub scan_path: String,
+    pub video_filename: String,
+    pub trailer_folder: String,
+    pub schedule: Option<String>,
+    pub data_source: DataSource,
+    pub imdb_id_regex: String,
+    pub tmdb_id_regex: String,
+    pub imdb_id_regexes: Vec<String>,
+    pub tmdb_id_regexes: Vec<String>,
+    pub id_presets: Vec<IdPreset>,
+    pub tv_folders: Vec<String>,
+    pub movie_folders: Vec<String>,
+    /// How many levels below each TV and movie folder to look for title folders. Folders that
+    /// match an ID pattern are titles wherever they are, and are not looked into.
+    pub scan_depth: usize,
+    pub resolve_untagged: bool,
+    pub tag_folders: FolderTagging,
+    pub tag_dry_run: bool,
+    /// The ID patterns, compiled on first use. Only the `with_` methods reset them.
+    id_patterns: CompiledIdPatterns,
+}
+
+impl LibraryProfile {
+    /// Creates a library with the default file name, trailer folder and ID patterns.
+    pub fn new(name: impl Into<String>, scan_path: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            scan_path: scan_path.into(),
+            video_filename: DEFAULT_VIDEO_FILENAME.to_string(),
+            trailer_folder: DEFAULT_TRAILER_FOLDER.to_string(),
+            schedule: None,
+            data_source: DataSource::Imdb,
+            imdb_id_regex: DEFAULT_IMDB_ID_REGEX.to_string(),
+            tmdb_id_regex: DEFAULT_TMDB_ID_REGEX.to_string(),
+            imdb_id_regexes: vec![],
+            tmdb_id_regexes: vec![],
+            id_presets: vec![],
+            tv_folders: vec![],
+            movie_folders: vec![],
+            scan_depth: DEFAULT_SCAN_DEPTH,
+            resolve_untagged: false,
+            tag_folders: FolderTagging::Off,
+            tag_dry_run: false,
+            id_patterns: CompiledIdPatterns::default(),
+        }
+    }
+
+    pub fn with_movie_folders<I: IntoIterator<Item = S>, S: Into<String>>(mut self, folders: I) -> Self {
+        self.movie_folders = folders.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_tv_folders<I: IntoIterator<Item = S>, S: Into<String>>(mut self, folders: I) -> Self {
+        self.tv_folders = folders.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_data_source(mut self, data_source: DataSource) -> Self {
+        self.data_source = data_source;
+        self
+    }
+
+    pub fn with_video_filename(mut self, video_filename: impl Into<String>) -> Self {
+        self.video_filename = video_filename.into();
+        self
+    }
+
+    pub fn with_trailer_folder(mut self, trailer_folder: impl Into<String>) -> Self {
+        self.trailer_folder = trailer_folder.into();
+        self
+    }
+
+    pub fn with_schedule(mut self, schedule: impl Into<String>) -> Self {
+        self.schedule = Some(schedule.into());
+        self
+    }
+
+    pub fn with_scan_depth(mut self, scan_depth: usize) -> Self {
+        self.scan_depth = scan_depth;
+        self
+    }
+
+    pub fn with_id_regexes(mut self, imdb_id_regex: impl Into<String>, tmdb_id_regex: impl Into<String>) -> Self {
+        self.imdb_id_regex = imdb_id_regex.into();
+        self.tmdb_id_regex = tmdb_id_regex.into();
+        self.id_patterns = CompiledIdPatterns::default();
+        self
+    }
+
+    /// Adds patterns that are tried after the single IMDb and TMDB ID regexes.
+    pub fn with_additional_id_regexes<I, T>(mut self, imdb_id_regexes: I, tmdb_id_regexes: T) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+        T: IntoIterator,
+        T::Item: Into<String>,
+    {
+        self.imdb_id_regexes = imdb_id_regexes.into_iter().map(Into::into).collect();
+        self.tmdb_id_regexes = tmdb_id_regexes.into_iter().map(Into::into).collect();
+        self.id_patterns = CompiledIdPatterns::default();
+        self
+    }
+
+    pub fn with_id_presets<I: IntoIterator<Item = IdPreset>>(mut self, presets: I) -> Self {
+        self.id_presets = presets.into_iter().collect();
+        self.id_patterns = CompiledIdPatterns::default();
+        self
+    }
+
+    /// The library's compiled ID patterns: its own patterns, then those of its presets. They are
+    /// compiled once, when the configuration is validated or else on first use.
+    pub fn id_patterns(&self) -> anyhow::Result<&LibraryIdPatterns> {
+        self.id_patterns
+            .0
+            .get_or_init(|| self.compile_id_patterns().map_err(|e| format!("{:#}", e)))
+            .as_ref()
+            .map_err(|e| anyhow!("{} (library '{}')", e, self.name))
+    }
+
+    fn compile_id_patterns(&self) -> anyhow::Result<LibraryIdPatterns> {
+        fn patterns<'a>(single: &'a str, list: &'a [String], presets: &[IdPreset], preset: fn(&IdPreset) -> &'static str) -> Vec<&'a str> {
+            std::iter::once(single)
+                .chain(list.iter().map(String::as_str))
+                .chain(presets.iter().map(preset))
+                .filter(|pattern| !pattern.trim().is_empty())
+                .collect()
+        }
+
+        Ok(LibraryIdPatterns {
+            imdb: IdPatterns::new(IdKind::Imdb, &patterns(&self.imdb_id_regex, &self.imdb_id_regexes, &self.id_presets, IdPreset::imdb_pattern))?,
+            tmdb: IdPatterns::new(IdKind::Tmdb, &patterns(&self.tmdb_id_regex, &self.tmdb_id_regexes, &self.id_presets, IdPreset::tmdb_pattern))?,
+        })
+    }
+
+    /// Looks up folders without an ID tag on TMDB by their `Title (Year)` name.
+    pub fn with_resolve_untagged(mut self, resolve_untagged: bool) -> Self {
+        self.resolve_untagged = resolve_untagged;
+        self
+    }
+
+    /// Writes the IDs of resolved folders back to the library, or only logs what would be written
+    /// when `dry_run` is set.
+    pub fn with_tag_folders(mut self, tag_folders: FolderTagging, dry_run: bool) -> Self {
+        self.tag_folders = tag_folders;
+        self.tag_dry_run = dry_run;
+        self
+    }
+}
+
+/// A library entry as written in the config file. Any setting left out falls back to the
+/// top-level value.
+#[derive(Debug, Default, serde::Deserialize)]
+struct LibrarySettings {
+    name: String,
+    scan_path: Option<String>,
+    video_filename: Option<String>,
+    trailer_folder: Option<String>,
+    schedule: Option<String>,
+    #[serde(default, deserialize_with = "case_insensitive_optional_datasource")]
+    data_source: Option<DataSource>,
+    imdb_id_regex: Option<String>,
+    tmdb_id_regex: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_patterns")]
+    imdb_id_regexes: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "deserialize_optional_patterns")]
+    tmdb_id_regexes: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "deserialize_optional_id_presets")]
+    id_presets: Option<Vec<IdPreset>>,
+    tv_folders: Option<Vec<String>>,
+    movie_folders: Option<Vec<String>>,
+    scan_depth: Option<usize>,
+    resolve_untagged: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_optional_folder_tagging")]
+    tag_folders: Option<FolderTagging>,
+    tag_dry_run: Option<bool>,
+}
+
+impl LibrarySettings {
+    fn resolve(self, defaults: &LibraryProfile) -> LibraryProfile {
+        LibraryProfile {
+            name: self.name.trim().to_string(),
+            scan_path: self.scan_path.unwrap_or_else(|| defaults.scan_path.clone()),
+            video_filename: self.video_filename.unwrap_or_else(|| defaults.video_filename.clone()),
+            trailer_folder: self.trailer_folder.unwrap_or_else(|| defaults.trailer_folder.clone()),
+            schedule: self.schedule.or_else(|| defaults.schedule.clone()),
+            data_source: self.data_source.unwrap_or_else(|| defaults.data_source.clone()),
+            imdb_id_regex: self.imdb_id_regex.unwrap_or_else(|| defaults.imdb_id_regex.clone()),
+            tmdb_id_regex: self.tmdb_id_regex.unwrap_or_else(|| defaults.tmdb_id_regex.clone()),
+            imdb_id_regexes: self.imdb_id_regexes.unwrap_or_else(|| defaults.imdb_id_regexes.clone()),
+            tmdb_id_regexes: self.tmdb_id_regexes.unwrap_or_else(|| defaults.tmdb_id_regexes.clone()),
+            id_presets: self.id_presets.unwrap_or_else(|| defaults.id_presets.clone()),
+            tv_folders: self.tv_folders.unwrap_or_else(|| defaults.tv_folders.clone()),
+            movie_folders: self.movie_folders.unwrap_or_else(|| defaults.movie_folders.clone()),
+            scan_depth: self.scan_depth.unwrap_or(defaults.scan_depth),
+            resolve_untagged: self.resolve_untagged.unwrap_or(defaults.resolve_untagged),
+            tag_folders: self.tag_folders.unwrap_or(defaults.tag_folders),
+            tag_dry_run: self.tag_dry_run.unwrap_or(defaults.tag_dry_run),
+            id_patterns: CompiledIdPatterns::default(),
+        }
+    }
+}
+
+fn read_secret(path: &Path, name: &str) -> anyhow::Result<String> {
+    let value = std::fs::read_to_string(path).with_context(|| format!("Failed to read {} from {:?}", name, path))?;
+    Ok(value.trim().to_string())
+}
+
+/// Adds the [SECRET_SETTINGS] that are given as files. A `_FILE` variable takes precedence over
+/// the config file, and can't be combined with the plain variable. A Docker secret is only used
+/// when neither variable nor the config file set the value.
+fn add_secret_files(mut builder: ConfigBuilder<DefaultState>) -> anyhow::Result<ConfigBuilder<DefaultState>> {
+    let secrets_dir = std::env::var(SECRETS_DIR_ENV).unwrap_or_else(|_| DEFAULT_SECRETS_DIR.to_string());
+
+    for setting in SECRET_SETTINGS {
+        let env_name = format!("TRAILERFIN_{}", setting.to_uppercase());
+        let file_env_name = format!("{}_FILE", env_name);
+
+        if let Ok(path) = std::env::var(&file_env_name) {
+            if std::env::var_os(&env_name).is_some() {
+                return Err(anyhow!("Only one of {} and {} can be set", env_name, file_env_name));
+            }
+            builder = builder.set_override(setting, read_secret(Path::new(&path), &file_env_name)?)?;
+            continue;
+        }
+
+        let docker_secret = [env_name.to_lowercase(), setting.to_string()]
+            .into_iter()
+            .map(|name| Path::new(&secrets_dir).join(name))
+            .find(|path| path.is_file());
+        if let Some(path) = docker_secret {
+            builder = builder.set_default(setting, read_secret(&path, &env_name)?)?;
+        }
+    }
+
+    Ok(builder)
 }
 
 #[derive(Debug)]
@@ -85,93 +853,233 @@ pub struct ConfigurationProvider;
 
 impl ConfigurationProvider {
     pub fn load_config() -> anyhow::Result<Arc<AppConfig>> {
-        let config = Config::builder()
+        let mut builder = Config::builder()
             .set_default("scan_path", "/mnt/plex")?
-            .set_default("user_agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 Chrome/124.0.0.0")?
+            .set_default("user_agent", DEFAULT_USER_AGENT)?
             .set_default("should_schedule", false)?
-            .set_default("video_filename", "video1.strm")?
+            .set_default("video_filename", DEFAULT_VIDEO_FILENAME)?
+            .set_default("trailer_folder", DEFAULT_TRAILER_FOLDER)?
+            .set_default("scan_depth", DEFAULT_SCAN_DEPTH as u64)?
+            .set_default("resolve_untagged", false)?
+            .set_default("tag_folders", "off")?
+            .set_default("tag_dry_run", false)?
             .set_default("threads", 1)?
-            .set_default("cache_path", "/config")?
+            .set_default("cache_path", DEFAULT_CACHE_PATH)?
             .set_default("data_source", "IMDB")?
-            .set_default("imdb_rate_limit", "30/minute")?
-            .set_default("tmdb_rate_limit", "50/second")?
-            .set_default("imdb_id_regex", r"\{imdb-(tt\d+)}")?
-            .set_default("tmdb_id_regex", r"\{tmdb-(\d+)}")?
+            .set_default("imdb_rate_limit", DEFAULT_IMDB_RATE_LIMIT)?
+            .set_default("tmdb_rate_limit", DEFAULT_TMDB_RATE_LIMIT)?
+            .set_default("imdb_id_regex", DEFAULT_IMDB_ID_REGEX)?
+            .set_default("tmdb_id_regex", DEFAULT_TMDB_ID_REGEX)?
+            .set_default("failure_threshold", 0)?
+            .set_default("imdb_base_url", DEFAULT_IMDB_BASE_URL)?
+            .set_default("tmdb_base_url", DEFAULT_TMDB_BASE_URL)?
+            .set_default("adaptive_rate_limit", true)?
+            .set_default("imdb_circuit_breaker_threshold", DEFAULT_CIRCUIT_BREAKER_THRESHOLD)?
+            .set_default("imdb_circuit_breaker_cooldown_secs", DEFAULT_CIRCUIT_BREAKER_COOLDOWN_SECS)?
+            .set_default("http_max_retries", DEFAULT_MAX_RETRIES)?
+            .set_default("http_retry_backoff_ms", DEFAULT_RETRY_BACKOFF_MS)?
+            .set_default("http_max_backoff_ms", DEFAULT_MAX_BACKOFF_MS)?
+            .set_default("http_retry_statuses", DEFAULT_RETRY_STATUSES.map(|s| s.to_string()).join(","))?
+            .set_default("http_connect_timeout_secs", DEFAULT_CONNECT_TIMEOUT_SECS)?
+            .set_default("http_request_timeout_secs", DEFAULT_REQUEST_TIMEOUT_SECS)?
+            .set_default("user_agent_rotation", "per_session")?
+            .set_default("imdb_accept_language", DEFAULT_ACCEPT_LANGUAGE)?
+            .set_default("imdb_accept", DEFAULT_ACCEPT)?
+            .set_default("imdb_cookie_jar", true)?
+            .set_default("http_cache", true)?
+            .set_default("imdb_gallery_cache_ttl_secs", DEFAULT_IMDB_GALLERY_CACHE_TTL_SECS)?;
+
+        builder = add_secret_files(builder)?;
+
+        if let Ok(config_file) = std::env::var(CONFIG_FILE_ENV) {
+            builder = builder.add_source(
+                config::File::new(&config_file, config::FileFormat::Json).required(true)
+            );
+        }
+
+        let config = builder
             .add_source(
                 config::Environment::with_prefix("TRAILERFIN")
             )
             .build()?;
 
-        let config: AppConfig = config.try_deserialize()?;
+        let library_settings: Vec<LibrarySettings> = match config.get("libraries") {
+            Ok(libraries) => libraries,
+            Err(ConfigError::NotFound(_)) => vec![],
+            Err(e) => return Err(anyhow!("Invalid libraries section in {}: {}", CONFIG_FILE_ENV, e)),
+        };
 
-        if config.threads < 1 {
-            return Err(anyhow::anyhow!("TRAILERFIN_THREADS must be greater than or equal to 1"));
+        let mut config: AppConfig = config.try_deserialize()?;
+
+        let defaults = config.default_library();
+        config.libraries = library_settings
+            .into_iter()
+            .map(|settings| Arc::new(settings.resolve(&defaults)))
+            .collect();
+
+        let config = Self::validate_config(config)?;
+
+        info!("Loaded configuration: {:?}", config);
+
+        Ok(config)
+    }
+
+    /// Reads only the cache path, from the config file and environment like [Self::load_config],
+    /// for commands such as `undo-tags` that don't need the rest of the configuration to be valid.
+    pub fn load_cache_path() -> anyhow::Result<String> {
+        let mut builder = Config::builder().set_default("cache_path", DEFAULT_CACHE_PATH)?;
+        if let Ok(config_file) = std::env::var(CONFIG_FILE_ENV) {
+            builder = builder.add_source(
+                config::File::new(&config_file, config::FileFormat::Json).required(true)
+            );
+        }
+
+        let cache_path: String = builder
+            .add_source(config::Environment::with_prefix("TRAILERFIN"))
+            .build()?
+            .get("cache_path")?;
+        if cache_path.trim().is_empty() {
+            return Err(anyhow!("TRAILERFIN_CACHE_PATH must be set and cannot be empty"));
         }
+        Ok(cache_path)
+    }
 
-        if config.scan_path.is_empty() {
-            return Err(anyhow::anyhow!("TRAILERFIN_SCAN_PATH must be set and cannot be empty"));
+    /// Validates a configuration built in code or loaded from the environment. When it has no
+    /// libraries, the top-level settings become a single `default` library.
+    pub fn validate_config(mut config: AppConfig) -> anyhow::Result<Arc<AppConfig>> {
+        if config.threads < 1 {
+            return Err(anyhow::anyhow!("TRAILERFIN_THREADS must be greater than or equal to 1"));
         }
 
         if config.user_agent.trim().is_empty() {
             return Err(anyhow::anyhow!("TRAILERFIN_USER_AGENT must be set and cannot be empty"));
         }
 
-        if config.video_filename.trim().is_empty() {
-            return Err(anyhow!("TRAILERFIN_VIDEO_FILENAME must be set and cannot be empty"));
+        if config.user_agents.iter().chain([&config.imdb_accept_language, &config.imdb_accept]).any(|v| v.contains(['\r', '\n'])) {
+            return Err(anyhow!("TRAILERFIN_USER_AGENTS, TRAILERFIN_IMDB_ACCEPT_LANGUAGE and TRAILERFIN_IMDB_ACCEPT cannot contain line breaks"));
         }
 
-        if config.should_schedule {
-            match config.schedule.as_deref().map(str::trim) {
-                Some("") | None => {
-                    return Err(anyhow!("TRAILERFIN_SCHEDULE must be set and not empty when scheduling is enabled"));
-                }
-                _ => {}
-            }
+        if config.cache_path.trim().is_empty() {
+            return Err(anyhow!("TRAILERFIN_CACHE_PATH must be set and cannot be empty"));
         }
 
-        if config.data_source == DataSource::Tmdb {
-            match config.tmdb_api_key.as_deref().map(str::trim) {
-                Some("") | None => {
-                    return Err(anyhow!("TRAILERFIN_TMDB_API_KEY must be set and not empty when datasource is set to TMDB"));
-                }
-                _ => {}
-            }
+        if config.http_retry_backoff_ms > config.http_max_backoff_ms {
+            return Err(anyhow!("TRAILERFIN_HTTP_RETRY_BACKOFF_MS must not be greater than TRAILERFIN_HTTP_MAX_BACKOFF_MS"));
         }
 
-        if config.cache_path.trim().is_empty() {
-            return Err(anyhow!("TRAILERFIN_CACHE_PATH must be set and cannot be empty"));
+        validate_base_url(&config.imdb_base_url, "TRAILERFIN_IMDB_BASE_URL")?;
+        validate_base_url(&config.tmdb_base_url, "TRAILERFIN_TMDB_BASE_URL")?;
+        for mirror in &config.imdb_mirrors {
+            validate_base_url(mirror, "TRAILERFIN_IMDB_MIRRORS")?;
+        }
+        for mirror in &config.tmdb_mirrors {
+            validate_base_url(mirror, "TRAILERFIN_TMDB_MIRRORS")?;
         }
 
-        // Validate regex patterns
-        if config.imdb_id_regex.trim().is_empty() {
-            return Err(anyhow!("TRAILERFIN_IMDB_ID_REGEX must be set and cannot be empty"));
+        if let Some(language) = &config.tmdb_language {
+            if !TMDB_LANGUAGE_REGEX.is_match(language) {
+                return Err(anyhow!("TRAILERFIN_TMDB_LANGUAGE must be an ISO 639-1 language code such as 'en' or 'en-US', got '{}'", language));
+            }
         }
-        if config.tmdb_id_regex.trim().is_empty() {
-            return Err(anyhow!("TRAILERFIN_TMDB_ID_REGEX must be set and cannot be empty"));
+        if let Some(region) = &config.tmdb_region {
+            if !TMDB_REGION_REGEX.is_match(region) {
+                return Err(anyhow!("TRAILERFIN_TMDB_REGION must be an ISO 3166-1 country code such as 'US', got '{}'", region));
+            }
         }
 
-        // Test regex compilation
-        if let Err(e) = Regex::new(&config.imdb_id_regex) {
-            return Err(anyhow!("Invalid TRAILERFIN_IMDB_ID_REGEX pattern: {}", e));
+        validate_proxy(&config.imdb_proxy, "TRAILERFIN_IMDB_PROXY")?;
+        validate_proxy(&config.tmdb_proxy, "TRAILERFIN_TMDB_PROXY")?;
+        validate_file(&config.imdb_ca_certificate, "TRAILERFIN_IMDB_CA_CERTIFICATE")?;
+        validate_file(&config.tmdb_ca_certificate, "TRAILERFIN_TMDB_CA_CERTIFICATE")?;
+
+        if config.record_fixtures.is_some() && config.replay_fixtures.is_some() {
+            return Err(anyhow!("Only one of TRAILERFIN_RECORD_FIXTURES and TRAILERFIN_REPLAY_FIXTURES can be set"));
         }
-        if let Err(e) = Regex::new(&config.tmdb_id_regex) {
-            return Err(anyhow!("Invalid TRAILERFIN_TMDB_ID_REGEX pattern: {}", e));
+        if let Some(dir) = &config.replay_fixtures {
+            _ = validate_path(dir, "TRAILERFIN_REPLAY_FIXTURES")?;
         }
 
-        _ = validate_path(&config.scan_path, "TRAILERFIN_SCAN_PATH")?;
-        _ = validate_path(&config.cache_path, "TRAILERFIN_CACHE_PATH")?;
+        if !(0.0..=100.0).contains(&config.failure_threshold) {
+            return Err(anyhow!("TRAILERFIN_FAILURE_THRESHOLD must be a percentage between 0 and 100"));
+        }
 
-        if config.tv_folders.is_empty() && config.movie_folders.is_empty() {
-            return Err(anyhow!("At least one of TRAILERFIN_TV_FOLDERS or TRAILERFIN_MOVIE_FOLDERS must be set and non-empty"));
+        if config.libraries.is_empty() {
+            config.libraries = vec![Arc::new(config.default_library())];
         }
 
-        for folder in config.tv_folders.iter().chain(config.movie_folders.iter()) {
-            let full_path = Path::new(&config.scan_path).join(folder);
-            validate_path(full_path.to_str().unwrap(), &format!("subfolder: {}", folder))?;
+        let mut names = HashSet::new();
+        for library in &config.libraries {
+            if library.name.is_empty() {
+                return Err(anyhow!("Every library in {} must have a non-empty name", CONFIG_FILE_ENV));
+            }
+            if !names.insert(library.name.as_str()) {
+                return Err(anyhow!("Duplicate library name: {}", library.name));
+            }
+            validate_library(library, config.should_schedule)?;
         }
 
-        info!("Loaded configuration: {:?}", config);
+        if config.uses_data_source(&DataSource::Tmdb) {
+            match config.tmdb_api_key.as_ref().map(|key| key.expose().trim()) {
+                Some("") | None => {
+                    return Err(anyhow!("TRAILERFIN_TMDB_API_KEY must be set and not empty when datasource is set to TMDB"));
+                }
+                _ => {}
+            }
+        } else if config.resolves_untagged() && config.tmdb_api_key.as_ref().is_none_or(|key| key.expose().trim().is_empty()) {
+            return Err(anyhow!("TRAILERFIN_TMDB_API_KEY must be set and not empty when TRAILERFIN_RESOLVE_UNTAGGED is enabled"));
+        }
+
+        _ = validate_path(&config.cache_path, "TRAILERFIN_CACHE_PATH")?;
 
         Ok(Arc::new(config))
     }
-}
\ No newline at end of file
+}
+
+fn validate_library(library: &LibraryProfile, should_schedule: bool) -> anyhow::Result<()> {
+    let name = &library.name;
+
+    if library.scan_path.is_empty() {
+        return Err(anyhow::anyhow!("TRAILERFIN_SCAN_PATH must be set and cannot be empty (library '{}')", name));
+    }
+
+    if library.video_filename.trim().is_empty() {
+        return Err(anyhow!("TRAILERFIN_VIDEO_FILENAME must be set and cannot be empty (library '{}')", name));
+    }
+
+    if library.trailer_folder.trim().is_empty() {
+        return Err(anyhow!("TRAILERFIN_TRAILER_FOLDER must be set and cannot be empty (library '{}')", name));
+    }
+
+    if library.scan_depth < 1 {
+        return Err(anyhow!("TRAILERFIN_SCAN_DEPTH must be at least 1 (library '{}')", name));
+    }
+
+    if should_schedule {
+        match library.schedule.as_deref().map(str::trim) {
+            Some("") | None => {
+                return Err(anyhow!("TRAILERFIN_SCHEDULE must be set and not empty when scheduling is enabled (library '{}')", name));
+            }
+            _ => {}
+        }
+    }
+
+    // Compiles the ID patterns once, for every scan of the library
+    library.id_patterns()?;
+
+    if library.tag_folders != FolderTagging::Off && !library.resolve_untagged {
+        return Err(anyhow!("TRAILERFIN_TAG_FOLDERS needs TRAILERFIN_RESOLVE_UNTAGGED to be enabled (library '{}')", name));
+    }
+
+    _ = validate_path(&library.scan_path, "TRAILERFIN_SCAN_PATH")?;
+
+    if library.tv_folders.is_empty() && library.movie_folders.is_empty() {
+        return Err(anyhow!("At least one of TRAILERFIN_TV_FOLDERS or TRAILERFIN_MOVIE_FOLDERS must be set and non-empty (library '{}')", name));
+    }
+
+    for folder in library.tv_folders.iter().chain(library.movie_folders.iter()) {
+        let full_path = Path::new(&library.scan_path).join(folder);
+        validate_path(full_path.to_str().unwrap(), &format!("subfolder: {}", folder))?;
+    }
+
+    Ok(())
+}
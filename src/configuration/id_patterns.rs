@@ -0,0 +1,145 @@
+//! Patterns that find IMDb and TMDB IDs in folder names.
+
+use anyhow::anyhow;
+use regex::Regex;
+
+/// Built-in ID patterns for the naming conventions of common media servers and managers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IdPreset {
+    /// `{imdb-tt0133093}` and `{tmdb-603}`.
+    Plex,
+    /// `[imdbid-tt0133093]` and `[tmdbid-603]`.
+    Jellyfin,
+    /// `[imdbid=tt0133093]` and `[tmdbid=603]`.
+    Emby,
+    /// Radarr's `{imdb-tt0133093}` and `{tmdb-603}` folder name tokens.
+    Radarr,
+}
+
+impl IdPreset {
+    pub const ALL: [IdPreset; 4] = [IdPreset::Plex, IdPreset::Jellyfin, IdPreset::Emby, IdPreset::Radarr];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IdPreset::Plex => "plex",
+            IdPreset::Jellyfin => "jellyfin",
+            IdPreset::Emby => "emby",
+            IdPreset::Radarr => "radarr",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| preset.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn imdb_pattern(&self) -> &'static str {
+        match self {
+            IdPreset::Plex | IdPreset::Radarr => r"\{imdb-(?P<imdb>tt\d+)\}",
+            IdPreset::Jellyfin => r"\[imdbid-(?P<imdb>tt\d+)\]",
+            IdPreset::Emby => r"\[imdbid=(?P<imdb>tt\d+)\]",
+        }
+    }
+
+    pub fn tmdb_pattern(&self) -> &'static str {
+        match self {
+            IdPreset::Plex | IdPreset::Radarr => r"\{tmdb-(?P<tmdb>\d+)\}",
+            IdPreset::Jellyfin => r"\[tmdbid-(?P<tmdb>\d+)\]",
+            IdPreset::Emby => r"\[tmdbid=(?P<tmdb>\d+)\]",
+        }
+    }
+
+    /// The tag this naming convention puts in a folder name, e.g. `[imdbid-tt0133093]`.
+    pub fn tag(&self, kind: IdKind, id: &str) -> String {
+        let name = match kind {
+            IdKind::Imdb => "imdb",
+            IdKind::Tmdb => "tmdb",
+        };
+        match self {
+            IdPreset::Plex | IdPreset::Radarr => format!("{{{}-{}}}", name, id),
+            IdPreset::Jellyfin => format!("[{}id-{}]", name, id),
+            IdPreset::Emby => format!("[{}id={}]", name, id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IdKind {
+    Imdb,
+    Tmdb,
+}
+
+impl IdKind {
+    /// The name of the capture group that holds the ID, e.g. `(?P<imdb>tt\d+)`.
+    pub fn group(&self) -> &'static str {
+        match self {
+            IdKind::Imdb => "imdb",
+            IdKind::Tmdb => "tmdb",
+        }
+    }
+
+    fn setting(&self) -> &'static str {
+        match self {
+            IdKind::Imdb => "TRAILERFIN_IMDB_ID_REGEX",
+            IdKind::Tmdb => "TRAILERFIN_TMDB_ID_REGEX",
+        }
+    }
+}
+
+/// The compiled patterns for one kind of ID, tried in order. A pattern puts the ID in the group
+/// named after the kind, or else in its first capture group.
+#[derive(Debug, Clone)]
+pub struct IdPatterns {
+    kind: IdKind,
+    patterns: Vec<Regex>,
+}
+
+impl IdPatterns {
+    pub fn new<S: AsRef<str>>(kind: IdKind, patterns: &[S]) -> anyhow::Result<Self> {
+        if patterns.is_empty() {
+            return Err(anyhow!("{} must be set and cannot be empty", kind.setting()));
+        }
+
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                let pattern = pattern.as_ref();
+                let regex = Regex::new(pattern).map_err(|e| anyhow!("Invalid {} pattern '{}': {}", kind.setting(), pattern, e))?;
+                let has_group = regex.capture_names().any(|name| name == Some(kind.group())) || regex.captures_len() > 1;
+                if !has_group {
+                    return Err(anyhow!(
+                        "{} pattern '{}' needs a capture group for the ID, such as (?P<{}>...)",
+                        kind.setting(), pattern, kind.group()
+                    ));
+                }
+                Ok(regex)
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self { kind, patterns })
+    }
+
+    /// The ID found in `haystack` by the first pattern that matches.
+    pub fn find<'h>(&self, haystack: &'h str) -> Option<&'h str> {
+        self.patterns.iter().find_map(|regex| {
+            let captures = regex.captures(haystack)?;
+            captures.name(self.kind.group()).or_else(|| captures.get(1)).map(|id| id.as_str())
+        })
+    }
+
+    pub fn patterns(&self) -> impl Iterator<Item = &str> {
+        self.patterns.iter().map(Regex::as_str)
+    }
+}
+
+impl PartialEq for IdPatterns {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.patterns().eq(other.patterns())
+    }
+}
+
+/// The compiled IMDb and TMDB patterns of a library.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibraryIdPatterns {
+    pub imdb: IdPatterns,
+    pub tmdb: IdPatterns,
+}
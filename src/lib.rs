@@ -1,3 +1,38 @@
+//! Trailerfin keeps trailer `.strm` files in media libraries up to date.
+//!
+//! Besides the `trailerfin_rust` binary, the crate can be embedded in other services through
+//! [app::App]:
+//!
+//! ```no_run
+//! use std::path::Path;
+//! use trailerfin_rust::app::{App, TitleId};
+//! use trailerfin_rust::configuration::configuration_provider::{AppConfig, ConfigurationProvider, LibraryProfile};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let config = ConfigurationProvider::validate_config(
+//!     AppConfig::new("/var/lib/trailerfin")
+//!         .with_library(LibraryProfile::new("movies", "/mnt/media").with_movie_folders(["Movies"])),
+//! )?;
+//! let app = App::from_config(config)?;
+//!
+//! let mut events = app.subscribe();
+//! tokio::spawn(async move {
+//!     while let Ok(event) = events.recv().await {
+//!         println!("{event:?}");
+//!     }
+//! });
+//!
+//! let summary = app.scan().await;
+//! println!("{summary}");
+//!
+//! let outcome = app.refresh_path(Path::new("/mnt/media/Movies/The Matrix (1999) {imdb-tt0133093}")).await?;
+//! println!("{outcome}");
+//! let trailer = app.resolve(&TitleId::Imdb("tt0133093".to_string())).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod app;
 pub mod configuration;
 pub mod scrapers;
 pub mod schedulers;
@@ -1,12 +1,22 @@
+use std::process::ExitCode;
 use std::sync::Arc;
-use tracing::{debug, info};
+use tracing::{debug, error, info};
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::fmt::time::FormatTime;
-use trailerfin_rust::caching::initialize_caching;
-use trailerfin_rust::configuration::configuration_provider::{AppConfig, ConfigurationProvider};
-use trailerfin_rust::request_clients::initialize_request_clients;
-use trailerfin_rust::schedulers::{get_scraping_scheduler, initialize_schedulers};
-use trailerfin_rust::scrapers::{get_scraper, initialize_scrapers};
+use trailerfin_rust::app::App;
+use trailerfin_rust::caching::{create_database, create_title_match_cache, create_tmdb_to_imdb_cache};
+use trailerfin_rust::configuration::configuration_provider::ConfigurationProvider;
+use trailerfin_rust::scrapers::folder_tagger::FolderTagger;
+use trailerfin_rust::scrapers::scrape_error::ScrapeErrorKind;
+
+/// Process exit codes for run-once mode, so cron jobs and Kubernetes Jobs can alert on them.
+mod exit_code {
+    pub const FAILURE: u8 = 1;
+    pub const CONFIG_ERROR: u8 = 2;
+    pub const CACHE_ERROR: u8 = 3;
+    pub const PARTIAL_FAILURE: u8 = 4;
+    pub const TMDB_AUTH_INVALID: u8 = 5;
+}
 
 struct LocalTimer;
 
@@ -24,32 +34,108 @@ fn init_tracing() {
         .init();
 }
 
+/// Reverts the folder tagging recorded in the undo log. Only the cache path is read, so tags can
+/// be undone even when the rest of the configuration no longer loads.
+fn undo_tags(dry_run: bool) -> ExitCode {
+    let cache_path = match ConfigurationProvider::load_cache_path() {
+        Ok(cache_path) => cache_path,
+        Err(e) => {
+            error!("Failed to load the cache path: {:#}", e);
+            return ExitCode::from(exit_code::CONFIG_ERROR);
+        }
+    };
+
+    let tagger = FolderTagger::new(&cache_path);
+    match tagger.undo(dry_run) {
+        Ok(undone) => {
+            let verb = if dry_run { "Would undo" } else { "Undid" };
+            info!("{} {} folder tagging changes from {:?}", verb, undone.len(), tagger.undo_log());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            error!("Failed to undo folder tagging: {:#}", e);
+            ExitCode::from(exit_code::FAILURE)
+        }
+    }
+}
+
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     init_tracing();
 
-    let app_config = ConfigurationProvider::load_config().expect("Failed to load configuration");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("undo-tags") {
+        let dry_run = args.iter().any(|arg| arg == "--dry-run");
+        return undo_tags(dry_run);
+    }
+
+    let app_config = match ConfigurationProvider::load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Failed to load configuration: {:#}", e);
+            return ExitCode::from(exit_code::CONFIG_ERROR);
+        }
+    };
 
-    initialize_services(&app_config);
+    let caches = create_database(&app_config)
+        .and_then(|db| Ok((create_tmdb_to_imdb_cache(db.clone())?, create_title_match_cache(db)?)));
+    let (cache, title_match_cache) = match caches {
+        Ok(caches) => caches,
+        Err(e) => {
+            error!("Failed to open cache in {}: {:#}", app_config.cache_path, e);
+            return ExitCode::from(exit_code::CACHE_ERROR);
+        }
+    };
+
+    let app = match App::builder(app_config.clone())
+        .with_tmdb_to_imdb_cache(cache)
+        .with_title_match_cache(title_match_cache)
+        .build() {
+        Ok(app) => Arc::new(app),
+        Err(e) => {
+            error!("Failed to initialize services: {:#}", e);
+            return ExitCode::from(exit_code::FAILURE);
+        }
+    };
+    debug!("Services initialized successfully");
+
+    if app_config.replay_fixtures.is_none() {
+        if let Err(e) = app.check_tmdb_credential().await {
+            error!("{:#}; check TRAILERFIN_TMDB_API_KEY", e);
+            return ExitCode::from(exit_code::TMDB_AUTH_INVALID);
+        }
+    }
 
     if app_config.should_schedule {
         info!("Starting in scheduled mode...");
-        get_scraping_scheduler()
-            .start_scheduler(app_config)
-            .await
-            .expect("Failed to start scheduler");
-    } else {
-        info!("Scheduling disabled: Running Once...");
-        get_scraper()
-            .scan_and_refresh_trailers(&app_config).await
-            .expect("Failed to scan and refresh trailers");
+        if let Err(e) = app.start_scheduler().await {
+            error!("Failed to start scheduler: {:#}", e);
+            return ExitCode::from(exit_code::FAILURE);
+        }
+        return ExitCode::SUCCESS;
     }
-}
 
-fn initialize_services(app_config: &Arc<AppConfig>) {
-    initialize_caching(app_config.clone());
-    initialize_request_clients(app_config.clone());
-    initialize_scrapers(app_config.clone());
-    initialize_schedulers();
-    debug!("Services initialized successfully");
+    info!("Scheduling disabled: Running Once...");
+    let summary = app.scan().await;
+    if summary.errors_of(ScrapeErrorKind::TmdbAuthInvalid) > 0 {
+        error!("TMDB rejected the API key for {} titles", summary.errors_of(ScrapeErrorKind::TmdbAuthInvalid));
+        ExitCode::from(exit_code::TMDB_AUTH_INVALID)
+    } else if !summary.failed_libraries.is_empty() {
+        error!("{} of {} libraries failed to scan", summary.failed_libraries.len(), app_config.libraries.len());
+        ExitCode::from(exit_code::PARTIAL_FAILURE)
+    } else if summary.cut_short.is_some() {
+        error!("Scan was cut short, {} titles were not processed", summary.skipped);
+        ExitCode::from(exit_code::PARTIAL_FAILURE)
+    } else if summary.exceeds_failure_threshold(app_config.failure_threshold) {
+        error!(
+            "{} of {} titles failed ({:.1}%), above the failure threshold of {}%",
+            summary.failed(),
+            summary.total,
+            summary.failure_rate(),
+            app_config.failure_threshold
+        );
+        ExitCode::from(exit_code::PARTIAL_FAILURE)
+    } else {
+        ExitCode::SUCCESS
+    }
 }
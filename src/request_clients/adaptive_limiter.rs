@@ -0,0 +1,121 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use governor::clock::DefaultClock;
+use governor::state::{InMemoryState, NotKeyed};
+use governor::{Quota, RateLimiter};
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// The lowest fraction of the configured rate the limiter slows down to.
+const MIN_FACTOR: f64 = 1.0 / 32.0;
+/// Consecutive successful requests needed before the rate is raised again.
+const RECOVERY_SUCCESSES: u32 = 10;
+/// Fraction of the configured rate that is added back after each recovery step.
+const RECOVERY_STEP: f64 = 0.1;
+
+/// A rate limiter that never exceeds the configured quota, halves its rate whenever the
+/// server signals throttling, and slowly climbs back to the quota while requests succeed.
+#[derive(Debug)]
+pub struct AdaptiveRateLimiter {
+    name: String,
+    ceiling: RateLimiter<NotKeyed, InMemoryState, DefaultClock>,
+    ceiling_interval: Duration,
+    adaptive: bool,
+    state: Mutex<AdaptiveState>,
+}
+
+#[derive(Debug)]
+struct AdaptiveState {
+    factor: f64,
+    successes: u32,
+    next_slot: Instant,
+}
+
+impl AdaptiveRateLimiter {
+    /// Creates a limiter for `quota`. With `adaptive` off it behaves like a plain quota limiter.
+    pub fn new(name: impl Into<String>, quota: Quota, adaptive: bool) -> Self {
+        Self {
+            name: name.into(),
+            ceiling: RateLimiter::direct(quota),
+            ceiling_interval: quota.replenish_interval(),
+            adaptive,
+            state: Mutex::new(AdaptiveState {
+                factor: 1.0,
+                successes: 0,
+                next_slot: Instant::now(),
+            }),
+        }
+    }
+
+    /// Waits until the next request may be sent.
+    pub async fn until_ready(&self) {
+        self.ceiling.until_ready().await;
+
+        let slot = {
+            let mut state = self.state.lock().unwrap();
+            if state.factor >= 1.0 {
+                return;
+            }
+            let interval = self.ceiling_interval.div_f64(state.factor);
+            let slot = state.next_slot.max(Instant::now());
+            state.next_slot = slot + interval;
+            slot
+        };
+
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// Called when the server answered 429/503 or served a block page.
+    pub fn on_throttled(&self) {
+        if !self.adaptive {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.successes = 0;
+        if state.factor <= MIN_FACTOR {
+            return;
+        }
+
+        state.factor = (state.factor / 2.0).max(MIN_FACTOR);
+        warn!("{} is throttling requests, slowing down to {}", self.name, self.describe(state.factor));
+    }
+
+    /// Called after a response that did not signal throttling.
+    pub fn on_success(&self) {
+        if !self.adaptive {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if state.factor >= 1.0 {
+            return;
+        }
+
+        state.successes += 1;
+        if state.successes >= RECOVERY_SUCCESSES {
+            state.successes = 0;
+            state.factor = (state.factor + RECOVERY_STEP).min(1.0);
+            info!("{} is recovering, speeding up to {}", self.name, self.describe(state.factor));
+        }
+    }
+
+    /// The rate currently allowed, in requests per second.
+    pub fn current_rate(&self) -> f64 {
+        self.state.lock().unwrap().factor / self.ceiling_interval.as_secs_f64()
+    }
+
+    /// The configured maximum rate, in requests per second.
+    pub fn ceiling_rate(&self) -> f64 {
+        1.0 / self.ceiling_interval.as_secs_f64()
+    }
+
+    fn describe(&self, factor: f64) -> String {
+        format!(
+            "{:.1}/minute ({:.0}% of the configured {:.1}/minute)",
+            factor * self.ceiling_rate() * 60.0,
+            factor * 100.0,
+            self.ceiling_rate() * 60.0
+        )
+    }
+}
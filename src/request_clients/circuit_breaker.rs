@@ -0,0 +1,105 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use crate::request_clients::request_errors::error::Error;
+
+pub const DEFAULT_CIRCUIT_BREAKER_THRESHOLD: u32 = 5;
+pub const DEFAULT_CIRCUIT_BREAKER_COOLDOWN_SECS: u64 = 300;
+
+/// Stops sending requests to a service after `threshold` consecutive failures.
+///
+/// Once open, requests fail immediately with [Error::CircuitOpen] until `cooldown` has passed.
+/// Then a single probe request is let through: if it succeeds the circuit closes again,
+/// otherwise it stays open for another cool-down. A threshold of `0` disables the breaker.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: String,
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    probing: bool,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: impl Into<String>, threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            name: name.into(),
+            threshold,
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::new("disabled", 0, Duration::ZERO)
+    }
+
+    /// Checks whether a request may be sent now.
+    pub fn acquire(&self) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        let Some(open_until) = state.open_until else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+        if now < open_until || state.probing {
+            return Err(Error::CircuitOpen {
+                service: self.name.clone(),
+                retry_in: open_until.saturating_duration_since(now),
+            });
+        }
+
+        info!("{} cool-down has passed, sending a probe request", self.name);
+        state.probing = true;
+        Ok(())
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.open_until.is_some() {
+            info!("{} probe request succeeded, resuming requests", self.name);
+        }
+        *state = BreakerState::default();
+    }
+
+    pub fn record_failure(&self) {
+        if self.threshold == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures += 1;
+
+        if state.probing || state.consecutive_failures >= self.threshold {
+            if state.open_until.is_none() || state.probing {
+                warn!(
+                    "{} failed {} times in a row, pausing requests for {:?}",
+                    self.name, state.consecutive_failures, self.cooldown
+                );
+            }
+            state.open_until = Some(Instant::now() + self.cooldown);
+            state.probing = false;
+        }
+    }
+
+    /// Describes why requests are paused, while the circuit is open.
+    pub fn open_reason(&self) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        let open_until = state.open_until?;
+        let remaining = open_until.saturating_duration_since(Instant::now());
+        if remaining.is_zero() && !state.probing {
+            return None;
+        }
+
+        Some(format!(
+            "{} requests paused for {:?} after {} consecutive failures",
+            self.name, remaining, state.consecutive_failures
+        ))
+    }
+}
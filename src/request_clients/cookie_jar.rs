@@ -0,0 +1,132 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Context};
+use cookie_store::CookieStore;
+use reqwest_cookie_store::CookieStoreMutex;
+use tracing::{debug, warn};
+
+/// How often the jar is written at most while responses keep setting cookies.
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Cookies kept across runs in a JSON file, so requests continue an existing session instead
+/// of starting a fresh one every time. Only persistent, unexpired cookies are written.
+#[derive(Debug)]
+pub struct PersistentCookieJar {
+    path: PathBuf,
+    store: Arc<CookieStoreMutex>,
+    /// Whether cookies changed since the jar was last saved.
+    changed: AtomicBool,
+    last_saved: Mutex<Option<Instant>>,
+    /// Serializes writes of the temporary file and its rename.
+    save_lock: Mutex<()>,
+}
+
+impl PersistentCookieJar {
+    /// Loads the cookies saved at `path`. A missing file starts an empty jar; an unreadable one
+    /// is logged and replaced on the next save.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let store = match Self::read(&path) {
+            Ok(store) => store,
+            Err(e) => {
+                warn!("Starting with an empty cookie jar, could not read {:?}: {:#}", path, e);
+                CookieStore::default()
+            }
+        };
+
+        Self {
+            path,
+            store: Arc::new(CookieStoreMutex::new(store)),
+            changed: AtomicBool::new(false),
+            last_saved: Mutex::new(None),
+            save_lock: Mutex::new(()),
+        }
+    }
+
+    fn read(path: &Path) -> anyhow::Result<CookieStore> {
+        if !path.exists() {
+            return Ok(CookieStore::default());
+        }
+
+        let file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+        cookie_store::serde::json::load(BufReader::new(file)).map_err(|e| anyhow!("{}", e))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The store to hand to the HTTP client.
+    pub fn store(&self) -> Arc<CookieStoreMutex> {
+        self.store.clone()
+    }
+
+    /// Names and values of the cookies currently in the jar.
+    pub fn cookies(&self) -> Vec<(String, String)> {
+        let store = self.store.lock().expect("cookie store lock poisoned");
+        store.iter_unexpired().map(|c| (c.name().to_string(), c.value().to_string())).collect()
+    }
+
+    /// Records that a response set cookies, to be written by [Self::save_if_due].
+    pub fn mark_changed(&self) {
+        self.changed.store(true, Ordering::SeqCst);
+    }
+
+    /// Saves changed cookies on the blocking thread pool, unless the jar was saved less than
+    /// [SAVE_INTERVAL] ago. Changes left unsaved are written by a later call or on drop.
+    pub async fn save_if_due(self: &Arc<Self>) {
+        if !self.changed.load(Ordering::SeqCst) || !self.claim_save() {
+            return;
+        }
+
+        let jar = Arc::clone(self);
+        match tokio::task::spawn_blocking(move || jar.save()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("Failed to save cookies to {:?}: {:#}", self.path, e),
+            Err(e) => warn!("Failed to save cookies to {:?}: {}", self.path, e),
+        }
+    }
+
+    fn claim_save(&self) -> bool {
+        let mut last_saved = self.last_saved.lock().unwrap_or_else(|e| e.into_inner());
+        if last_saved.is_some_and(|at| at.elapsed() < SAVE_INTERVAL) {
+            return false;
+        }
+        *last_saved = Some(Instant::now());
+        true
+    }
+
+    /// Writes the jar to disk, replacing the previous file atomically. This blocks.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let _guard = self.save_lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.changed.store(false, Ordering::SeqCst);
+        self.write().inspect_err(|_| self.changed.store(true, Ordering::SeqCst))
+    }
+
+    fn write(&self) -> anyhow::Result<()> {
+        let temp_path = self.path.with_extension("json.tmp");
+        {
+            let mut file = File::create(&temp_path).with_context(|| format!("Failed to create {:?}", temp_path))?;
+            let store = self.store.lock().map_err(|_| anyhow!("cookie store lock poisoned"))?;
+            cookie_store::serde::json::save(&store, &mut file).map_err(|e| anyhow!("Failed to write {:?}: {}", temp_path, e))?;
+        }
+        std::fs::rename(&temp_path, &self.path).with_context(|| format!("Failed to replace {:?}", self.path))?;
+
+        debug!("Saved cookies to {:?}", self.path);
+        Ok(())
+    }
+}
+
+impl Drop for PersistentCookieJar {
+    fn drop(&mut self) {
+        if self.changed.load(Ordering::SeqCst) {
+            if let Err(e) = self.save() {
+                warn!("Failed to save cookies to {:?}: {:#}", self.path, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Recorded responses for deterministic offline runs.
+//!
+//! A [RecordingExecutor] passes requests on to a live executor and saves every response to a
+//! [FixtureSet]. A [ReplayExecutor] answers the same requests from that set without touching the
+//! network, so a scan, or a test, can be reproduced exactly.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use anyhow::Context;
+use futures::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+use url::Url;
+use crate::request_clients::client_settings::SECRET_QUERY_PARAMS;
+use crate::request_clients::rate_limited_client::{parse_json_response, Executor, RateLimitedClient};
+use crate::request_clients::request_errors::error::Error;
+
+/// Response headers that describe the connection rather than the content, and cookies.
+const SKIPPED_HEADERS: [&str; 5] = ["connection", "content-encoding", "content-length", "set-cookie", "transfer-encoding"];
+
+/// A recorded response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    /// The request, as path and query without secrets.
+    pub request: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Fixture {
+    pub async fn record(request: String, res: reqwest::Response) -> Result<Self, Error> {
+        let status = res.status().as_u16();
+        let headers = res
+            .headers()
+            .iter()
+            .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = res.text().await?;
+
+        Ok(Self { request, status, headers, body })
+    }
+
+    pub fn to_response(&self) -> Result<reqwest::Response, Error> {
+        let mut builder = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        let res = builder.body(self.body.clone()).map_err(|err| Error::Response {
+            source: Box::new(err),
+        })?;
+        Ok(res.into())
+    }
+}
+
+/// A directory of recorded responses, one file per request. Requests are matched by path and
+/// query, so fixtures replay against any base URL or mirror.
+#[derive(Debug)]
+pub struct FixtureSet {
+    dir: PathBuf,
+}
+
+impl FixtureSet {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The path and query of `url`, without secrets such as the TMDB API key.
+    pub fn request_key(url: &str) -> String {
+        let Ok(url) = Url::parse(url) else {
+            return url.to_string();
+        };
+
+        // Filter the raw query instead of re-encoding it, so keys read like the requested URL.
+        let query: Vec<&str> = url
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .filter(|pair| !SECRET_QUERY_PARAMS.contains(&pair.split('=').next().unwrap_or_default()))
+            .collect();
+
+        if query.is_empty() {
+            url.path().to_string()
+        } else {
+            format!("{}?{}", url.path(), query.join("&"))
+        }
+    }
+
+    /// A readable file name for `key`, made unique with a short hash.
+    fn path_for(&self, key: &str) -> PathBuf {
+        let mut slug: String = key
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+            .collect::<String>()
+            .split('-')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        slug.truncate(80);
+
+        let digest = Sha256::digest(key.as_bytes());
+        let mut name = slug;
+        name.push('-');
+        for byte in &digest[..4] {
+            _ = write!(name, "{:02x}", byte);
+        }
+        name.push_str(".json");
+        self.dir.join(name)
+    }
+
+    pub fn load(&self, url: &str) -> anyhow::Result<Option<Fixture>> {
+        let path = self.path_for(&Self::request_key(url));
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = std::fs::read_to_string(&path).with_context(|| format!("Failed to read fixture {:?}", path))?;
+        let fixture = serde_json::from_str(&contents).with_context(|| format!("Invalid fixture {:?}", path))?;
+        Ok(Some(fixture))
+    }
+
+    pub fn save(&self, fixture: &Fixture) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.dir).with_context(|| format!("Failed to create fixture directory {:?}", self.dir))?;
+        let path = self.path_for(&fixture.request);
+        std::fs::write(&path, serde_json::to_string_pretty(fixture)?)
+            .with_context(|| format!("Failed to write fixture {:?}", path))?;
+        debug!("Recorded {} to {:?}", fixture.request, path);
+        Ok(())
+    }
+}
+
+/// Appends serialized `params` to the query of `url`, the way a live executor sends them.
+fn url_with_params<P: Serialize>(url: &str, params: &P) -> Result<String, Error> {
+    let query = serde_urlencoded::to_string(params).map_err(|err| Error::Request {
+        source: Box::new(err),
+    })?;
+
+    Ok(match (query.is_empty(), url.contains('?')) {
+        (true, _) => url.to_string(),
+        (false, true) => format!("{}&{}", url, query),
+        (false, false) => format!("{}?{}", url, query),
+    })
+}
+
+/// Sends requests through `inner` and saves every response to a [FixtureSet].
+#[derive(Debug, Clone)]
+pub struct RecordingExecutor<E = RateLimitedClient> {
+    inner: E,
+    fixtures: Arc<FixtureSet>,
+}
+
+impl<E: Executor> RecordingExecutor<E> {
+    pub fn new(inner: E, fixtures: FixtureSet) -> Self {
+        Self { inner, fixtures: Arc::new(fixtures) }
+    }
+
+    async fn record(&self, url: &str) -> Result<reqwest::Response, Error> {
+        let res = self.inner.execute_raw(url).await?;
+        let fixture = Fixture::record(FixtureSet::request_key(url), res).await?;
+        if let Err(e) = self.fixtures.save(&fixture) {
+            warn!("Failed to record {}: {:#}", fixture.request, e);
+        }
+        fixture.to_response()
+    }
+}
+
+impl<E: Executor> Executor for RecordingExecutor<E> {
+    fn execute<T, P>(&self, url: &str, params: P) -> BoxFuture<Result<T, Error>>
+    where
+        T: DeserializeOwned + Send + 'static,
+        P: Serialize + Send + 'static,
+    {
+        let url = url_with_params(url, &params);
+        Box::pin(async move { parse_json_response(self.record(&url?).await?).await })
+    }
+
+    fn execute_raw(&self, url: &str) -> BoxFuture<Result<reqwest::Response, Error>> {
+        let url = url.to_string();
+        Box::pin(async move { self.record(&url).await })
+    }
+
+    fn report_throttled(&self) {
+        self.inner.report_throttled();
+    }
+
+    fn evict_cached(&self, url: &str) -> BoxFuture<()> {
+        self.inner.evict_cached(url)
+    }
+}
+
+/// Answers requests from a [FixtureSet] without touching the network. Requests without a
+/// recorded response fail with [Error::MissingFixture].
+#[derive(Debug, Clone)]
+pub struct ReplayExecutor {
+    fixtures: Arc<FixtureSet>,
+}
+
+impl ReplayExecutor {
+    pub fn new(fixtures: FixtureSet) -> Self {
+        Self { fixtures: Arc::new(fixtures) }
+    }
+
+    fn replay(&self, url: &str) -> Result<reqwest::Response, Error> {
+        let fixture = self.fixtures.load(url).map_err(|err| Error::Response {
+            source: err.into(),
+        })?;
+
+        match fixture {
+            Some(fixture) => fixture.to_response(),
+            None => Err(Error::MissingFixture { request: FixtureSet::request_key(url) }),
+        }
+    }
+}
+
+impl Executor for ReplayExecutor {
+    fn execute<T, P>(&self, url: &str, params: P) -> BoxFuture<Result<T, Error>>
+    where
+        T: DeserializeOwned + Send + 'static,
+        P: Serialize + Send + 'static,
+    {
+        let res = url_with_params(url, &params).and_then(|url| self.replay(&url));
+        Box::pin(async move { parse_json_response(res?).await })
+    }
+
+    fn execute_raw(&self, url: &str) -> BoxFuture<Result<reqwest::Response, Error>> {
+        let res = self.replay(url);
+        Box::pin(async move { res })
+    }
+}
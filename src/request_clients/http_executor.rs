@@ -0,0 +1,71 @@
+use futures::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::request_clients::fixtures::{RecordingExecutor, ReplayExecutor};
+use crate::request_clients::rate_limited_client::{Executor, RateLimitedClient};
+use crate::request_clients::request_errors::error::Error;
+
+/// The executor behind the IMDb and TMDB clients: live requests, live requests that are recorded
+/// to fixtures, or replayed fixtures.
+#[derive(Debug, Clone)]
+pub enum HttpExecutor {
+    Live(RateLimitedClient),
+    Recording(RecordingExecutor),
+    Replaying(ReplayExecutor),
+}
+
+impl From<RateLimitedClient> for HttpExecutor {
+    fn from(executor: RateLimitedClient) -> Self {
+        Self::Live(executor)
+    }
+}
+
+impl From<RecordingExecutor> for HttpExecutor {
+    fn from(executor: RecordingExecutor) -> Self {
+        Self::Recording(executor)
+    }
+}
+
+impl From<ReplayExecutor> for HttpExecutor {
+    fn from(executor: ReplayExecutor) -> Self {
+        Self::Replaying(executor)
+    }
+}
+
+impl Executor for HttpExecutor {
+    fn execute<T, P>(&self, url: &str, params: P) -> BoxFuture<Result<T, Error>>
+    where
+        T: DeserializeOwned + Send + 'static,
+        P: Serialize + Send + 'static,
+    {
+        match self {
+            Self::Live(executor) => executor.execute(url, params),
+            Self::Recording(executor) => executor.execute(url, params),
+            Self::Replaying(executor) => executor.execute(url, params),
+        }
+    }
+
+    fn execute_raw(&self, url: &str) -> BoxFuture<Result<reqwest::Response, Error>> {
+        match self {
+            Self::Live(executor) => executor.execute_raw(url),
+            Self::Recording(executor) => executor.execute_raw(url),
+            Self::Replaying(executor) => executor.execute_raw(url),
+        }
+    }
+
+    fn report_throttled(&self) {
+        match self {
+            Self::Live(executor) => executor.report_throttled(),
+            Self::Recording(executor) => executor.report_throttled(),
+            Self::Replaying(executor) => executor.report_throttled(),
+        }
+    }
+
+    fn evict_cached(&self, url: &str) -> BoxFuture<()> {
+        match self {
+            Self::Live(executor) => executor.evict_cached(url),
+            Self::Recording(executor) => executor.evict_cached(url),
+            Self::Replaying(executor) => executor.evict_cached(url),
+        }
+    }
+}
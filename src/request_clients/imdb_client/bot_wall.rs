@@ -0,0 +1,41 @@
+use reqwest::StatusCode;
+use crate::request_clients::raw_response::RawResponse;
+
+/// Header set by the AWS WAF in front of IMDb when it challenges or blocks a request.
+const WAF_ACTION_HEADER: &str = "x-amzn-waf-action";
+
+/// Fragments of the challenge and CAPTCHA pages IMDb serves instead of real content.
+const CHALLENGE_MARKERS: [&str; 6] = [
+    "awswafintegration",
+    "challenge.js",
+    "captcha-container",
+    "gokuprops",
+    "robot check",
+    "enter the characters you see below",
+];
+
+/// Recognizes bot walls, WAF challenges and CAPTCHA pages. Returns why the response looks
+/// like a block, or `None` for a regular response.
+pub fn detect_block(res: &RawResponse) -> Option<String> {
+    if let Some(action) = res.headers.get(WAF_ACTION_HEADER) {
+        return Some(format!("WAF action '{}'", action.to_str().unwrap_or("unknown")));
+    }
+
+    if res.status == StatusCode::ACCEPTED && res.body.trim().is_empty() {
+        return Some("empty 202 response".to_string());
+    }
+
+    // Real IMDb pages are large; only inspect small bodies or error pages for challenge markers.
+    if res.status == StatusCode::FORBIDDEN || res.status == StatusCode::METHOD_NOT_ALLOWED || res.body.len() < 16 * 1024 {
+        let body = res.body.to_lowercase();
+        if let Some(marker) = CHALLENGE_MARKERS.iter().find(|m| body.contains(*m)) {
+            return Some(format!("challenge page ({})", marker));
+        }
+    }
+
+    if res.status == StatusCode::FORBIDDEN {
+        return Some("access forbidden".to_string());
+    }
+
+    None
+}
@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// Number of most recent extractions compared against the earlier success rate.
+pub const DRIFT_WINDOW: usize = 20;
+
+/// Extractions needed before the window, so a few early misses don't count as drift.
+pub const DRIFT_BASELINE: u64 = 20;
+
+/// A drop to below this fraction of the earlier success rate is reported as drift.
+pub const DRIFT_RATIO: f64 = 0.5;
+
+/// The kinds of IMDb pages that data is extracted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PageKind {
+    VideoGallery,
+    VideoPage,
+}
+
+impl fmt::Display for PageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PageKind::VideoGallery => write!(f, "video gallery"),
+            PageKind::VideoPage => write!(f, "video page"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PageStats {
+    by_strategy: BTreeMap<&'static str, u64>,
+    attempts: u64,
+    successes: u64,
+    recent: VecDeque<bool>,
+    drifting: bool,
+}
+
+impl PageStats {
+    fn record(&mut self, strategy: Option<&'static str>) {
+        if let Some(strategy) = strategy {
+            *self.by_strategy.entry(strategy).or_default() += 1;
+            self.successes += 1;
+        }
+        self.attempts += 1;
+        self.recent.push_back(strategy.is_some());
+        if self.recent.len() > DRIFT_WINDOW {
+            self.recent.pop_front();
+        }
+    }
+
+    /// The success rate before and within the recent window, once both have enough samples.
+    fn rates(&self) -> Option<(f64, f64)> {
+        let recent_successes = self.recent.iter().filter(|s| **s).count() as u64;
+        let earlier_attempts = self.attempts - self.recent.len() as u64;
+        if self.recent.len() < DRIFT_WINDOW || earlier_attempts < DRIFT_BASELINE {
+            return None;
+        }
+
+        let earlier = (self.successes - recent_successes) as f64 / earlier_attempts as f64;
+        let recent = recent_successes as f64 / self.recent.len() as f64;
+        Some((earlier, recent))
+    }
+}
+
+/// Counts which extraction strategy succeeded for each IMDb page, and warns when the success
+/// rate drops sharply, which usually means IMDb changed its markup.
+#[derive(Debug, Default)]
+pub struct ExtractionStats {
+    pages: Mutex<HashMap<PageKind, PageStats>>,
+}
+
+impl ExtractionStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an extraction from a `page`, with the strategy that found the data, or `None`
+    /// when every strategy came up empty.
+    pub fn record(&self, page: PageKind, strategy: Option<&'static str>) {
+        let mut pages = self.pages.lock().unwrap();
+        let stats = pages.entry(page).or_default();
+        stats.record(strategy);
+
+        let Some((earlier, recent)) = stats.rates() else {
+            return;
+        };
+        let drifting = recent < earlier * DRIFT_RATIO;
+
+        if drifting && !stats.drifting {
+            warn!(
+                "IMDb {} extraction success dropped from {:.0}% to {:.0}% over the last {} pages; IMDb has probably changed its markup",
+                page,
+                earlier * 100.0,
+                recent * 100.0,
+                DRIFT_WINDOW
+            );
+        } else if !drifting && stats.drifting {
+            info!("IMDb {} extraction has recovered to {:.0}%", page, recent * 100.0);
+        }
+        stats.drifting = drifting;
+    }
+
+    /// How often `strategy` found the data on a `page`.
+    pub fn successes(&self, page: PageKind, strategy: &str) -> u64 {
+        let pages = self.pages.lock().unwrap();
+        pages.get(&page).and_then(|stats| stats.by_strategy.get(strategy).copied()).unwrap_or(0)
+    }
+
+    /// How often no strategy found anything on a `page`.
+    pub fn misses(&self, page: PageKind) -> u64 {
+        let pages = self.pages.lock().unwrap();
+        pages.get(&page).map_or(0, |stats| stats.attempts - stats.successes)
+    }
+
+    /// Whether the recent success rate of a `page` has dropped sharply.
+    pub fn is_drifting(&self, page: PageKind) -> bool {
+        let pages = self.pages.lock().unwrap();
+        pages.get(&page).is_some_and(|stats| stats.drifting)
+    }
+
+    /// Logs the success counts of every strategy.
+    pub fn log(&self) {
+        let pages = self.pages.lock().unwrap();
+        let mut kinds: Vec<_> = pages.keys().copied().collect();
+        kinds.sort();
+
+        for page in kinds {
+            let stats = &pages[&page];
+            let strategies = stats
+                .by_strategy
+                .iter()
+                .map(|(strategy, count)| format!("{strategy}: {count}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            info!("IMDb {} extraction: {} of {} succeeded ({})", page, stats.successes, stats.attempts, strategies);
+        }
+    }
+}
@@ -1,26 +1,40 @@
 use std::borrow::Cow;
 use anyhow::{anyhow, Result};
 use serde::de::DeserializeOwned;
-use crate::request_clients::rate_limited_client::{Executor, RateLimitedClient};
+use reqwest::StatusCode;
+use crate::request_clients::circuit_breaker::CircuitBreaker;
+use crate::request_clients::imdb_client::bot_wall::detect_block;
+use crate::request_clients::imdb_client::extraction_stats::ExtractionStats;
+use crate::request_clients::imdb_client::title_endpoints::TitleService;
+use crate::request_clients::imdb_client::video_gallery_endpoints::VideoGalleryService;
+use crate::request_clients::imdb_client::video_page_endpoints::VideoPageService;
+use crate::request_clients::mirrors::BaseUrls;
+use crate::request_clients::http_executor::HttpExecutor;
+use crate::request_clients::rate_limited_client::Executor;
+use crate::request_clients::raw_response::RawResponse;
 use crate::request_clients::request_errors::error::Error;
 
 #[derive(Debug)]
 pub struct ImdbRequestClient(
-    pub Client<RateLimitedClient>
+    pub Client<HttpExecutor>
 );
 
-const BASE_URL: &str = "https://www.imdb.com";
+pub const BASE_URL: &str = "https://www.imdb.com";
 
 pub struct ClientBuilder<E: Executor> {
     base_url: Cow<'static, str>,
-    executor: Option<E>
+    mirrors: Vec<String>,
+    executor: Option<E>,
+    circuit_breaker: Option<CircuitBreaker>,
 }
 
 impl<E: Executor> Default for ClientBuilder<E> {
     fn default() -> Self {
         Self {
             base_url: Cow::Borrowed(BASE_URL),
+            mirrors: vec![],
             executor: None,
+            circuit_breaker: None,
         }
     }
 }
@@ -35,36 +49,54 @@ impl<E: Executor> ClientBuilder<E> {
         self.base_url = value.into();
     }
 
-    pub fn with_executor(mut self, executor: E) -> Self {
-        self.executor = Some(executor);
+    /// Base URLs to fall back to, in order, when the base URL can't be reached.
+    pub fn with_mirrors<U: Into<String>>(mut self, mirrors: impl IntoIterator<Item = U>) -> Self {
+        self.mirrors = mirrors.into_iter().map(Into::into).collect();
         self
     }
 
-    pub fn set_executor(mut self, executor: E) {
-        self.executor = Some(executor);
+    pub fn with_executor(mut self, executor: impl Into<E>) -> Self {
+        self.executor = Some(executor.into());
+        self
+    }
+
+    pub fn set_executor(mut self, executor: impl Into<E>) {
+        self.executor = Some(executor.into());
+    }
+
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
     }
 
     pub fn build(self) -> Result<Client<E>> {
-        let base_url = self.base_url;
+        let base_urls = BaseUrls::new(self.base_url, self.mirrors);
         let executor = self.executor.ok_or_else(|| anyhow!("missing executor"))?;
+        let circuit_breaker = self.circuit_breaker.unwrap_or_else(CircuitBreaker::disabled);
 
         Ok(Client {
             executor,
-            base_url,
+            base_urls,
+            circuit_breaker,
+            extraction_stats: ExtractionStats::new(),
         })
     }
 }
 
 pub struct Client<E> {
     executor: E,
-    base_url: Cow<'static, str>,
+    base_urls: BaseUrls,
+    circuit_breaker: CircuitBreaker,
+    extraction_stats: ExtractionStats,
 }
 
 impl<E: std::fmt::Debug> std::fmt::Debug for Client<E> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct(stringify!(Client))
             .field("executor", &self.executor)
-            .field("base_url", &self.base_url)
+            .field("base_urls", &self.base_urls)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .field("extraction_stats", &self.extraction_stats)
             .finish()
     }
 }
@@ -78,7 +110,11 @@ impl<E: Executor> Client<E> {
 
 impl ImdbRequestClient {
     pub fn base_url(&self) -> &str {
-        &self.0.base_url
+        self.0.base_urls.primary()
+    }
+
+    pub fn base_urls(&self) -> &[String] {
+        self.0.base_urls.all()
     }
 
     pub async fn execute<T>(&self, _: &str) -> Result<T, Error>
@@ -89,10 +125,55 @@ impl ImdbRequestClient {
 
     }
 
-    pub async fn get_raw(&self, path: &str) -> Result<reqwest::Response, Error> {
-        let url = format!("{}{}", self.0.base_url, path);
-        self.0.executor
-            .execute_raw(&url)
-            .await
+    pub fn video_gallery(&self) -> VideoGalleryService<'_> {
+        VideoGalleryService { client: self }
+    }
+
+    pub fn video_page(&self) -> VideoPageService<'_> {
+        VideoPageService { client: self }
+    }
+
+    pub fn title(&self) -> TitleService<'_> {
+        TitleService { client: self }
+    }
+
+    /// Which extraction strategies have found data on IMDb pages so far.
+    pub fn extraction_stats(&self) -> &ExtractionStats {
+        &self.0.extraction_stats
+    }
+
+    /// Why IMDb requests are currently paused by the circuit breaker, if they are.
+    pub fn paused_reason(&self) -> Option<String> {
+        self.0.circuit_breaker.open_reason()
+    }
+
+    /// Fetches a page. Bot walls and challenge pages are returned as [Error::Blocked], and
+    /// repeated failures open the circuit breaker.
+    pub async fn get_raw(&self, path: &str) -> Result<RawResponse, Error> {
+        let breaker = &self.0.circuit_breaker;
+        breaker.acquire()?;
+
+        let result = self.0.base_urls
+            .request(path, |url| async move {
+                let res = self.0.executor.execute_raw(&url).await?;
+                let res = RawResponse::read(res).await?;
+                match detect_block(&res) {
+                    Some(reason) => {
+                        self.0.executor.report_throttled();
+                        self.0.executor.evict_cached(&url).await;
+                        Err(Error::Blocked { status: res.status.as_u16(), reason })
+                    }
+                    None => Ok(res),
+                }
+            })
+            .await;
+
+        match &result {
+            Ok(res) if res.status == StatusCode::TOO_MANY_REQUESTS || res.status.is_server_error() => breaker.record_failure(),
+            Ok(_) => breaker.record_success(),
+            Err(_) => breaker.record_failure(),
+        }
+
+        result
     }
 }
\ No newline at end of file
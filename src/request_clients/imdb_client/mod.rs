@@ -1 +1,7 @@
-pub mod imdb_request_client;
\ No newline at end of file
+pub mod bot_wall;
+pub mod extraction_stats;
+pub mod imdb_request_client;
+pub(crate) mod page_data;
+pub mod title_endpoints;
+pub mod video_gallery_endpoints;
+pub mod video_page_endpoints;
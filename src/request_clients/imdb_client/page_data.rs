@@ -0,0 +1,92 @@
+use once_cell::sync::Lazy;
+use scraper::{Html, Selector};
+use serde_json::Value;
+
+/// Nested JSON deeper than this is not searched.
+const MAX_DEPTH: usize = 32;
+
+static NEXT_DATA_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse("script#\\__NEXT_DATA__").expect("Invalid __NEXT_DATA__ selector")
+});
+static JSON_SCRIPT_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse("script[type=\"application/json\"]").expect("Invalid JSON script selector")
+});
+static JSON_LD_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse("script[type=\"application/ld+json\"]").expect("Invalid JSON-LD selector")
+});
+
+/// The data scripts embedded in an IMDb page.
+#[derive(Debug, Default)]
+pub(crate) struct PageData {
+    /// The raw body of `script#__NEXT_DATA__`.
+    pub next_data: Option<String>,
+    /// Every `application/json` script that parses, including `__NEXT_DATA__`.
+    pub json: Vec<Value>,
+    /// Every JSON-LD script that parses.
+    pub json_ld: Vec<Value>,
+}
+
+impl PageData {
+    pub fn parse(doc: &Html) -> Self {
+        let script_text = |selector: &Selector| -> Vec<String> {
+            doc.select(selector).map(|script| script.text().collect()).collect()
+        };
+        let parse_all = |scripts: Vec<String>| -> Vec<Value> {
+            scripts.iter().filter_map(|text| serde_json::from_str(text.trim()).ok()).collect()
+        };
+
+        Self {
+            next_data: script_text(&NEXT_DATA_SELECTOR).into_iter().next(),
+            json: parse_all(script_text(&JSON_SCRIPT_SELECTOR)),
+            json_ld: parse_all(script_text(&JSON_LD_SELECTOR)),
+        }
+    }
+
+    /// The first line of `__NEXT_DATA__`, which holds the whole object on IMDb's current pages.
+    pub fn next_data_first_line(&self) -> Option<Value> {
+        let line = self.next_data.as_deref()?.lines().next()?;
+        serde_json::from_str(line.trim()).ok()
+    }
+
+    /// All of `__NEXT_DATA__`, for pages that spread it over several lines.
+    pub fn next_data(&self) -> Option<Value> {
+        serde_json::from_str(self.next_data.as_deref()?.trim()).ok()
+    }
+}
+
+/// Calls `visit` on every object in `value`, depth first. Strings holding JSON, as GraphQL
+/// caches embed their responses, are parsed and searched as well.
+pub(crate) fn walk_objects(value: &Value, visit: &mut dyn FnMut(&serde_json::Map<String, Value>)) {
+    walk(value, visit, 0);
+}
+
+fn walk(value: &Value, visit: &mut dyn FnMut(&serde_json::Map<String, Value>), depth: usize) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+
+    match value {
+        Value::Object(map) => {
+            visit(map);
+            for child in map.values() {
+                walk(child, visit, depth + 1);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                walk(item, visit, depth + 1);
+            }
+        }
+        Value::String(text) if text.starts_with('{') || text.starts_with('[') => {
+            if let Ok(parsed) = serde_json::from_str::<Value>(text) {
+                walk(&parsed, visit, depth + 1);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A string, or the `value` of an object such as `{"value": "Trailer"}`.
+pub(crate) fn text_value(value: &Value) -> Option<&str> {
+    value.as_str().or_else(|| value.get("value")?.as_str())
+}
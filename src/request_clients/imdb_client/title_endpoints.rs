@@ -0,0 +1,98 @@
+use once_cell::sync::Lazy;
+use reqwest::StatusCode;
+use scraper::{Html, Selector};
+use serde::Deserialize;
+use crate::request_clients::imdb_client::imdb_request_client::ImdbRequestClient;
+use crate::request_clients::request_errors::error::Error;
+
+static JSON_LD_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse("script[type=\"application/ld+json\"]").expect("Invalid JSON-LD selector")
+});
+
+/// Basic metadata of an IMDb title.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TitleMetadata {
+    pub id: String,
+    pub title: String,
+    /// The schema.org type, e.g. `Movie` or `TVSeries`.
+    pub kind: String,
+    pub year: Option<i32>,
+    pub genres: Vec<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JsonLdTitle {
+    #[serde(rename = "@type")]
+    kind: String,
+    name: String,
+    #[serde(rename = "datePublished")]
+    date_published: Option<String>,
+    #[serde(default)]
+    genre: Genres,
+    description: Option<String>,
+}
+
+/// `genre` is a single string for titles with one genre.
+#[derive(Deserialize, Default)]
+#[serde(untagged)]
+enum Genres {
+    One(String),
+    Many(Vec<String>),
+    #[default]
+    None,
+}
+
+impl From<Genres> for Vec<String> {
+    fn from(genres: Genres) -> Self {
+        match genres {
+            Genres::One(genre) => vec![genre],
+            Genres::Many(genres) => genres,
+            Genres::None => vec![],
+        }
+    }
+}
+
+/// IMDb HTML-escapes quotes and ampersands inside its JSON-LD strings.
+fn unescape(text: &str) -> String {
+    text.replace("&apos;", "'").replace("&quot;", "\"").replace("&amp;", "&")
+}
+
+/// Parses the metadata of `imdb_id` from the JSON-LD block of its title page.
+pub fn parse_title_page(imdb_id: &str, html: &str) -> Option<TitleMetadata> {
+    let doc = Html::parse_document(html);
+
+    doc.select(&JSON_LD_SELECTOR)
+        .filter_map(|script| serde_json::from_str::<JsonLdTitle>(&script.text().collect::<String>()).ok())
+        .map(|ld| TitleMetadata {
+            id: imdb_id.to_string(),
+            title: unescape(&ld.name),
+            kind: ld.kind,
+            year: ld.date_published.and_then(|date| date.get(..4)?.parse().ok()),
+            genres: Vec::from(ld.genre).iter().map(|g| unescape(g)).collect(),
+            description: ld.description.as_deref().map(unescape),
+        })
+        .next()
+}
+
+pub struct TitleService<'a> {
+    pub(crate) client: &'a ImdbRequestClient,
+}
+
+impl<'a> TitleService<'a> {
+    /// The metadata of a title. `None` when IMDb doesn't know the title or the page has no
+    /// metadata block.
+    pub async fn get(&self, imdb_id: &str) -> Result<Option<TitleMetadata>, Error> {
+        let path = format!("/title/{}/", imdb_id);
+        let res = self.client.get_raw(&path).await?;
+
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !res.status().is_success() {
+            return Err(Error::UnexpectedStatus(res.status().as_u16()));
+        }
+
+        Ok(parse_title_page(imdb_id, res.text()))
+    }
+}
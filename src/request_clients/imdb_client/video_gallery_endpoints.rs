@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::StatusCode;
+use scraper::{ElementRef, Html, Selector};
+use serde_json::Value;
+use tracing::debug;
+use crate::request_clients::imdb_client::extraction_stats::PageKind;
+use crate::request_clients::imdb_client::imdb_request_client::ImdbRequestClient;
+use crate::request_clients::imdb_client::page_data::{text_value, walk_objects, PageData};
+use crate::request_clients::request_errors::error::Error;
+
+static VIDEO_LINK_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse("a[href*=\"/video/vi\"]").expect("Invalid video link selector")
+});
+static VIDEO_ID_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"/video/(vi\d+)").expect("Invalid video ID regex"));
+static VIDEO_ID_ONLY_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^vi\d+$").expect("Invalid video ID regex"));
+/// A video ID anywhere in the markup, in whatever form IMDb happens to link it.
+static VIDEO_MARKUP_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bvi\d+\b").expect("Invalid video markup regex"));
+static DURATION_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:(\d+):)?(\d{1,2}):(\d{2})$").expect("Invalid duration regex")
+});
+
+const DATE_FORMATS: [&str; 3] = ["%b %d, %Y", "%B %d, %Y", "%Y-%m-%d"];
+
+/// How far up from a video link to look for the card that holds its details.
+const MAX_CARD_DEPTH: usize = 4;
+
+/// What a gallery video is, as labelled by IMDb.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoKind {
+    Trailer,
+    Teaser,
+    Clip,
+    Featurette,
+    Interview,
+    Other,
+}
+
+impl VideoKind {
+    const LABELS: [(&'static str, VideoKind); 5] = [
+        ("trailer", VideoKind::Trailer),
+        ("teaser", VideoKind::Teaser),
+        ("clip", VideoKind::Clip),
+        ("featurette", VideoKind::Featurette),
+        ("interview", VideoKind::Interview),
+    ];
+
+    /// The kind for a label such as `Trailer`.
+    fn from_label(label: &str) -> Option<Self> {
+        let label = label.trim().to_lowercase();
+        Self::LABELS.iter().find(|(l, _)| label == *l).map(|(_, kind)| *kind)
+    }
+
+    /// The kind mentioned in a title such as `Official Trailer #2`.
+    fn from_title(title: &str) -> Option<Self> {
+        let title = title.to_lowercase();
+        Self::LABELS.iter().find(|(l, _)| title.contains(l)).map(|(_, kind)| *kind)
+    }
+}
+
+/// A video listed in a title's video gallery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoGalleryItem {
+    /// The IMDb video ID, e.g. `vi1032782617`.
+    pub id: String,
+    pub title: String,
+    pub kind: VideoKind,
+    pub duration: Option<Duration>,
+    pub date: Option<NaiveDate>,
+}
+
+impl VideoGalleryItem {
+    fn new(id: String) -> Self {
+        Self { id, title: String::new(), kind: VideoKind::Other, duration: None, date: None }
+    }
+}
+
+/// The ways videos are found on a gallery page, in the order they are tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoGalleryStrategy {
+    /// Links to video pages, with details from the card around each link.
+    Links,
+    /// Video objects in the page's JSON, including embedded GraphQL responses.
+    PageData,
+    /// Any video ID mentioned in the markup, without details.
+    RawIds,
+}
+
+impl VideoGalleryStrategy {
+    pub const ALL: [VideoGalleryStrategy; 3] = [
+        VideoGalleryStrategy::Links,
+        VideoGalleryStrategy::PageData,
+        VideoGalleryStrategy::RawIds,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VideoGalleryStrategy::Links => "links",
+            VideoGalleryStrategy::PageData => "page_data",
+            VideoGalleryStrategy::RawIds => "raw_ids",
+        }
+    }
+
+    fn extract(&self, html: &str, doc: &Html) -> Vec<VideoGalleryItem> {
+        match self {
+            VideoGalleryStrategy::Links => items_from_links(doc),
+            VideoGalleryStrategy::PageData => items_from_page_data(&PageData::parse(doc)),
+            VideoGalleryStrategy::RawIds => {
+                let mut items: Vec<VideoGalleryItem> = vec![];
+                for captures in VIDEO_ID_REGEX.captures_iter(html) {
+                    if !items.iter().any(|item| item.id == captures[1]) {
+                        items.push(VideoGalleryItem::new(captures[1].to_string()));
+                    }
+                }
+                items
+            }
+        }
+    }
+}
+
+/// The videos of a gallery page, and the strategy that found them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoGallery {
+    pub items: Vec<VideoGalleryItem>,
+    pub strategy: Option<VideoGalleryStrategy>,
+}
+
+/// Parses the videos of a gallery page, in page order, trying each [VideoGalleryStrategy] in turn.
+/// Each video is listed once, even when the page links to it several times.
+pub fn parse_video_gallery(html: &str) -> VideoGallery {
+    let doc = Html::parse_document(html);
+
+    VideoGalleryStrategy::ALL
+        .iter()
+        .map(|strategy| (strategy, strategy.extract(html, &doc)))
+        .find(|(_, items)| !items.is_empty())
+        .map(|(strategy, items)| VideoGallery { items, strategy: Some(*strategy) })
+        .unwrap_or(VideoGallery { items: vec![], strategy: None })
+}
+
+/// The videos linked from gallery cards, with the details shown on each card.
+fn items_from_links(doc: &Html) -> Vec<VideoGalleryItem> {
+    let mut items: Vec<VideoGalleryItem> = vec![];
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut labelled: Vec<bool> = vec![];
+
+    for link in doc.select(&VIDEO_LINK_SELECTOR) {
+        let Some(id) = link.value().attr("href").and_then(|href| VIDEO_ID_REGEX.captures(href)).map(|c| c[1].to_string()) else {
+            continue;
+        };
+        let position = *positions.entry(id.clone()).or_insert_with(|| {
+            items.push(VideoGalleryItem::new(id));
+            labelled.push(false);
+            items.len() - 1
+        });
+        let item = &mut items[position];
+
+        let text = link.text().map(str::trim).filter(|t| !t.is_empty() && !DURATION_REGEX.is_match(t)).collect::<Vec<_>>().join(" ");
+        if item.title.is_empty() && !text.is_empty() {
+            item.title = text;
+        }
+
+        for fragment in card(link).text().map(str::trim).filter(|t| !t.is_empty()) {
+            if let Some(duration) = parse_duration(fragment) {
+                item.duration.get_or_insert(duration);
+            } else if let Some(kind) = VideoKind::from_label(fragment) {
+                item.kind = kind;
+                labelled[position] = true;
+            } else if let Some(date) = parse_date(fragment) {
+                item.date.get_or_insert(date);
+            }
+        }
+
+        if item.title.is_empty() {
+            if let Some(label) = link.value().attr("aria-label") {
+                item.title = label.trim().to_string();
+            }
+        }
+    }
+
+    for (item, labelled) in items.iter_mut().zip(labelled) {
+        if !labelled {
+            item.kind = VideoKind::from_title(&item.title).unwrap_or(VideoKind::Other);
+        }
+    }
+
+    items
+}
+
+/// Objects with a video ID in the page's JSON, e.g.
+/// `{"id": "vi1", "name": {"value": "Trailer"}, "contentType": {"displayName": {"value": "Trailer"}}, "runtime": {"value": 146}}`.
+fn items_from_page_data(data: &PageData) -> Vec<VideoGalleryItem> {
+    let mut items: Vec<VideoGalleryItem> = vec![];
+
+    for value in data.next_data().iter().chain(&data.json) {
+        walk_objects(value, &mut |object| {
+            let Some(id) = object.get("id").and_then(Value::as_str).filter(|id| VIDEO_ID_ONLY_REGEX.is_match(id)) else {
+                return;
+            };
+            if items.iter().any(|item| item.id == id) {
+                return;
+            }
+
+            let mut item = VideoGalleryItem::new(id.to_string());
+            item.title = object.get("name").and_then(text_value).unwrap_or_default().to_string();
+            let label = object
+                .get("contentType")
+                .and_then(|content_type| content_type.get("displayName").and_then(text_value).or_else(|| text_value(content_type)));
+            item.kind = label
+                .and_then(VideoKind::from_label)
+                .or_else(|| VideoKind::from_title(&item.title))
+                .unwrap_or(VideoKind::Other);
+            item.duration = object
+                .get("runtime")
+                .and_then(|runtime| runtime.as_u64().or_else(|| runtime.get("value")?.as_u64()))
+                .map(Duration::from_secs);
+            item.date = object
+                .get("createdDate")
+                .and_then(Value::as_str)
+                .and_then(|date| parse_date(date.get(..10)?));
+            items.push(item);
+        });
+    }
+
+    items
+}
+
+/// The element around a video link that holds its title, duration and labels.
+fn card(link: ElementRef<'_>) -> ElementRef<'_> {
+    let ancestors: Vec<ElementRef> = link.ancestors().filter_map(ElementRef::wrap).take(MAX_CARD_DEPTH).collect();
+
+    ancestors
+        .iter()
+        .find(|el| {
+            let element = el.value();
+            matches!(element.name(), "li" | "article") || element.attr("class").is_some_and(|c| c.contains("card"))
+        })
+        .or(ancestors.first())
+        .copied()
+        .unwrap_or(link)
+}
+
+fn parse_duration(text: &str) -> Option<Duration> {
+    let captures = DURATION_REGEX.captures(text)?;
+    let hours: u64 = captures.get(1).map_or(Ok(0), |h| h.as_str().parse()).ok()?;
+    let minutes: u64 = captures[2].parse().ok()?;
+    let seconds: u64 = captures[3].parse().ok()?;
+    Some(Duration::from_secs(hours * 3600 + minutes * 60 + seconds))
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    DATE_FORMATS.iter().find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+}
+
+pub struct VideoGalleryService<'a> {
+    pub(crate) client: &'a ImdbRequestClient,
+}
+
+impl<'a> VideoGalleryService<'a> {
+    /// The videos of a title, oldest first. `None` when IMDb doesn't know the title.
+    pub async fn get(&self, imdb_id: &str) -> Result<Option<Vec<VideoGalleryItem>>, Error> {
+        let path = format!("/title/{}/videogallery/?sort=date,asc", imdb_id);
+        let res = self.client.get_raw(&path).await?;
+
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !res.status().is_success() {
+            return Err(Error::UnexpectedStatus(res.status().as_u16()));
+        }
+
+        let html = res.text();
+        let gallery = parse_video_gallery(html);
+        let strategy = gallery.strategy.map(|s| s.name());
+        if gallery.strategy.is_some_and(|s| s != VideoGalleryStrategy::Links) {
+            debug!("Found videos for {} with the {} fallback", imdb_id, strategy.unwrap_or_default());
+        }
+        // Titles without videos have empty galleries, which say nothing about whether the
+        // strategies still work. Only pages that mention videos none of them found are misses.
+        if strategy.is_some() || VIDEO_MARKUP_REGEX.is_match(html) {
+            self.client.extraction_stats().record(PageKind::VideoGallery, strategy);
+        }
+
+        Ok(Some(gallery.items))
+    }
+}
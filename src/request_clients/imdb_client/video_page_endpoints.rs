@@ -0,0 +1,209 @@
+use scraper::Html;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::debug;
+use crate::request_clients::imdb_client::extraction_stats::PageKind;
+use crate::request_clients::imdb_client::imdb_request_client::ImdbRequestClient;
+use crate::request_clients::imdb_client::page_data::{text_value, walk_objects, PageData};
+use crate::request_clients::request_errors::error::Error;
+
+const VIDEO_PROPS_PATH: &str = "/props/pageProps/videoPlaybackData/video/playbackURLs";
+const MP4: &str = "MP4";
+
+/// Where playback URLs have been found on earlier versions of the video page.
+const ALTERNATIVE_PROPS_PATHS: [&str; 3] = [
+    "/props/pageProps/videoPlaybackData/playbackURLs",
+    "/props/pageProps/video/playbackURLs",
+    "/props/pageProps/videoData/playbackURLs",
+];
+
+/// Keys that GraphQL responses use for the playback URL list.
+const PLAYBACK_URL_KEYS: [&str; 2] = ["playbackURLs", "playbackUrls"];
+
+/// One encoding of a video, as listed on its video page.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PlaybackRendition {
+    pub url: String,
+    #[serde(rename = "videoMimeType", alias = "mimeType", default)]
+    pub mime_type: Option<String>,
+    /// IMDb's label for the resolution, e.g. `DEF_1080p`.
+    #[serde(rename = "videoDefinition", default)]
+    pub definition: Option<String>,
+}
+
+impl PlaybackRendition {
+    pub fn is_mp4(&self) -> bool {
+        self.mime_type.as_deref() == Some(MP4)
+    }
+
+    /// Higher for better resolutions; 0 when the definition is unknown.
+    pub fn quality_rank(&self) -> u8 {
+        match self.definition.as_deref() {
+            Some(d) if d.contains("1080") => 3,
+            Some(d) if d.contains("720") => 2,
+            Some(d) if d.contains("480") => 1,
+            _ => 0,
+        }
+    }
+
+    /// A rendition from a JSON-LD `VideoObject`.
+    fn from_video_object(object: &serde_json::Map<String, Value>) -> Option<Self> {
+        let url = object.get("contentUrl")?.as_str()?.to_string();
+        let format = object.get("encodingFormat").and_then(Value::as_str).unwrap_or_default().to_lowercase();
+        let mime_type = if format.contains("mp4") || url.split('?').next().is_some_and(|path| path.ends_with(".mp4")) {
+            Some(MP4.to_string())
+        } else {
+            format.rsplit('/').next().filter(|f| !f.is_empty()).map(str::to_uppercase)
+        };
+        let definition = object.get("height").and_then(|h| h.as_u64().or_else(|| h.as_str()?.parse().ok())).map(|h| format!("DEF_{h}p"));
+
+        Some(Self { url, mime_type, definition })
+    }
+}
+
+/// The best MP4 rendition, or the first rendition when there is no MP4.
+pub fn best_rendition(renditions: &[PlaybackRendition]) -> Option<&PlaybackRendition> {
+    renditions
+        .iter()
+        .filter(|r| r.is_mp4())
+        .max_by_key(|r| r.quality_rank())
+        .or_else(|| renditions.first())
+}
+
+/// The ways playback renditions are found on a video page, in the order they are tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoPageStrategy {
+    /// The first line of `__NEXT_DATA__`, at the current props path.
+    NextDataFirstLine,
+    /// All of `__NEXT_DATA__`, at the current props path.
+    NextDataScript,
+    /// All of `__NEXT_DATA__`, at props paths used by earlier versions of the page.
+    AlternativePath,
+    /// A playback URL list anywhere in the page's JSON, including embedded GraphQL responses.
+    GraphQl,
+    /// JSON-LD `VideoObject`s with a `contentUrl`.
+    JsonLdVideoObject,
+}
+
+impl VideoPageStrategy {
+    pub const ALL: [VideoPageStrategy; 5] = [
+        VideoPageStrategy::NextDataFirstLine,
+        VideoPageStrategy::NextDataScript,
+        VideoPageStrategy::AlternativePath,
+        VideoPageStrategy::GraphQl,
+        VideoPageStrategy::JsonLdVideoObject,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VideoPageStrategy::NextDataFirstLine => "next_data_first_line",
+            VideoPageStrategy::NextDataScript => "next_data_script",
+            VideoPageStrategy::AlternativePath => "alternative_path",
+            VideoPageStrategy::GraphQl => "graphql",
+            VideoPageStrategy::JsonLdVideoObject => "json_ld_video_object",
+        }
+    }
+
+    fn extract(&self, data: &PageData) -> Vec<PlaybackRendition> {
+        match self {
+            VideoPageStrategy::NextDataFirstLine => data
+                .next_data_first_line()
+                .and_then(|next_data| next_data.pointer(VIDEO_PROPS_PATH).map(renditions_from))
+                .unwrap_or_default(),
+            VideoPageStrategy::NextDataScript => data
+                .next_data()
+                .and_then(|next_data| next_data.pointer(VIDEO_PROPS_PATH).map(renditions_from))
+                .unwrap_or_default(),
+            VideoPageStrategy::AlternativePath => data
+                .next_data()
+                .and_then(|next_data| {
+                    ALTERNATIVE_PROPS_PATHS
+                        .iter()
+                        .filter_map(|path| next_data.pointer(path))
+                        .map(renditions_from)
+                        .find(|renditions| !renditions.is_empty())
+                })
+                .unwrap_or_default(),
+            VideoPageStrategy::GraphQl => {
+                let mut renditions = vec![];
+                for value in data.next_data().iter().chain(&data.json) {
+                    walk_objects(value, &mut |object| {
+                        if renditions.is_empty() {
+                            if let Some(list) = PLAYBACK_URL_KEYS.iter().find_map(|key| object.get(*key)) {
+                                renditions = renditions_from(list);
+                            }
+                        }
+                    });
+                }
+                renditions
+            }
+            VideoPageStrategy::JsonLdVideoObject => {
+                let mut renditions = vec![];
+                for value in &data.json_ld {
+                    walk_objects(value, &mut |object| {
+                        let is_video = object.get("@type").is_some_and(|t| match t {
+                            Value::Array(types) => types.iter().any(|t| text_value(t) == Some("VideoObject")),
+                            t => text_value(t) == Some("VideoObject"),
+                        });
+                        if is_video {
+                            renditions.extend(PlaybackRendition::from_video_object(object));
+                        }
+                    });
+                }
+                renditions
+            }
+        }
+    }
+}
+
+/// The renditions in a playback URL list. Entries without a URL are skipped.
+fn renditions_from(list: &Value) -> Vec<PlaybackRendition> {
+    list.as_array()
+        .map(|entries| entries.iter().filter_map(|entry| PlaybackRendition::deserialize(entry).ok()).collect())
+        .unwrap_or_default()
+}
+
+/// The playback renditions of a video page, and the strategy that found them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoPage {
+    pub renditions: Vec<PlaybackRendition>,
+    pub strategy: Option<VideoPageStrategy>,
+}
+
+/// Parses the playback renditions embedded in a video page, trying each [VideoPageStrategy] in
+/// turn. A page where none of them finds anything has no renditions.
+pub fn parse_video_page(html: &str) -> VideoPage {
+    let data = PageData::parse(&Html::parse_document(html));
+
+    VideoPageStrategy::ALL
+        .iter()
+        .map(|strategy| (strategy, strategy.extract(&data)))
+        .find(|(_, renditions)| !renditions.is_empty())
+        .map(|(strategy, renditions)| VideoPage { renditions, strategy: Some(*strategy) })
+        .unwrap_or(VideoPage { renditions: vec![], strategy: None })
+}
+
+pub struct VideoPageService<'a> {
+    pub(crate) client: &'a ImdbRequestClient,
+}
+
+impl<'a> VideoPageService<'a> {
+    /// The playback renditions of an IMDb video, e.g. `vi1032782617`.
+    pub async fn get(&self, video_id: &str) -> Result<Vec<PlaybackRendition>, Error> {
+        let path = format!("/video/{}/", video_id);
+        let res = self.client.get_raw(&path).await?;
+
+        if !res.status().is_success() {
+            return Err(Error::UnexpectedStatus(res.status().as_u16()));
+        }
+
+        let page = parse_video_page(res.text());
+        let strategy = page.strategy.map(|s| s.name());
+        if page.strategy.is_some_and(|s| s != VideoPageStrategy::NextDataFirstLine) {
+            debug!("Found playback URLs for {} with the {} fallback", video_id, strategy.unwrap_or_default());
+        }
+        self.client.extraction_stats().record(PageKind::VideoPage, strategy);
+
+        Ok(page.renditions)
+    }
+}
@@ -0,0 +1,64 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::warn;
+use crate::request_clients::request_errors::error::Error;
+
+/// The base URL of an upstream service plus mirrors to fall back to, in order.
+///
+/// Requests go to the most recently working base URL first. When it can't be reached, the
+/// others are tried in turn; any other error is returned without trying further mirrors.
+#[derive(Debug)]
+pub struct BaseUrls {
+    urls: Vec<String>,
+    preferred: AtomicUsize,
+}
+
+impl BaseUrls {
+    pub fn new(primary: impl Into<String>, mirrors: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let urls = std::iter::once(primary.into())
+            .chain(mirrors.into_iter().map(Into::into))
+            .map(|url| url.trim_end_matches('/').to_string())
+            .collect();
+
+        Self { urls, preferred: AtomicUsize::new(0) }
+    }
+
+    pub fn primary(&self) -> &str {
+        &self.urls[0]
+    }
+
+    pub fn all(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// Calls `send` with the full URL for `path` on each base URL in turn, until one of them
+    /// can be connected to.
+    pub async fn request<T, F, Fut>(&self, path: &str, send: F) -> Result<T, Error>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let start = self.preferred.load(Ordering::Relaxed);
+        let count = self.urls.len();
+
+        for offset in 0..count {
+            let index = (start + offset) % count;
+            let base = &self.urls[index];
+
+            match send(format!("{}{}", base, path)).await {
+                Err(e) if e.is_connection_error() && offset + 1 < count => {
+                    let next = &self.urls[(index + 1) % count];
+                    warn!("Could not connect to {}, trying {}: {}", base, next, e);
+                }
+                result => {
+                    if result.is_ok() {
+                        self.preferred.store(index, Ordering::Relaxed);
+                    }
+                    return result;
+                }
+            }
+        }
+
+        unreachable!("the last base URL always returns")
+    }
+}
@@ -1,74 +1,121 @@
+use std::path::Path;
 use std::sync::Arc;
-use once_cell::sync::OnceCell;
-use crate::configuration::configuration_provider::{AppConfig, DataSource};
+use std::time::Duration;
+use anyhow::{anyhow, Context};
+use tracing::info;
+use crate::app::{default_app, initialize_default_app};
+use crate::configuration::configuration_provider::AppConfig;
+use crate::request_clients::circuit_breaker::CircuitBreaker;
+use crate::request_clients::client_settings::{ClientSettings, RedactedUrl, Upstream};
+use crate::request_clients::fixtures::{FixtureSet, RecordingExecutor, ReplayExecutor};
+use crate::request_clients::http_executor::HttpExecutor;
 use crate::request_clients::imdb_client::imdb_request_client;
 use crate::request_clients::imdb_client::imdb_request_client::ImdbRequestClient;
 use crate::request_clients::rate_limited_client::RateLimitedClient;
+use crate::request_clients::tmdb_client::credentials::TmdbCredential;
 use crate::request_clients::tmdb_client::tmdb_request_client;
-use crate::request_clients::tmdb_client::tmdb_request_client::TmdbRequestClient;
+use crate::request_clients::tmdb_client::tmdb_request_client::{Locale, TmdbRequestClient};
 
 pub mod tmdb_client;
 pub mod imdb_client;
 pub mod rate_limited_client;
 pub mod request_errors;
+pub mod adaptive_limiter;
+pub mod circuit_breaker;
+pub mod client_settings;
+pub mod cookie_jar;
+pub mod fixtures;
+pub mod http_executor;
+pub mod mirrors;
+pub mod raw_response;
+pub mod response_cache;
+pub mod retry_policy;
 
-static IMDB_REQUEST_CLIENT: OnceCell<Arc<ImdbRequestClient>> = OnceCell::new();
-
-static TMDB_REQUEST_CLIENT: OnceCell<Arc<TmdbRequestClient>> = OnceCell::new();
-
+/// Creates the request clients of the default [crate::app::App], building it from `app_config`
+/// if needed.
 pub fn initialize_request_clients(app_config: Arc<AppConfig>) {
-    initialize_imdb_request_client(app_config.clone());
-
-    if app_config.data_source == DataSource::Tmdb {
-        initialize_tmdb_request_client(app_config.clone());
-    }
+    initialize_default_app(app_config).expect("Failed to initialize request clients");
 }
 
 pub fn initialize_imdb_request_client(app_config: Arc<AppConfig>) {
-    let executor = RateLimitedClient::from_config(
-        &app_config.user_agent,
-        &app_config.imdb_rate_limit,
-    ).expect("Failed to create IMDB executor");
-
-    let inner_client = imdb_request_client::ClientBuilder::default()
-        .with_executor(executor)
-        .build()
-        .expect("Failed to build IMDB request client");
-
-    let client = ImdbRequestClient(inner_client);
-    
-    IMDB_REQUEST_CLIENT
-        .set(Arc::new(client))
-        .expect("IMDB request client already initialized");
+    initialize_request_clients(app_config);
 }
 
 pub fn initialize_tmdb_request_client(app_config: Arc<AppConfig>) {
-    let executor = RateLimitedClient::from_config(
-        &app_config.user_agent,
-        &app_config.tmdb_rate_limit,
-    ).expect("Failed to create TMDB executor");
+    initialize_request_clients(app_config);
+}
 
-    let tmdb_api_key = app_config.tmdb_api_key.clone().unwrap_or_else(|| {
-        panic!("TMDB API key is required but not provided in the configuration");
-    });
+pub fn get_imdb_client() -> Arc<ImdbRequestClient> {
+    default_app().imdb_client().clone()
+}
 
-    let inner_client = tmdb_request_client::ClientBuilder::default()
-        .with_api_key(tmdb_api_key)
-        .with_executor(executor)
-        .build()
-        .expect("Failed to build TMDB request client");
+pub fn get_tmdb_client() -> Arc<TmdbRequestClient> {
+    default_app().tmdb_client().expect("TMDB Client not initialized").clone()
+}
+
+/// Creates the executor for `upstream`: live requests, or the recorded fixtures when the config
+/// asks to record or replay them. Each upstream has its own fixture subdirectory.
+pub fn create_executor(app_config: &AppConfig, upstream: Upstream) -> anyhow::Result<HttpExecutor> {
+    let fixtures_subdir = match upstream {
+        Upstream::Imdb => "imdb",
+        Upstream::Tmdb => "tmdb",
+    };
+
+    if let Some(dir) = &app_config.replay_fixtures {
+        info!("Replaying {:?} responses from {}", upstream, dir);
+        return Ok(ReplayExecutor::new(FixtureSet::new(Path::new(dir).join(fixtures_subdir))).into());
+    }
 
-    let client = TmdbRequestClient(inner_client);
+    let executor = RateLimitedClient::from_settings(&ClientSettings::for_upstream(app_config, upstream))?;
 
-    TMDB_REQUEST_CLIENT
-        .set(Arc::new(client))
-        .expect("TMDB request client already initialized");
+    Ok(match &app_config.record_fixtures {
+        Some(dir) => {
+            info!("Recording {:?} responses to {}", upstream, dir);
+            RecordingExecutor::new(executor, FixtureSet::new(Path::new(dir).join(fixtures_subdir))).into()
+        }
+        None => executor.into(),
+    })
 }
 
-pub fn get_imdb_client() -> Arc<ImdbRequestClient> {
-    IMDB_REQUEST_CLIENT.get().expect("IMDB Client not initialized").clone()
+pub fn create_imdb_request_client(app_config: &AppConfig) -> anyhow::Result<Arc<ImdbRequestClient>> {
+    let executor = create_executor(app_config, Upstream::Imdb).context("Failed to create IMDB executor")?;
+
+    let circuit_breaker = CircuitBreaker::new(
+        "IMDb",
+        app_config.imdb_circuit_breaker_threshold,
+        Duration::from_secs(app_config.imdb_circuit_breaker_cooldown_secs),
+    );
+
+    let mut builder = imdb_request_client::ClientBuilder::default()
+        .with_executor(executor)
+        .with_circuit_breaker(circuit_breaker)
+        .with_mirrors(app_config.imdb_mirrors.iter().map(RedactedUrl::as_str));
+    if !app_config.imdb_base_url.as_str().is_empty() {
+        builder.set_base_url(app_config.imdb_base_url.as_str().to_string());
+    }
+    let inner_client = builder.build()?;
+
+    Ok(Arc::new(ImdbRequestClient(inner_client)))
 }
 
-pub fn get_tmdb_client() -> Arc<TmdbRequestClient> {
-    TMDB_REQUEST_CLIENT.get().expect("TMDB Client not initialized").clone()
-}
\ No newline at end of file
+pub fn create_tmdb_request_client(app_config: &AppConfig) -> anyhow::Result<Arc<TmdbRequestClient>> {
+    let executor = create_executor(app_config, Upstream::Tmdb).context("Failed to create TMDB executor")?;
+
+    let tmdb_api_key = app_config.tmdb_api_key.clone()
+        .ok_or_else(|| anyhow!("TMDB API key is required but not provided in the configuration"))?;
+
+    let mut builder = tmdb_request_client::ClientBuilder::default()
+        .with_credential(TmdbCredential::detect(tmdb_api_key.expose()))
+        .with_locale(Locale {
+            language: app_config.tmdb_language.clone(),
+            region: app_config.tmdb_region.clone(),
+        })
+        .with_executor(executor)
+        .with_mirrors(app_config.tmdb_mirrors.iter().map(RedactedUrl::as_str));
+    if !app_config.tmdb_base_url.as_str().is_empty() {
+        builder.set_base_url(app_config.tmdb_base_url.as_str().to_string());
+    }
+    let inner_client = builder.build()?;
+
+    Ok(Arc::new(TmdbRequestClient(inner_client)))
+}
@@ -1,12 +1,19 @@
 use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use anyhow::anyhow;
+use anyhow::{anyhow, Context};
 use futures::future::BoxFuture;
-use governor::clock::DefaultClock;
-use governor::{Quota, RateLimiter};
-use governor::state::NotKeyed;
+use governor::Quota;
 use serde::de::DeserializeOwned;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, SET_COOKIE, USER_AGENT};
+use reqwest::{Certificate, NoProxy, Proxy, StatusCode};
 use serde::Serialize;
+use tracing::{debug, warn};
+use crate::request_clients::adaptive_limiter::AdaptiveRateLimiter;
+use crate::request_clients::client_settings::{redact_url_password, ClientSettings, UserAgentRotation};
+use crate::request_clients::cookie_jar::PersistentCookieJar;
+use crate::request_clients::response_cache::{CachedResponse, ResponseCache};
+use crate::request_clients::retry_policy::RetryPolicy;
 use crate::request_clients::request_errors::error::Error;
 use crate::request_clients::request_errors::server_other_body_error::ServerOtherBodyError;
 use crate::request_clients::request_errors::server_validation_error::ServerValidationBodyError;
@@ -14,9 +21,21 @@ use crate::request_clients::request_errors::server_validation_error::ServerValid
 #[derive(Clone, Debug)]
 pub struct RateLimitedClient {
     inner: Arc<reqwest::Client>,
-    limiter: Arc<RateLimiter<NotKeyed, governor::state::InMemoryState, DefaultClock>>,
+    limiter: Arc<AdaptiveRateLimiter>,
+    retry_policy: Arc<RetryPolicy>,
+    /// User agents cycled through per request. Empty when the client keeps one user agent.
+    rotating_user_agents: Arc<[HeaderValue]>,
+    next_user_agent: Arc<AtomicUsize>,
+    cookie_jar: Option<Arc<PersistentCookieJar>>,
+    response_cache: Option<Arc<ResponseCache>>,
 }
 
+/// Statuses that mean the server wants us to slow down.
+const THROTTLING_STATUSES: [reqwest::StatusCode; 2] = [
+    reqwest::StatusCode::TOO_MANY_REQUESTS,
+    reqwest::StatusCode::SERVICE_UNAVAILABLE,
+];
+
 pub trait Executor: Send + Sync {
     fn execute<T, P>(&self, url: &str, params: P) -> BoxFuture<Result<T, Error>>
     where
@@ -24,20 +43,125 @@ pub trait Executor: Send + Sync {
         P: Serialize + Send + 'static;
 
     fn execute_raw(&self, url: &str) -> BoxFuture<Result<reqwest::Response, Error>>;
+
+    /// Tells the executor a response was throttled in a way only the caller can recognize,
+    /// such as a block page served with a success status.
+    fn report_throttled(&self) {}
+
+    /// Drops any cached response for `url`, e.g. because the caller found it to be a block page.
+    fn evict_cached(&self, _url: &str) -> BoxFuture<()> {
+        Box::pin(async {})
+    }
 }
 
 impl RateLimitedClient {
     pub fn from_config(user_agent: &str, rate_limit: &str) -> anyhow::Result<Self> {
+        Self::from_settings(&ClientSettings::new(user_agent, rate_limit))
+    }
+
+    pub fn from_settings(settings: &ClientSettings) -> anyhow::Result<Self> {
+        let user_agents = settings
+            .user_agents
+            .iter()
+            .map(|ua| HeaderValue::from_str(ua).with_context(|| format!("Invalid user agent for {}: {}", settings.name, ua)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        // Both rotation modes start at a random position, so restarts don't always present the same agent.
+        let first_user_agent = fastrand::usize(..user_agents.len().max(1));
+        let session_user_agent = match (settings.user_agent_rotation, user_agents.get(first_user_agent)) {
+            (UserAgentRotation::PerSession, Some(user_agent)) => user_agent.clone(),
+            _ => HeaderValue::from_str(&settings.user_agent)
+                .with_context(|| format!("Invalid user agent for {}: {}", settings.name, settings.user_agent))?,
+        };
+        let rotating_user_agents = match settings.user_agent_rotation {
+            UserAgentRotation::PerRequest => user_agents,
+            UserAgentRotation::PerSession => vec![],
+        };
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &settings.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .with_context(|| format!("Invalid header name for {}: {}", settings.name, name))?;
+            let value = HeaderValue::from_str(value)
+                .with_context(|| format!("Invalid value for header {} of {}: {}", name, settings.name, value))?;
+            headers.insert(name, value);
+        }
+        if let Some(token) = &settings.bearer_token {
+            let mut value = HeaderValue::from_str(&format!("Bearer {}", token.expose()))
+                .with_context(|| format!("Invalid bearer token for {}", settings.name))?;
+            value.set_sensitive(true);
+            headers.insert(AUTHORIZATION, value);
+        }
+
+        let mut builder = reqwest::Client::builder()
+            .user_agent(session_user_agent)
+            .default_headers(headers);
+
+        let cookie_jar = settings.cookie_jar.as_ref().map(|path| Arc::new(PersistentCookieJar::load(path)));
+        if let Some(jar) = &cookie_jar {
+            builder = builder.cookie_provider(jar.store());
+        }
+        if let Some(timeout) = settings.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = settings.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        if let Some(proxy) = &settings.proxy {
+            let no_proxy = NoProxy::from_string(&proxy.no_proxy.join(","));
+            let reqwest_proxy = Proxy::all(&proxy.url)
+                .with_context(|| format!("Invalid proxy URL for {}: {}", settings.name, redact_url_password(&proxy.url)))?
+                .no_proxy(no_proxy);
+            builder = builder.proxy(reqwest_proxy);
+        }
+
+        for path in &settings.ca_certificates {
+            let pem = std::fs::read(path)
+                .with_context(|| format!("Failed to read CA certificate {:?} for {}", path, settings.name))?;
+            let certificates = Certificate::from_pem_bundle(&pem)
+                .with_context(|| format!("Invalid CA certificate {:?} for {}", path, settings.name))?;
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        let response_cache = match &settings.response_cache {
+            Some(dir) if !settings.cache_rules.is_empty() => Some(Arc::new(
+                ResponseCache::new(dir, settings.cache_rules.clone())
+                    .with_context(|| format!("Failed to create the HTTP cache for {}", settings.name))?,
+            )),
+            _ => None,
+        };
+
         let client = Arc::new(
-            reqwest::Client::builder()
-                .user_agent(user_agent)
+            builder
                 .build()
                 .map_err(|e| anyhow::anyhow!("Failed to build reqwest client: {e}"))?,
         );
 
-        let limiter = Arc::new(RateLimiter::direct(Self::parse_quota(rate_limit)?));
+        let limiter = Arc::new(AdaptiveRateLimiter::new(
+            &settings.name,
+            Self::parse_quota(&settings.rate_limit)?,
+            settings.adaptive_rate_limit,
+        ));
+
+        Ok(Self {
+            inner: client,
+            limiter,
+            retry_policy: Arc::new(settings.retry_policy.clone()),
+            rotating_user_agents: rotating_user_agents.into(),
+            next_user_agent: Arc::new(AtomicUsize::new(first_user_agent)),
+            cookie_jar,
+            response_cache,
+        })
+    }
+
+    pub fn limiter(&self) -> &Arc<AdaptiveRateLimiter> {
+        &self.limiter
+    }
 
-        Ok(Self { inner: client, limiter })
+    pub fn cookie_jar(&self) -> Option<&Arc<PersistentCookieJar>> {
+        self.cookie_jar.as_ref()
     }
 
     fn parse_quota(s: &str) -> anyhow::Result<Quota> {
@@ -57,6 +181,144 @@ impl RateLimitedClient {
             other => anyhow::bail!("Invalid rate unit: {}", other),
         }
     }
+
+    /// Sends `request`, answering it from the response cache when a cache rule applies.
+    /// Cached responses are used as they are within the rule's TTL and revalidated after it.
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+        let mut request = request.build().map_err(|err| Error::Request {
+            source: Box::new(err),
+        })?;
+
+        let Some((cache, rule)) = self
+            .response_cache
+            .as_ref()
+            .and_then(|cache| Some((cache, cache.rule_for(request.url())?.clone())))
+        else {
+            return self.send_with_retry(request).await;
+        };
+
+        let url = request.url().clone();
+        let cached = cache.get(&url).await;
+        if let Some(entry) = &cached {
+            if entry.is_fresh(rule.ttl) {
+                debug!("Using cached {} response", rule.name);
+                return Ok(entry.to_response());
+            }
+            entry.add_validators(request.headers_mut());
+        }
+
+        let res = self.send_with_retry(request).await?;
+
+        if let (StatusCode::NOT_MODIFIED, Some(mut entry)) = (res.status(), cached) {
+            debug!("Cached {} response is still current", rule.name);
+            entry.stored_at = chrono::Utc::now().timestamp();
+            if let Err(e) = cache.put(&url, &entry).await {
+                warn!("Failed to update cached {} response: {:#}", rule.name, e);
+            }
+            return Ok(entry.to_response());
+        }
+
+        if res.status() != StatusCode::OK {
+            return Ok(res);
+        }
+
+        let status = res.status();
+        let headers = res.headers().clone();
+        let entry = CachedResponse::new(status, &headers, res.text().await?);
+        if entry.has_validators() || !rule.ttl.is_zero() {
+            if let Err(e) = cache.put(&url, &entry).await {
+                warn!("Failed to cache {} response: {:#}", rule.name, e);
+            }
+        }
+
+        Ok(entry.to_response())
+    }
+
+    /// Sends `request`, retrying transient failures according to the retry policy.
+    /// Every attempt waits for the rate limiter.
+    async fn send_with_retry(&self, request: reqwest::Request) -> Result<reqwest::Response, Error> {
+        let limiter = &self.limiter;
+        let retry_policy = &self.retry_policy;
+        // The query is left out of log messages because it can carry the TMDB API key.
+        let mut url = request.url().clone();
+        url.set_query(None);
+        let mut retry = 0;
+
+        loop {
+            limiter.until_ready().await;
+            retry += 1;
+
+            let mut attempt = request.try_clone().expect("GET requests have no streaming body");
+            if !self.rotating_user_agents.is_empty() {
+                let index = self.next_user_agent.fetch_add(1, Ordering::Relaxed) % self.rotating_user_agents.len();
+                attempt.headers_mut().insert(USER_AGENT, self.rotating_user_agents[index].clone());
+            }
+
+            match self.inner.execute(attempt).await {
+                Ok(res) => {
+                    if let Some(jar) = self.cookie_jar.as_ref().filter(|_| res.headers().contains_key(SET_COOKIE)) {
+                        jar.mark_changed();
+                        jar.save_if_due().await;
+                    }
+                    if THROTTLING_STATUSES.contains(&res.status()) {
+                        limiter.on_throttled();
+                    } else {
+                        limiter.on_success();
+                    }
+
+                    let Some(delay) = retry_policy.response_delay(retry, res.status(), res.headers()) else {
+                        return Ok(res);
+                    };
+                    warn!(
+                        "Request to {} returned {}, retrying in {:?} ({}/{})",
+                        url, res.status(), delay, retry, retry_policy.max_retries
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(err) if retry <= retry_policy.max_retries && retry_policy.should_retry_error(&err) => {
+                    let delay = retry_policy.backoff(retry);
+                    warn!(
+                        "Request to {} failed: {err}, retrying in {:?} ({}/{})",
+                        url, delay, retry, retry_policy.max_retries
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(err) => {
+                    return Err(Error::Request {
+                        source: Box::new(err),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Parses a successful response as `T`, and turns any other response into the error the
+/// server described in its body. Bodies that aren't the server's JSON error, such as an HTML page
+/// from a proxy, are kept as a snippet in [Error::UnparsedBody].
+pub(crate) async fn parse_json_response<T: DeserializeOwned>(res: reqwest::Response) -> Result<T, Error> {
+    let status = res.status();
+    let body = res.text().await?;
+
+    if status.is_success() {
+        let parsed: T = serde_json::from_str(&body).map_err(|err| Error::Response {
+            source: Box::new(err),
+        })?;
+        Ok(parsed)
+    } else if status == reqwest::StatusCode::UNPROCESSABLE_ENTITY {
+        match serde_json::from_str::<ServerValidationBodyError>(&body) {
+            Ok(payload) => Err(Error::Validation(payload)),
+            Err(_) => Err(Error::unparsed_body(status.as_u16(), &body)),
+        }
+    } else {
+        match serde_json::from_str::<ServerOtherBodyError>(&body) {
+            Ok(payload) => Err(Error::Server {
+                code: status.as_u16(),
+                content: payload,
+            }),
+            Err(_) => Err(Error::unparsed_body(status.as_u16(), &body)),
+        }
+    }
 }
 
 impl Executor for RateLimitedClient {
@@ -65,64 +327,34 @@ impl Executor for RateLimitedClient {
         T: DeserializeOwned + Send + 'static,
         P: Serialize + Send + 'static,
     {
-        let client = self.inner.clone();
-        let limiter = self.limiter.clone();
+        let this = self.clone();
         let url = url.to_string();
 
         Box::pin(async move {
-            limiter.until_ready().await;
-
-            let res = client
-                .get(&url)
-                .query(&params)
-                .send()
-                .await
-                .map_err(|err| Error::Request {
-                    source: Box::new(err),
-                })?;
-
-            let status = res.status();
-            if status.is_success() {
-                let body = res.text().await?;
-                let parsed: T = serde_json::from_str(&body).map_err(|err| Error::Response {
-                    source: Box::new(err),
-                })?;
-                Ok(parsed)
-            } else if status == reqwest::StatusCode::UNPROCESSABLE_ENTITY {
-                let payload = res.json::<ServerValidationBodyError>().await.map_err(|err| {
-                    Error::Response {
-                        source: Box::new(err),
-                    }
-                })?;
-                Err(Error::Validation(payload))
-            } else {
-                let payload = res.json::<ServerOtherBodyError>().await.map_err(|err| {
-                    Error::Response {
-                        source: Box::new(err),
-                    }
-                })?;
-                Err(Error::Server {
-                    code: status.as_u16(),
-                    content: payload,
-                })
-            }
+            let res = this.send(this.inner.get(&url).query(&params)).await?;
+            parse_json_response(res).await
         })
     }
 
     fn execute_raw(&self, url: &str) -> BoxFuture<Result<reqwest::Response, Error>> {
-        let client = self.inner.clone();
-        let limiter = self.limiter.clone();
+        let this = self.clone();
         let url = url.to_string();
 
         Box::pin(async move {
-            limiter.until_ready().await;
-            client
-                .get(&url)
-                .send()
-                .await
-                .map_err(|e| Error::Request {
-                    source: Box::new(e),
-                })
+            this.send(this.inner.get(&url)).await
+        })
+    }
+
+    fn report_throttled(&self) {
+        self.limiter.on_throttled();
+    }
+
+    fn evict_cached(&self, url: &str) -> BoxFuture<()> {
+        let url = url::Url::parse(url);
+        Box::pin(async move {
+            if let (Some(cache), Ok(url)) = (&self.response_cache, url) {
+                cache.evict(&url).await;
+            }
         })
     }
 }
\ No newline at end of file
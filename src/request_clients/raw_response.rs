@@ -0,0 +1,28 @@
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use crate::request_clients::request_errors::error::Error;
+
+/// A response whose body has been read into memory, so it can be inspected before parsing.
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl RawResponse {
+    pub async fn read(res: reqwest::Response) -> Result<Self, Error> {
+        let status = res.status();
+        let headers = res.headers().clone();
+        let body = res.text().await?;
+        Ok(Self { status, headers, body })
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn text(&self) -> &str {
+        &self.body
+    }
+}
@@ -1,5 +1,10 @@
+use crate::request_clients::client_settings::redact_url_secrets;
 use crate::request_clients::request_errors::server_other_body_error::ServerOtherBodyError;
 use crate::request_clients::request_errors::server_validation_error::ServerValidationBodyError;
+use crate::request_clients::request_errors::tmdb_status::TmdbStatus;
+
+/// How much of an unparsable error body is kept for the error message.
+pub const BODY_SNIPPET_LEN: usize = 200;
 
 #[derive(Debug)]
 pub enum Error {
@@ -17,6 +22,24 @@ pub enum Error {
     Reqwest(reqwest::Error),
     SerdeJson(serde_json::Error),
     UnsupportedOperation(String),
+    UnexpectedStatus(u16),
+    Blocked {
+        status: u16,
+        reason: String,
+    },
+    CircuitOpen {
+        service: String,
+        retry_in: std::time::Duration,
+    },
+    MissingFixture {
+        request: String,
+    },
+    /// A non-success response whose body isn't the JSON error the server usually sends, e.g. an
+    /// HTML page from a proxy or an empty body.
+    UnparsedBody {
+        code: u16,
+        snippet: String,
+    },
 }
 
 impl std::fmt::Display for Error {
@@ -25,10 +48,16 @@ impl std::fmt::Display for Error {
             Error::Request { .. } => write!(f, "couldn't execute request"),
             Error::Response { .. } => write!(f, "couldn't read response"),
             Error::Validation(err) => write!(f, "validation failed: {}", err),
-            Error::Server { code, content } => write!(f, "internal server error with code {}: {}", code, content),
-            Error::Reqwest(err) => write!(f, "reqwest error: {}", err),
-            Error::SerdeJson(err) => write!(f, "serde_json error: {}", err),
+            Error::Server { code, content } => write!(f, "server error with code {} ({}): {}", code, content.tmdb_status(), content.status_message),
+            Error::Reqwest(_) => write!(f, "HTTP request failed"),
+            Error::SerdeJson(_) => write!(f, "invalid JSON"),
             Error::UnsupportedOperation(op) => write!(f, "unsupported operation: {}", op),
+            Error::UnexpectedStatus(code) => write!(f, "unexpected status code {}", code),
+            Error::Blocked { status, reason } => write!(f, "request blocked by bot protection (status {}): {}", status, reason),
+            Error::CircuitOpen { service, retry_in } => write!(f, "{} requests paused after repeated failures, retrying in {:?}", service, retry_in),
+            Error::MissingFixture { request } => write!(f, "no recorded response for {}", request),
+            Error::UnparsedBody { code, snippet } if snippet.is_empty() => write!(f, "unexpected status code {} with an empty body", code),
+            Error::UnparsedBody { code, snippet } => write!(f, "unexpected status code {}: {}", code, snippet),
         }
     }
 }
@@ -41,6 +70,27 @@ impl Error {
         }
     }
 
+    /// Whether the server could not be reached at all, e.g. because of DNS or a refused connection.
+    pub fn is_connection_error(&self) -> bool {
+        match self {
+            Self::Request { source } => source
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(|e| e.is_connect()),
+            Self::Reqwest(e) => e.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// Whether TMDB rejected the API key, either by status code or by its own `status_code`.
+    pub fn is_auth_rejected(&self) -> bool {
+        match self {
+            Self::Server { code, content } => *code == 401 || content.tmdb_status() == TmdbStatus::AuthRejected,
+            Self::UnparsedBody { code, .. } => *code == 401,
+            Self::UnexpectedStatus(code) => *code == 401,
+            _ => false,
+        }
+    }
+
     pub fn as_server_error(&self) -> Option<&ServerOtherBodyError> {
         match self {
             Self::Server { code: _, content } => Some(content),
@@ -49,7 +99,55 @@ impl Error {
     }
 }
 
-impl std::error::Error for Error {}
+impl Error {
+    /// An [Error::UnparsedBody] with the start of `body`, with whitespace collapsed.
+    pub fn unparsed_body(code: u16, body: &str) -> Self {
+        let mut snippet = body.split_whitespace().collect::<Vec<_>>().join(" ");
+        if let Some((cut, _)) = snippet.char_indices().nth(BODY_SNIPPET_LEN) {
+            snippet.truncate(cut);
+            snippet.push('…');
+        }
+        Error::UnparsedBody { code, snippet }
+    }
+}
+
+impl Error {
+    /// Replaces secret query parameters, such as the TMDB API key, in the URL that a reqwest error
+    /// carries, so the error can be logged.
+    pub fn redact_secrets(self) -> Self {
+        match self {
+            Error::Reqwest(err) => Error::Reqwest(redact_reqwest_error(err)),
+            Error::Request { source } => Error::Request { source: redact_boxed_error(source) },
+            Error::Response { source } => Error::Response { source: redact_boxed_error(source) },
+            other => other,
+        }
+    }
+}
+
+fn redact_reqwest_error(mut err: reqwest::Error) -> reqwest::Error {
+    if let Some(url) = err.url_mut() {
+        redact_url_secrets(url);
+    }
+    err
+}
+
+fn redact_boxed_error(source: Box<dyn std::error::Error + Send + Sync>) -> Box<dyn std::error::Error + Send + Sync> {
+    match source.downcast::<reqwest::Error>() {
+        Ok(err) => Box::new(redact_reqwest_error(*err)),
+        Err(source) => source,
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Request { source } | Error::Response { source } => Some(source.as_ref()),
+            Error::Reqwest(err) => Some(err),
+            Error::SerdeJson(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 impl From<reqwest::Error> for Error {
     fn from(err: reqwest::Error) -> Self {
@@ -2,4 +2,5 @@ pub mod error;
 pub mod server_error;
 pub mod server_body_error;
 pub mod server_other_body_error;
-pub mod server_validation_error;
\ No newline at end of file
+pub mod server_validation_error;
+pub mod tmdb_status;
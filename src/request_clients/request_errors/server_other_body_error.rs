@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use crate::request_clients::request_errors::tmdb_status::TmdbStatus;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ServerOtherBodyError {
@@ -6,6 +7,12 @@ pub struct ServerOtherBodyError {
     pub status_message: String,
 }
 
+impl ServerOtherBodyError {
+    pub fn tmdb_status(&self) -> TmdbStatus {
+        TmdbStatus::from_code(self.status_code)
+    }
+}
+
 impl std::error::Error for ServerOtherBodyError {}
 impl std::fmt::Display for ServerOtherBodyError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -0,0 +1,41 @@
+/// The meaning of a TMDB `status_code`, as listed at <https://developer.themoviedb.org/docs/errors>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TmdbStatus {
+    /// The API key is missing, invalid or suspended, or the request isn't allowed with it.
+    AuthRejected,
+    /// The requested ID or resource doesn't exist.
+    NotFound,
+    /// The request count is over the allowed limit.
+    RateLimited,
+    /// TMDB is down, under maintenance or timed out.
+    Unavailable,
+    /// A parameter of the request was invalid.
+    InvalidRequest,
+    Other(u16),
+}
+
+impl TmdbStatus {
+    pub fn from_code(code: u16) -> Self {
+        match code {
+            3 | 7 | 10 | 14 | 16 | 17 | 33 | 35 | 36 | 38 => TmdbStatus::AuthRejected,
+            6 | 34 | 37 => TmdbStatus::NotFound,
+            25 => TmdbStatus::RateLimited,
+            9 | 11 | 15 | 24 | 43 | 44 | 46 => TmdbStatus::Unavailable,
+            2 | 4 | 5 | 18 | 19 | 20 | 21 | 22 | 23 | 26 | 27 | 28 | 47 => TmdbStatus::InvalidRequest,
+            code => TmdbStatus::Other(code),
+        }
+    }
+}
+
+impl std::fmt::Display for TmdbStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TmdbStatus::AuthRejected => write!(f, "API key rejected"),
+            TmdbStatus::NotFound => write!(f, "not found"),
+            TmdbStatus::RateLimited => write!(f, "rate limited"),
+            TmdbStatus::Unavailable => write!(f, "service unavailable"),
+            TmdbStatus::InvalidRequest => write!(f, "invalid request"),
+            TmdbStatus::Other(code) => write!(f, "status {}", code),
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use anyhow::Context;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
+use tracing::debug;
+use url::Url;
+
+/// Directory in the cache path that holds cached responses.
+pub const HTTP_CACHE_DIR: &str = "http_cache";
+pub const DEFAULT_IMDB_GALLERY_CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Headers kept with a cached body. Everything else, cookies in particular, is dropped.
+const STORED_HEADERS: [reqwest::header::HeaderName; 3] = [CONTENT_TYPE, ETAG, LAST_MODIFIED];
+
+/// A class of endpoints whose responses are cached, e.g. IMDb video galleries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheRule {
+    /// Names the endpoint class in log messages.
+    pub name: String,
+    /// Responses are cached for URLs whose path contains this.
+    pub path_contains: String,
+    /// How long a cached response is used without asking the server. After that it is
+    /// revalidated with `If-None-Match`/`If-Modified-Since`.
+    pub ttl: Duration,
+}
+
+impl CacheRule {
+    pub fn new(name: impl Into<String>, path_contains: impl Into<String>, ttl: Duration) -> Self {
+        Self { name: name.into(), path_contains: path_contains.into(), ttl }
+    }
+}
+
+/// A successful response stored on disk with its validators.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// Unix timestamp of when the response was fetched or last revalidated.
+    pub stored_at: i64,
+}
+
+impl CachedResponse {
+    /// Keeps the body and the validator headers of a response.
+    pub fn new(status: reqwest::StatusCode, headers: &HeaderMap, body: String) -> Self {
+        let headers = STORED_HEADERS
+            .iter()
+            .filter_map(|name| {
+                let value = headers.get(name)?.to_str().ok()?;
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect();
+
+        Self { status: status.as_u16(), headers, body, stored_at: chrono::Utc::now().timestamp() }
+    }
+
+    fn header(&self, name: &reqwest::header::HeaderName) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n == name.as_str()).map(|(_, v)| v.as_str())
+    }
+
+    pub fn has_validators(&self) -> bool {
+        self.header(&ETAG).is_some() || self.header(&LAST_MODIFIED).is_some()
+    }
+
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        let age = chrono::Utc::now().timestamp().saturating_sub(self.stored_at);
+        age >= 0 && (age as u64) < ttl.as_secs()
+    }
+
+    /// Adds `If-None-Match` and `If-Modified-Since` for this response to `headers`.
+    pub fn add_validators(&self, headers: &mut HeaderMap) {
+        for (validator, condition) in [(ETAG, IF_NONE_MATCH), (LAST_MODIFIED, IF_MODIFIED_SINCE)] {
+            if let Some(value) = self.header(&validator).and_then(|v| HeaderValue::from_str(v).ok()) {
+                headers.insert(condition, value);
+            }
+        }
+    }
+
+    pub fn to_response(&self) -> reqwest::Response {
+        let mut builder = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        builder
+            .body(self.body.clone())
+            .expect("cached responses only hold valid statuses and headers")
+            .into()
+    }
+}
+
+/// An on-disk cache of response bodies for the endpoint classes in `rules`, one file per URL.
+#[derive(Debug)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    rules: Vec<CacheRule>,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>, rules: Vec<CacheRule>) -> anyhow::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create HTTP cache directory {:?}", dir))?;
+        Ok(Self { dir, rules })
+    }
+
+    /// The rule that applies to `url`, or `None` when its responses aren't cached.
+    pub fn rule_for(&self, url: &Url) -> Option<&CacheRule> {
+        self.rules.iter().find(|rule| url.path().contains(&rule.path_contains))
+    }
+
+    /// Cache files are named after a hash of the URL, so query parameters such as API keys
+    /// never end up in file names.
+    fn entry_path(&self, url: &Url) -> PathBuf {
+        let digest = Sha256::digest(url.as_str().as_bytes());
+        let mut name = String::with_capacity(digest.len() * 2 + 5);
+        for byte in digest {
+            _ = write!(name, "{:02x}", byte);
+        }
+        name.push_str(".json");
+        self.dir.join(name)
+    }
+
+    pub async fn get(&self, url: &Url) -> Option<CachedResponse> {
+        let path = self.entry_path(url);
+        let contents = tokio::fs::read_to_string(&path).await.ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                debug!("Ignoring unreadable cache entry {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Replaces the entry for `url` through a temporary file of its own, so that concurrent
+    /// writes of the same URL never interleave and readers never see a partial entry.
+    pub async fn put(&self, url: &Url, entry: &CachedResponse) -> anyhow::Result<()> {
+        let path = self.entry_path(url);
+        let dir = self.dir.clone();
+        let contents = serde_json::to_vec(entry)?;
+
+        tokio::task::spawn_blocking(move || {
+            let mut file = NamedTempFile::new_in(&dir).with_context(|| format!("Failed to create a cache entry in {:?}", dir))?;
+            file.write_all(&contents).with_context(|| format!("Failed to write cache entry {:?}", file.path()))?;
+            file.persist(&path).with_context(|| format!("Failed to replace cache entry {:?}", path))?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn evict(&self, url: &Url) {
+        _ = tokio::fs::remove_file(self.entry_path(url)).await;
+    }
+}
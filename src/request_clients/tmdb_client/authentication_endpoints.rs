@@ -0,0 +1,22 @@
+use serde::Deserialize;
+use crate::request_clients::request_errors::error::Error;
+use crate::request_clients::tmdb_client::tmdb_request_client::TmdbRequestClient;
+
+#[derive(Debug, Deserialize)]
+pub struct AuthenticationStatus {
+    pub success: bool,
+    pub status_code: Option<u16>,
+    pub status_message: Option<String>,
+}
+
+pub struct AuthenticationService<'a> {
+    pub(crate) client: &'a TmdbRequestClient,
+}
+
+impl<'a> AuthenticationService<'a> {
+    /// Checks the configured credential against TMDB. A rejected credential is an error for which
+    /// [Error::is_auth_rejected] holds.
+    pub async fn validate(&self) -> Result<AuthenticationStatus, Error> {
+        self.client.execute("/authentication", &()).await
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+
+/// How requests to TMDB are authenticated.
+///
+/// TMDB accepts either a v3 API key, sent as the `api_key` query parameter, or a v4 read access
+/// token, sent as an `Authorization: Bearer` header. Neither is ever printed by `Debug` or `Display`.
+#[derive(Clone, PartialEq)]
+pub enum TmdbCredential {
+    ApiKey(String),
+    ReadAccessToken(String),
+}
+
+impl TmdbCredential {
+    /// Tells the two formats apart: read access tokens are JWTs, three dot-separated base64url
+    /// segments starting with `eyJ`, while API keys are 32 hex digits.
+    pub fn detect(value: &str) -> Self {
+        let value = value.trim();
+        if is_jwt(value) {
+            TmdbCredential::ReadAccessToken(value.to_string())
+        } else {
+            TmdbCredential::ApiKey(value.to_string())
+        }
+    }
+
+    pub fn secret(&self) -> &str {
+        match self {
+            TmdbCredential::ApiKey(secret) | TmdbCredential::ReadAccessToken(secret) => secret,
+        }
+    }
+
+    pub fn is_read_access_token(&self) -> bool {
+        matches!(self, TmdbCredential::ReadAccessToken(_))
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            TmdbCredential::ApiKey(_) => "v3 API key",
+            TmdbCredential::ReadAccessToken(_) => "v4 read access token",
+        }
+    }
+}
+
+fn is_jwt(value: &str) -> bool {
+    let segments: Vec<&str> = value.split('.').collect();
+    value.starts_with("eyJ")
+        && segments.len() == 3
+        && segments.iter().all(|segment| {
+            !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        })
+}
+
+impl fmt::Debug for TmdbCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TmdbCredential::ApiKey(_) => f.debug_tuple("ApiKey").field(&"REDACTED").finish(),
+            TmdbCredential::ReadAccessToken(_) => f.debug_tuple("ReadAccessToken").field(&"REDACTED").finish(),
+        }
+    }
+}
+
+impl fmt::Display for TmdbCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.describe())
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use crate::request_clients::request_errors::error::Error;
+use crate::request_clients::tmdb_client::external_ids_endpoints::{MovieExternalIds, TvShowExternalIds};
+use crate::request_clients::tmdb_client::release_dates_endpoints::ReleaseDates;
+use crate::request_clients::tmdb_client::tmdb_request_client::{Locale, TmdbRequestClient};
+use crate::request_clients::tmdb_client::videos_endpoints::VideoList;
+use crate::utils::empty_strings;
+
+/// Related data that is fetched with the details in the same request. Release dates only exist
+/// for movies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AppendToResponse {
+    Videos,
+    ReleaseDates,
+    ExternalIds,
+}
+
+impl AppendToResponse {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppendToResponse::Videos => "videos",
+            AppendToResponse::ReleaseDates => "release_dates",
+            AppendToResponse::ExternalIds => "external_ids",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Genre {
+    pub id: u64,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MovieDetails {
+    pub id: u64,
+    #[serde(default, deserialize_with = "empty_strings::deserialize")]
+    pub imdb_id: Option<String>,
+    pub title: String,
+    #[serde(default)]
+    pub original_title: String,
+    #[serde(default)]
+    pub overview: String,
+    #[serde(default, deserialize_with = "empty_strings::deserialize")]
+    pub release_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub runtime: Option<u32>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub genres: Vec<Genre>,
+    pub videos: Option<VideoList>,
+    pub release_dates: Option<ReleaseDates>,
+    pub external_ids: Option<MovieExternalIds>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TvDetails {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub original_name: String,
+    #[serde(default)]
+    pub overview: String,
+    #[serde(default, deserialize_with = "empty_strings::deserialize")]
+    pub first_air_date: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "empty_strings::deserialize")]
+    pub last_air_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub number_of_seasons: Option<u32>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub genres: Vec<Genre>,
+    pub videos: Option<VideoList>,
+    pub external_ids: Option<TvShowExternalIds>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DetailsParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    append_to_response: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    include_video_language: Option<String>,
+}
+
+impl DetailsParams {
+    fn new(locale: &Locale, append: &[AppendToResponse]) -> Self {
+        let names: Vec<&str> = append.iter().map(AppendToResponse::name).collect();
+        let include_videos = append.contains(&AppendToResponse::Videos);
+        Self {
+            append_to_response: (!names.is_empty()).then(|| names.join(",")),
+            language: locale.language.clone(),
+            include_video_language: locale
+                .iso_639_1()
+                .filter(|_| include_videos)
+                .map(|language| format!("{language},null")),
+        }
+    }
+}
+
+pub struct DetailsService<'a> {
+    pub(crate) client: &'a TmdbRequestClient,
+    pub(crate) locale: Locale,
+}
+
+impl<'a> DetailsService<'a> {
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    pub async fn movie(&self, movie_id: &str, append: &[AppendToResponse]) -> Result<MovieDetails, Error> {
+        self.client.execute(&format!("/movie/{movie_id}"), DetailsParams::new(&self.locale, append)).await
+    }
+
+    pub async fn tv(&self, tv_id: &str, append: &[AppendToResponse]) -> Result<TvDetails, Error> {
+        self.client.execute(&format!("/tv/{tv_id}"), DetailsParams::new(&self.locale, append)).await
+    }
+}
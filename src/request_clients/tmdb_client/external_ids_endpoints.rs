@@ -5,6 +5,8 @@ use crate::utils::empty_strings;
 
 #[derive(Debug, Deserialize)]
 pub struct MovieExternalIds {
+    /// Left out when the IDs are appended to the details.
+    #[serde(default)]
     pub id: u64,
     #[serde(deserialize_with = "empty_strings::deserialize")]
     pub imdb_id: Option<String>,
@@ -12,9 +14,12 @@ pub struct MovieExternalIds {
 
 #[derive(Debug, Deserialize)]
 pub struct TvShowExternalIds {
+    #[serde(default)]
     pub id: u64,
     #[serde(deserialize_with = "empty_strings::deserialize")]
     pub imdb_id: Option<String>,
+    #[serde(default)]
+    pub tvdb_id: Option<u64>,
 }
 
 pub(crate) enum ExternalIds {
@@ -29,6 +34,14 @@ impl ExternalIds {
             ExternalIds::Tv(t) => t.imdb_id.as_deref(),
         }
     }
+
+    /// TheTVDB only lists shows.
+    pub fn tvdb_id(&self) -> Option<u64> {
+        match self {
+            ExternalIds::Movie(_) => None,
+            ExternalIds::Tv(t) => t.tvdb_id,
+        }
+    }
 }
 
 
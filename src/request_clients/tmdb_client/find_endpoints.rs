@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use crate::request_clients::request_errors::error::Error;
+use crate::request_clients::tmdb_client::search_endpoints::{MovieSummary, TvSummary};
+use crate::request_clients::tmdb_client::tmdb_request_client::{Locale, TmdbRequestClient};
+
+/// The ID systems `/find` can look titles up by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExternalSource {
+    Imdb,
+    Tvdb,
+    Wikidata,
+}
+
+impl ExternalSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExternalSource::Imdb => "imdb_id",
+            ExternalSource::Tvdb => "tvdb_id",
+            ExternalSource::Wikidata => "wikidata_id",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FindResults {
+    #[serde(default)]
+    pub movie_results: Vec<MovieSummary>,
+    #[serde(default)]
+    pub tv_results: Vec<TvSummary>,
+}
+
+impl FindResults {
+    pub fn is_empty(&self) -> bool {
+        self.movie_results.is_empty() && self.tv_results.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FindParams {
+    external_source: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+}
+
+pub struct FindService<'a> {
+    pub(crate) client: &'a TmdbRequestClient,
+    pub(crate) locale: Locale,
+}
+
+impl<'a> FindService<'a> {
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    pub async fn by_external_id(&self, id: &str, source: ExternalSource) -> Result<FindResults, Error> {
+        let params = FindParams {
+            external_source: source.name(),
+            language: self.locale.language.clone(),
+        };
+        self.client.execute(&format!("/find/{id}"), params).await
+    }
+
+    /// The TMDB movies and TV shows for an IMDb ID such as `tt0133093`.
+    pub async fn by_imdb_id(&self, imdb_id: &str) -> Result<FindResults, Error> {
+        self.by_external_id(imdb_id, ExternalSource::Imdb).await
+    }
+}
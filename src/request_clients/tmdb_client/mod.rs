@@ -1,2 +1,9 @@
 pub mod tmdb_request_client;
-pub mod external_ids_endpoints;
\ No newline at end of file
+pub mod authentication_endpoints;
+pub mod credentials;
+pub mod details_endpoints;
+pub mod external_ids_endpoints;
+pub mod find_endpoints;
+pub mod release_dates_endpoints;
+pub mod search_endpoints;
+pub mod videos_endpoints;
\ No newline at end of file
@@ -0,0 +1,94 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+use crate::request_clients::request_errors::error::Error;
+use crate::request_clients::tmdb_client::tmdb_request_client::TmdbRequestClient;
+
+/// How a movie was released, as numbered by TMDB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(from = "u8")]
+pub enum ReleaseType {
+    Premiere,
+    TheatricalLimited,
+    Theatrical,
+    Digital,
+    Physical,
+    Tv,
+    Other(u8),
+}
+
+impl From<u8> for ReleaseType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => ReleaseType::Premiere,
+            2 => ReleaseType::TheatricalLimited,
+            3 => ReleaseType::Theatrical,
+            4 => ReleaseType::Digital,
+            5 => ReleaseType::Physical,
+            6 => ReleaseType::Tv,
+            other => ReleaseType::Other(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseDate {
+    #[serde(default)]
+    pub certification: String,
+    #[serde(default)]
+    pub note: String,
+    /// An ISO 8601 timestamp such as `1999-03-31T00:00:00.000Z`.
+    pub release_date: String,
+    #[serde(rename = "type")]
+    pub kind: ReleaseType,
+}
+
+impl ReleaseDate {
+    pub fn date(&self) -> Option<NaiveDate> {
+        self.release_date.get(..10).and_then(|date| date.parse().ok())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CountryReleaseDates {
+    pub iso_3166_1: String,
+    pub release_dates: Vec<ReleaseDate>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ReleaseDates {
+    #[serde(default)]
+    pub id: u64,
+    pub results: Vec<CountryReleaseDates>,
+}
+
+impl ReleaseDates {
+    /// The release dates in a region such as `US`.
+    pub fn for_region(&self, region: &str) -> &[ReleaseDate] {
+        self.results
+            .iter()
+            .find(|country| country.iso_3166_1.eq_ignore_ascii_case(region))
+            .map(|country| country.release_dates.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The earliest release of one of `kinds` in `region`.
+    pub fn earliest(&self, region: &str, kinds: &[ReleaseType]) -> Option<NaiveDate> {
+        self.for_region(region)
+            .iter()
+            .filter(|release| kinds.contains(&release.kind))
+            .filter_map(ReleaseDate::date)
+            .min()
+    }
+}
+
+pub struct ReleaseDatesService<'a> {
+    pub(crate) client: &'a TmdbRequestClient,
+}
+
+impl<'a> ReleaseDatesService<'a> {
+    /// The release dates of a movie in every region. Use [ReleaseDates::for_region] with the
+    /// client's [crate::request_clients::tmdb_client::tmdb_request_client::Locale] to pick one.
+    pub async fn movie(&self, movie_id: &str) -> Result<ReleaseDates, Error> {
+        self.client.execute(&format!("/movie/{movie_id}/release_dates"), &()).await
+    }
+}
@@ -0,0 +1,155 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use crate::request_clients::request_errors::error::Error;
+use crate::request_clients::tmdb_client::tmdb_request_client::{Locale, TmdbRequestClient};
+use crate::utils::empty_strings;
+
+/// One page of a paginated TMDB response. Pages start at 1.
+#[derive(Debug, Deserialize)]
+pub struct Page<T> {
+    pub page: u32,
+    pub total_pages: u32,
+    pub total_results: u32,
+    pub results: Vec<T>,
+}
+
+impl<T> Page<T> {
+    pub fn next_page(&self) -> Option<u32> {
+        (self.page < self.total_pages).then_some(self.page + 1)
+    }
+}
+
+/// A movie as listed in search and find results.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MovieSummary {
+    pub id: u64,
+    pub title: String,
+    #[serde(default)]
+    pub original_title: String,
+    #[serde(default)]
+    pub original_language: String,
+    #[serde(default)]
+    pub overview: String,
+    #[serde(default, deserialize_with = "empty_strings::deserialize")]
+    pub release_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub popularity: f64,
+    #[serde(default)]
+    pub genre_ids: Vec<u64>,
+}
+
+impl MovieSummary {
+    pub fn year(&self) -> Option<i32> {
+        self.release_date.map(|date| chrono::Datelike::year(&date))
+    }
+}
+
+/// A TV show as listed in search and find results.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TvSummary {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub original_name: String,
+    #[serde(default)]
+    pub original_language: String,
+    #[serde(default)]
+    pub overview: String,
+    #[serde(default, deserialize_with = "empty_strings::deserialize")]
+    pub first_air_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub popularity: f64,
+    #[serde(default)]
+    pub genre_ids: Vec<u64>,
+}
+
+impl TvSummary {
+    pub fn year(&self) -> Option<i32> {
+        self.first_air_date.map(|date| chrono::Datelike::year(&date))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct MovieSearchParams {
+    query: String,
+    page: u32,
+    include_adult: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    year: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TvSearchParams {
+    query: String,
+    page: u32,
+    include_adult: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_air_date_year: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+}
+
+pub struct SearchService<'a> {
+    pub(crate) client: &'a TmdbRequestClient,
+    pub(crate) locale: Locale,
+}
+
+impl<'a> SearchService<'a> {
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Searches movies by title, optionally released in `year` (in any region).
+    pub async fn movies(&self, query: &str, year: Option<i32>, page: u32) -> Result<Page<MovieSummary>, Error> {
+        let params = MovieSearchParams {
+            query: query.to_string(),
+            page: page.max(1),
+            include_adult: false,
+            year,
+            language: self.locale.language.clone(),
+            region: self.locale.region.clone(),
+        };
+        self.client.execute("/search/movie", params).await
+    }
+
+    /// Searches TV shows by name, optionally first aired in `year`.
+    pub async fn tv(&self, query: &str, first_air_date_year: Option<i32>, page: u32) -> Result<Page<TvSummary>, Error> {
+        let params = TvSearchParams {
+            query: query.to_string(),
+            page: page.max(1),
+            include_adult: false,
+            first_air_date_year,
+            language: self.locale.language.clone(),
+        };
+        self.client.execute("/search/tv", params).await
+    }
+
+    /// The results of up to `max_pages` pages of [SearchService::movies].
+    pub async fn all_movies(&self, query: &str, year: Option<i32>, max_pages: u32) -> Result<Vec<MovieSummary>, Error> {
+        let mut results = vec![];
+        let mut page = Some(1);
+        while let Some(number) = page.filter(|&number| number <= max_pages) {
+            let response = self.movies(query, year, number).await?;
+            page = response.next_page();
+            results.extend(response.results);
+        }
+        Ok(results)
+    }
+
+    /// The results of up to `max_pages` pages of [SearchService::tv].
+    pub async fn all_tv(&self, query: &str, first_air_date_year: Option<i32>, max_pages: u32) -> Result<Vec<TvSummary>, Error> {
+        let mut results = vec![];
+        let mut page = Some(1);
+        while let Some(number) = page.filter(|&number| number <= max_pages) {
+            let response = self.tv(query, first_air_date_year, number).await?;
+            page = response.next_page();
+            results.extend(response.results);
+        }
+        Ok(results)
+    }
+}
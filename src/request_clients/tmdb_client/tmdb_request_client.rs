@@ -1,29 +1,42 @@
 use std::borrow::Cow;
 use serde::Serialize;
 use anyhow::{anyhow, Result};
-use crate::request_clients::rate_limited_client::{Executor, RateLimitedClient};
+use crate::request_clients::mirrors::BaseUrls;
+use crate::request_clients::http_executor::HttpExecutor;
+use crate::request_clients::rate_limited_client::Executor;
 use crate::request_clients::request_errors::error::Error;
+use crate::request_clients::tmdb_client::authentication_endpoints::AuthenticationService;
+use crate::request_clients::tmdb_client::credentials::TmdbCredential;
+use crate::request_clients::tmdb_client::details_endpoints::DetailsService;
 use crate::request_clients::tmdb_client::external_ids_endpoints::ExternalIdsService;
+use crate::request_clients::tmdb_client::find_endpoints::FindService;
+use crate::request_clients::tmdb_client::release_dates_endpoints::ReleaseDatesService;
+use crate::request_clients::tmdb_client::search_endpoints::SearchService;
+use crate::request_clients::tmdb_client::videos_endpoints::VideosService;
 
 #[derive(Debug)]
 pub struct TmdbRequestClient(
-    pub Client<RateLimitedClient>
+    pub Client<HttpExecutor>
 );
 
-const BASE_URL: &str = "https://api.themoviedb.org/3";
+pub const BASE_URL: &str = "https://api.themoviedb.org/3";
 
 pub struct ClientBuilder<E: Executor> {
     base_url: Cow<'static, str>,
+    mirrors: Vec<String>,
     executor: Option<E>,
-    api_key: Option<String>,
+    credential: Option<TmdbCredential>,
+    locale: Locale,
 }
 
 impl<E: Executor> Default for ClientBuilder<E> {
     fn default() -> Self {
         Self {
             base_url: Cow::Borrowed(BASE_URL),
+            mirrors: vec![],
             executor: None,
-            api_key: None,
+            credential: None,
+            locale: Locale::default(),
         }
     }
 }
@@ -38,37 +51,87 @@ impl<E: Executor> ClientBuilder<E> {
         self.base_url = value.into();
     }
 
-    pub fn with_executor(mut self, executor: E) -> Self {
-        self.executor = Some(executor);
+    /// Base URLs to fall back to, in order, when the base URL can't be reached.
+    pub fn with_mirrors<U: Into<String>>(mut self, mirrors: impl IntoIterator<Item = U>) -> Self {
+        self.mirrors = mirrors.into_iter().map(Into::into).collect();
         self
     }
 
-    pub fn set_executor(mut self, executor: E) {
-        self.executor = Some(executor);
+    pub fn with_executor(mut self, executor: impl Into<E>) -> Self {
+        self.executor = Some(executor.into());
+        self
+    }
+
+    pub fn set_executor(mut self, executor: impl Into<E>) {
+        self.executor = Some(executor.into());
     }
 
     pub fn with_api_key(mut self, value: String) -> Self {
-        self.api_key = Some(value);
+        self.credential = Some(TmdbCredential::ApiKey(value));
         self
     }
 
     pub fn set_api_key(mut self, value: String) {
-        self.api_key = Some(value);
+        self.credential = Some(TmdbCredential::ApiKey(value));
+    }
+
+    /// Authenticates with `credential`. The client adds a v3 API key to every request itself; a
+    /// v4 read access token must be set as the executor's bearer token, see
+    /// [crate::request_clients::client_settings::ClientSettings::with_bearer_token].
+    pub fn with_credential(mut self, credential: TmdbCredential) -> Self {
+        self.credential = Some(credential);
+        self
+    }
+
+    /// The language and region that requests use unless a service is given its own [Locale].
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
     }
 
     pub fn build(self) -> Result<Client<E>> {
-        let base_url = self.base_url;
+        let base_urls = BaseUrls::new(self.base_url, self.mirrors);
         let executor = self.executor.ok_or_else(|| anyhow!("missing executor"))?;
-        let api_key = self.api_key.ok_or_else(|| anyhow!("missing api key"))?;
+        let credential = self.credential.ok_or_else(|| anyhow!("missing api key"))?;
 
         Ok(Client {
             executor,
-            base_url,
-            api_key,
+            base_urls,
+            credential,
+            locale: self.locale,
         })
     }
 }
 
+/// The language (`en-US`) and region (`US`) that TMDB localizes titles, overviews, search results
+/// and release dates for. TMDB falls back to its own defaults for whatever is left out.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Locale {
+    pub language: Option<String>,
+    pub region: Option<String>,
+}
+
+impl Locale {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn with_region(mut self, region: impl Into<String>) -> Self {
+        self.region = Some(region.into());
+        self
+    }
+
+    /// The language part of `language`, e.g. `en` for `en-US`, as used to filter videos.
+    pub fn iso_639_1(&self) -> Option<&str> {
+        self.language.as_deref().map(|language| language.split('-').next().unwrap_or(language))
+    }
+}
+
 #[derive(Serialize)]
 struct WithApiKey<V> {
     api_key: String,
@@ -78,16 +141,18 @@ struct WithApiKey<V> {
 
 pub struct Client<E> {
     executor: E,
-    base_url: Cow<'static, str>,
-    api_key: String,
+    base_urls: BaseUrls,
+    credential: TmdbCredential,
+    locale: Locale,
 }
 
 impl<E: std::fmt::Debug> std::fmt::Debug for Client<E> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct(stringify!(Client))
             .field("executor", &self.executor)
-            .field("base_url", &self.base_url)
-            .field("api_key", &"REDACTED")
+            .field("base_urls", &self.base_urls)
+            .field("credential", &self.credential)
+            .field("locale", &self.locale)
             .finish()
     }
 }
@@ -100,27 +165,70 @@ impl<E: Executor> Client<E> {
 
 impl TmdbRequestClient {
     pub fn base_url(&self) -> &str {
-        &self.0.base_url
+        self.0.base_urls.primary()
     }
 
+    pub fn base_urls(&self) -> &[String] {
+        self.0.base_urls.all()
+    }
+
+    pub fn credential(&self) -> &TmdbCredential {
+        &self.0.credential
+    }
+
+    pub fn locale(&self) -> &Locale {
+        &self.0.locale
+    }
+
+    /// Sends a request for `path`, with the API key added to `params` unless the credential is a
+    /// read access token. Errors never contain the API key.
     pub async fn execute<T, P>(&self, path: &str, params: P) -> Result<T, Error>
     where
         T: serde::de::DeserializeOwned + Send + 'static,
-        P: serde::Serialize + Send + Sync + 'static,
+        P: serde::Serialize + Clone + Send + Sync + 'static,
     {
-        let url = format!("{}{}", self.0.base_url, path);
-        self.0.executor
-            .execute(
-                &url,
-                WithApiKey {
-                    api_key: self.0.api_key.clone(),
-                    inner: params,
-                },
-            )
+        self.0.base_urls
+            .request(path, |url| {
+                let response = match &self.0.credential {
+                    TmdbCredential::ApiKey(api_key) => self.0.executor.execute(
+                        &url,
+                        WithApiKey {
+                            api_key: api_key.clone(),
+                            inner: params.clone(),
+                        },
+                    ),
+                    TmdbCredential::ReadAccessToken(_) => self.0.executor.execute(&url, params.clone()),
+                };
+                async move { response.await.map_err(Error::redact_secrets) }
+            })
             .await
     }
 
+    pub fn authentication(&self) -> AuthenticationService<'_> {
+        AuthenticationService { client: self }
+    }
+
     pub fn external_ids(&self) -> ExternalIdsService<'_> {
         ExternalIdsService { client: self }
     }
+
+    pub fn details(&self) -> DetailsService<'_> {
+        DetailsService { client: self, locale: self.0.locale.clone() }
+    }
+
+    pub fn find(&self) -> FindService<'_> {
+        FindService { client: self, locale: self.0.locale.clone() }
+    }
+
+    pub fn search(&self) -> SearchService<'_> {
+        SearchService { client: self, locale: self.0.locale.clone() }
+    }
+
+    pub fn videos(&self) -> VideosService<'_> {
+        VideosService { client: self, locale: self.0.locale.clone() }
+    }
+
+    pub fn release_dates(&self) -> ReleaseDatesService<'_> {
+        ReleaseDatesService { client: self }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use crate::request_clients::request_errors::error::Error;
+use crate::request_clients::tmdb_client::tmdb_request_client::{Locale, TmdbRequestClient};
+
+/// A video TMDB links to, hosted on YouTube or Vimeo.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Video {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub iso_639_1: Option<String>,
+    #[serde(default)]
+    pub iso_3166_1: Option<String>,
+    pub name: String,
+    pub key: String,
+    pub site: String,
+    #[serde(default)]
+    pub size: u32,
+    /// `Trailer`, `Teaser`, `Clip`, `Featurette`, `Behind the Scenes`, `Bloopers`, ...
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub official: bool,
+    #[serde(default)]
+    pub published_at: Option<String>,
+}
+
+impl Video {
+    pub fn is_trailer(&self) -> bool {
+        self.kind == "Trailer"
+    }
+
+    /// Where the video can be watched, for the sites TMDB links to.
+    pub fn url(&self) -> Option<String> {
+        match self.site.as_str() {
+            "YouTube" => Some(format!("https://www.youtube.com/watch?v={}", self.key)),
+            "Vimeo" => Some(format!("https://vimeo.com/{}", self.key)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct VideoList {
+    #[serde(default)]
+    pub id: u64,
+    pub results: Vec<Video>,
+}
+
+impl VideoList {
+    /// The best trailer: official ones before others, then the highest resolution.
+    pub fn best_trailer(&self) -> Option<&Video> {
+        self.results
+            .iter()
+            .filter(|video| video.is_trailer())
+            .max_by_key(|video| (video.official, video.size))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct VideosParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    include_video_language: Option<String>,
+}
+
+impl VideosParams {
+    /// Videos in the locale's language, plus those without a language, which many trailers are.
+    pub(crate) fn new(locale: &Locale) -> Self {
+        Self {
+            language: locale.language.clone(),
+            include_video_language: locale.iso_639_1().map(|language| format!("{language},null")),
+        }
+    }
+}
+
+pub struct VideosService<'a> {
+    pub(crate) client: &'a TmdbRequestClient,
+    pub(crate) locale: Locale,
+}
+
+impl<'a> VideosService<'a> {
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    pub async fn movie(&self, movie_id: &str) -> Result<VideoList, Error> {
+        self.client.execute(&format!("/movie/{movie_id}/videos"), VideosParams::new(&self.locale)).await
+    }
+
+    pub async fn tv(&self, tv_id: &str) -> Result<VideoList, Error> {
+        self.client.execute(&format!("/tv/{tv_id}/videos"), VideosParams::new(&self.locale)).await
+    }
+}
@@ -1,15 +1,14 @@
 use anyhow::Result;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::Ordering;
 use async_trait::async_trait;
 use tokio_cron_scheduler::{Job, JobScheduler};
 use tracing::{error, info};
 
-use crate::configuration::configuration_provider::AppConfig;
+use crate::app::App;
+use crate::configuration::configuration_provider::{AppConfig, LibraryProfile};
 use crate::schedulers::traits::Scheduler;
-use crate::schedulers::types::ScanFn;
-use crate::scrapers::{get_scraper};
-use crate::scrapers::traits::TrailerScraper;
+use crate::schedulers::types::{ScanFn, ScanLocks};
 
 #[derive(Debug)]
 pub struct ScrapingScheduler;
@@ -22,38 +21,52 @@ impl Scheduler for ScrapingScheduler {
         &self,
         app_config: Arc<AppConfig>,
         scan_fn: Option<ScanFn>,
-        is_running: Arc<AtomicBool>,
+        scan_locks: Arc<ScanLocks>,
     ) -> Result<JobScheduler> {
-        self.setup_scheduler_with_lock(app_config, scan_fn, is_running).await
+        self.setup_scheduler_with_lock(app_config, scan_fn, scan_locks).await
     }
 }
 
 impl ScrapingScheduler {
     pub async fn start_scheduler(&self, app_config: Arc<AppConfig>) -> Result<()> {
-        let is_running = Arc::new(AtomicBool::new(false)); // shared lock
-        let sched = self.setup_scheduler_with_lock(app_config.clone(), None, Arc::clone(&is_running)).await?;
-
-        info!(
-        "Scheduler started with schedule: {}",
-        app_config.schedule.as_deref().unwrap_or("No schedule")
-    );
-
-        let scraper = get_scraper();
-        let config = Arc::clone(&app_config);
-        let is_running_clone = Arc::clone(&is_running);
+        self.start_scheduler_with_scan_fn(app_config, None).await
+    }
 
-        tokio::spawn(async move {
-            if is_running_clone.swap(true, Ordering::SeqCst) {
-                tracing::warn!("Initial one-shot scan skipped: job already running");
-                return;
-            }
+    /// Runs an initial scan of every library, then keeps scanning on each library's schedule
+    /// until ctrl-c. `scan_fn` defaults to scanning with an [App] built from `app_config`.
+    pub async fn start_scheduler_with_scan_fn(&self, app_config: Arc<AppConfig>, scan_fn: Option<ScanFn>) -> Result<()> {
+        let scan_handler = match scan_fn {
+            Some(scan_fn) => scan_fn,
+            None => default_scan_fn(&app_config)?,
+        };
+        let scan_locks = Arc::new(ScanLocks::for_config(&app_config));
+        let sched = self.setup_scheduler_with_lock(app_config.clone(), Some(Arc::clone(&scan_handler)), Arc::clone(&scan_locks)).await?;
+
+        for library in &app_config.libraries {
+            info!(
+                "Scheduler started for library '{}' with schedule: {}",
+                library.name,
+                library.schedule.as_deref().unwrap_or("No schedule")
+            );
+
+            let scan_handler = Arc::clone(&scan_handler);
+            let config = Arc::clone(&app_config);
+            let library = Arc::clone(library);
+            let is_running = scan_locks.get(&library.name);
+
+            tokio::spawn(async move {
+                if is_running.swap(true, Ordering::SeqCst) {
+                    tracing::warn!("Initial one-shot scan of library '{}' skipped: job already running", library.name);
+                    return;
+                }
 
-            if let Err(err) = scraper.scan_and_refresh_trailers(&config).await {
-                error!("Initial trailer scan failed: {err}");
-            }
+                if let Err(err) = scan_handler(config, Arc::clone(&library)).await {
+                    error!("Initial trailer scan of library '{}' failed: {err}", library.name);
+                }
 
-            is_running_clone.store(false, Ordering::SeqCst);
-        });
+                is_running.store(false, Ordering::SeqCst);
+            });
+        }
 
         sched.start().await?;
         tokio::signal::ctrl_c().await?;
@@ -64,48 +77,62 @@ impl ScrapingScheduler {
         &self,
         app_config: Arc<AppConfig>,
         scan_fn: Option<ScanFn>,
-        is_running: Arc<AtomicBool>,
+        scan_locks: Arc<ScanLocks>,
     ) -> Result<JobScheduler> {
         let sched = JobScheduler::new().await?;
-        let schedule_expr = app_config.schedule.clone().unwrap_or_else(|| "0 0 * * *".to_string());
 
-        let scan_handler: ScanFn = scan_fn.unwrap_or_else(|| {
-            Arc::new(|config: Arc<AppConfig>| {
-                Box::pin(async move {
-                    info!("Starting trailer scan and refresh...");
-                    let scraper: Arc<dyn TrailerScraper> = get_scraper();
-                    scraper.scan_and_refresh_trailers(&config)
-                        .await
-                        .map_err(|e| anyhow::anyhow!(e))
-                })
-            })
-        });
+        let scan_handler: ScanFn = match scan_fn {
+            Some(scan_fn) => scan_fn,
+            None => default_scan_fn(&app_config)?,
+        };
 
-        let config = Arc::clone(&app_config);
-        let scan_handler_clone = Arc::clone(&scan_handler);
-        let is_running_clone = Arc::clone(&is_running);
+        for library in &app_config.libraries {
+            let schedule_expr = library.schedule.clone().unwrap_or_else(|| "0 0 * * *".to_string());
 
-        let job = Job::new_async(&schedule_expr, move |_uuid, _l| {
-            let config = Arc::clone(&config);
-            let scan_handler = Arc::clone(&scan_handler_clone);
-            let is_running = Arc::clone(&is_running_clone);
+            let config = Arc::clone(&app_config);
+            let library = Arc::clone(library);
+            let scan_handler_clone = Arc::clone(&scan_handler);
+            let is_running_clone = scan_locks.get(&library.name);
 
-            Box::pin(async move {
-                if is_running.swap(true, Ordering::SeqCst) {
-                    tracing::warn!("Scheduled scan skipped: previous job still running");
-                    return;
-                }
+            let job = Job::new_async(&schedule_expr, move |_uuid, _l| {
+                let config = Arc::clone(&config);
+                let library = Arc::clone(&library);
+                let scan_handler = Arc::clone(&scan_handler_clone);
+                let is_running = Arc::clone(&is_running_clone);
 
-                tracing::info!("Running scheduled trailer scan...");
-                if let Err(err) = scan_handler(config).await {
-                    error!("Scheduled scan failed: {err}");
-                }
+                Box::pin(async move {
+                    if is_running.swap(true, Ordering::SeqCst) {
+                        tracing::warn!("Scheduled scan of library '{}' skipped: previous job still running", library.name);
+                        return;
+                    }
 
-                is_running.store(false, Ordering::SeqCst);
-            })
-        })?;
+                    tracing::info!("Running scheduled trailer scan for library '{}'...", library.name);
+                    if let Err(err) = scan_handler(config, Arc::clone(&library)).await {
+                        error!("Scheduled scan of library '{}' failed: {err}", library.name);
+                    }
+
+                    is_running.store(false, Ordering::SeqCst);
+                })
+            })?;
+
+            sched.add(job).await?;
+        }
 
-        sched.add(job).await?;
         Ok(sched)
     }
-}
\ No newline at end of file
+}
+
+/// Scans each library with the services of `app`.
+pub fn app_scan_fn(app: Arc<App>) -> ScanFn {
+    Arc::new(move |_config: Arc<AppConfig>, library: Arc<LibraryProfile>| {
+        let app = Arc::clone(&app);
+        Box::pin(async move {
+            info!("Starting trailer scan and refresh for library '{}'...", library.name);
+            app.scan_library(&library).await.map(|_| ())
+        })
+    })
+}
+
+fn default_scan_fn(app_config: &Arc<AppConfig>) -> Result<ScanFn> {
+    Ok(app_scan_fn(Arc::new(App::from_config(Arc::clone(app_config))?)))
+}
@@ -1,9 +1,8 @@
 use std::sync::Arc;
-use std::sync::atomic::AtomicBool;
 use async_trait::async_trait;
 use tokio_cron_scheduler::JobScheduler;
 use crate::configuration::configuration_provider::AppConfig;
-use crate::schedulers::types::ScanFn;
+use crate::schedulers::types::{ScanFn, ScanLocks};
 
 #[async_trait]
 pub trait Scheduler {
@@ -11,6 +10,6 @@ pub trait Scheduler {
         &self,
         app_config: Arc<AppConfig>,
         scan_fn: Option<ScanFn>,
-        is_running: Arc<AtomicBool>,
+        scan_locks: Arc<ScanLocks>,
     ) -> anyhow::Result<JobScheduler>;
-}
\ No newline at end of file
+}
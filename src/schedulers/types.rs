@@ -1,6 +1,32 @@
+use std::collections::HashMap;
 use std::sync::Arc;
-use crate::configuration::configuration_provider::AppConfig;
+use std::sync::atomic::AtomicBool;
+use crate::configuration::configuration_provider::{AppConfig, LibraryProfile};
 
-pub type ScanFn = Arc<dyn Fn(Arc<AppConfig>) -> ScanFuture + Send + Sync>;
+pub type ScanFn = Arc<dyn Fn(Arc<AppConfig>, Arc<LibraryProfile>) -> ScanFuture + Send + Sync>;
 
-pub type ScanFuture = std::pin::Pin<Box<dyn std::future::Future<Output = anyhow::Result<()>> + Send>>;
\ No newline at end of file
+pub type ScanFuture = std::pin::Pin<Box<dyn std::future::Future<Output = anyhow::Result<()>> + Send>>;
+
+/// One "scan in progress" flag per library, so a slow library never blocks another one's schedule.
+#[derive(Debug, Default)]
+pub struct ScanLocks {
+    locks: HashMap<String, Arc<AtomicBool>>,
+}
+
+impl ScanLocks {
+    pub fn for_config(app_config: &AppConfig) -> Self {
+        let locks = app_config
+            .libraries
+            .iter()
+            .map(|library| (library.name.clone(), Arc::new(AtomicBool::new(false))))
+            .collect();
+        Self { locks }
+    }
+
+    pub fn get(&self, library_name: &str) -> Arc<AtomicBool> {
+        self.locks
+            .get(library_name)
+            .cloned()
+            .unwrap_or_else(|| panic!("No scan lock registered for library '{}'", library_name))
+    }
+}
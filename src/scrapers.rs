@@ -1,47 +1,36 @@
 use std::sync::Arc;
-use once_cell::sync::OnceCell;
-use tracing::{debug};
-use crate::caching::get_tmdb_to_imdb_cache;
+use tracing::debug;
+use crate::app::{default_app, initialize_default_app};
 use crate::configuration::configuration_provider::{AppConfig, DataSource};
-use crate::scrapers::imdb_trailers::ImdbTrailerScraper;
-use crate::scrapers::tmdb_trailers::TmdbTrailerScraper;
+use crate::scrapers::outcomes::RunSummary;
 use crate::scrapers::traits::TrailerScraper;
 
+pub mod events;
+pub mod folder_tagger;
+pub mod imdb_id_lookup;
 pub mod imdb_trailers;
 pub mod tmdb_trailers;
 pub mod traits;
-mod media_directories;
-
-pub static TRAILER_SCRAPER: OnceCell<Arc<dyn TrailerScraper>> = OnceCell::new();
+pub mod media_directories;
+pub mod outcomes;
+pub mod scrape_error;
+pub mod title_resolver;
 
+/// Creates the trailer scrapers of the default [crate::app::App], building it from `app_config`
+/// if needed.
 pub fn initialize_scrapers(app_config: Arc<AppConfig>) {
-    let imdb_scraper = Arc::new(ImdbTrailerScraper {});
-
-    debug!("Using data source: {:?}", app_config.data_source);
-    
-    match app_config.data_source {
-        DataSource::Imdb => {
-            let scraper: Arc<dyn TrailerScraper> = imdb_scraper.clone();
-            TRAILER_SCRAPER.set(scraper).expect("Scraper already initialized");
-        }
-        DataSource::Tmdb => {
-            let scraper: Arc<dyn TrailerScraper> = Arc::new(
-                TmdbTrailerScraper {
-                    imdb_trailer_scraper: imdb_scraper.clone(),
-                    tmdb_to_imdb_cache: get_tmdb_to_imdb_cache()
-                }
-            );
-            TRAILER_SCRAPER.set(scraper).expect("Scraper already initialized");
-        }
-    }
-    
-    debug!("Initialized trailer scraper");
+    initialize_default_app(app_config).expect("Failed to initialize trailer scrapers");
+    debug!("Initialized trailer scrapers");
 }
 
+pub fn get_scraper(data_source: &DataSource) -> Arc<dyn TrailerScraper> {
+    default_app()
+        .scraper(data_source)
+        .unwrap_or_else(|e| panic!("Trailer scraper for {:?} not initialized: {}", data_source, e))
+}
 
-pub fn get_scraper() -> Arc<dyn TrailerScraper> {
-    TRAILER_SCRAPER
-        .get()
-        .expect("Trailer scraper not initialized")
-        .clone()
-}
\ No newline at end of file
+/// Scans every configured library in turn with the default [crate::app::App], see
+/// [crate::app::App::scan].
+pub async fn scan_all_libraries(app_config: &Arc<AppConfig>) -> anyhow::Result<RunSummary> {
+    Ok(initialize_default_app(app_config.clone())?.scan().await)
+}
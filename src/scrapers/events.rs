@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+use tokio::sync::broadcast;
+use crate::scrapers::outcomes::{RunSummary, TitleOutcome};
+
+/// Progress notifications published while libraries are scanned.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanEvent {
+    LibraryStarted { library: String, titles: usize },
+    TitleStarted { library: String, path: PathBuf },
+    TitleFinished { library: String, path: PathBuf, outcome: TitleOutcome },
+    LibraryFinished { library: String, summary: RunSummary },
+}
+
+pub type ScanEventSender = broadcast::Sender<ScanEvent>;
+
+pub(crate) fn emit(events: Option<&ScanEventSender>, event: ScanEvent) {
+    if let Some(sender) = events {
+        // Sending only fails when nobody is subscribed, which is fine.
+        _ = sender.send(event);
+    }
+}
@@ -0,0 +1,220 @@
+//! Writes the IDs resolved for untagged folders back to the library, so that later scans read
+//! them instead of searching again, and undoes those changes.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use crate::configuration::configuration_provider::{DataSource, FolderTagging, LibraryProfile};
+use crate::configuration::id_patterns::{IdKind, IdPreset};
+
+/// The ID file written to a title folder in [FolderTagging::Sidecar] mode.
+pub const SIDECAR_FILENAME: &str = ".trailerfin.json";
+/// The log of tagging changes in the cache directory, one JSON object per line.
+pub const UNDO_LOG_FILENAME: &str = "tagging_undo.jsonl";
+
+/// The IDs of a title, as kept in a [SIDECAR_FILENAME] file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FolderIds {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imdb: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmdb: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tvdb: Option<u64>,
+}
+
+impl FolderIds {
+    /// The IDs in a folder's sidecar file, if it has a readable one.
+    pub fn read(folder: &Path) -> Option<Self> {
+        let path = folder.join(SIDECAR_FILENAME);
+        let contents = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(ids) => Some(ids),
+            Err(e) => {
+                warn!("Ignoring unreadable ID file {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+}
+
+/// A change made to the library, as recorded in the undo log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum TagChange {
+    Rename { from: PathBuf, to: PathBuf },
+    Sidecar { path: PathBuf },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UndoEntry {
+    at: u64,
+    #[serde(flatten)]
+    change: TagChange,
+}
+
+#[derive(Debug)]
+pub struct FolderTagger {
+    undo_log: PathBuf,
+    /// Serializes renames and undo log writes of concurrently processed titles.
+    lock: Mutex<()>,
+}
+
+impl FolderTagger {
+    /// A tagger that keeps its undo log in `cache_path`.
+    pub fn new(cache_path: impl AsRef<Path>) -> Self {
+        Self {
+            undo_log: cache_path.as_ref().join(UNDO_LOG_FILENAME),
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn undo_log(&self) -> &Path {
+        &self.undo_log
+    }
+
+    /// Tags a folder as configured for its library, and returns where the folder is afterwards.
+    /// Tagging is best effort: a folder that can't be tagged is logged and left as it is.
+    pub fn tag(&self, folder: &Path, library: &LibraryProfile, ids: &FolderIds) -> PathBuf {
+        let result = match library.tag_folders {
+            FolderTagging::Off => return folder.to_path_buf(),
+            FolderTagging::Sidecar => self.write_sidecar(folder, ids, library.tag_dry_run).map(|_| folder.to_path_buf()),
+            FolderTagging::Rename => self.rename(folder, library, ids),
+        };
+
+        result.unwrap_or_else(|e| {
+            warn!("Not tagging {:?}: {:#}", folder, e);
+            folder.to_path_buf()
+        })
+    }
+
+    fn write_sidecar(&self, folder: &Path, ids: &FolderIds, dry_run: bool) -> anyhow::Result<()> {
+        let path = folder.join(SIDECAR_FILENAME);
+        if path.exists() {
+            return match FolderIds::read(folder) {
+                Some(existing) if &existing == ids => Ok(()),
+                _ => Err(anyhow!("{:?} already exists with other IDs", path)),
+            };
+        }
+
+        let contents = serde_json::to_string_pretty(ids)?;
+        if dry_run {
+            info!("Dry run: would write {:?}: {}", path, contents);
+            return Ok(());
+        }
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        fs::write(&path, contents).with_context(|| format!("Failed to write {:?}", path))?;
+        self.record(TagChange::Sidecar { path: path.clone() })?;
+        info!("Wrote IDs to {:?}", path);
+        Ok(())
+    }
+
+    fn rename(&self, folder: &Path, library: &LibraryProfile, ids: &FolderIds) -> anyhow::Result<PathBuf> {
+        let patterns = library.id_patterns()?;
+        let (kind, id, patterns) = match (&library.data_source, &ids.imdb, ids.tmdb) {
+            (DataSource::Imdb, Some(imdb_id), _) => (IdKind::Imdb, imdb_id.clone(), &patterns.imdb),
+            (DataSource::Tmdb, _, Some(tmdb_id)) => (IdKind::Tmdb, tmdb_id.to_string(), &patterns.tmdb),
+            _ => return Err(anyhow!("no {:?} ID to tag the folder with", library.data_source)),
+        };
+
+        let name = folder
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow!("folder name is not valid UTF-8"))?;
+
+        // The tag follows the library's first preset whose patterns read it back, or Plex's
+        // convention, which the default ID patterns read.
+        let presets = if library.id_presets.is_empty() { &[IdPreset::Plex][..] } else { &library.id_presets[..] };
+        let target = presets
+            .iter()
+            .map(|preset| format!("{} {}", name, preset.tag(kind, &id)))
+            .find(|tagged| patterns.find(tagged) == Some(id.as_str()))
+            .map(|tagged| folder.with_file_name(tagged))
+            .ok_or_else(|| anyhow!("the library's ID patterns would not read the {} ID {} back from a tagged name", kind.group(), id))?;
+
+        if library.tag_dry_run {
+            info!("Dry run: would rename {:?} to {:?}", folder, target);
+            return Ok(folder.to_path_buf());
+        }
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        if target.exists() {
+            return Err(anyhow!("{:?} already exists", target));
+        }
+        fs::rename(folder, &target).with_context(|| format!("Failed to rename {:?} to {:?}", folder, target))?;
+        // The folder has moved either way, so a failed undo entry must not hide its new path.
+        if let Err(e) = self.record(TagChange::Rename { from: folder.to_path_buf(), to: target.clone() }) {
+            warn!("Renamed {:?} to {:?}, but it can't be undone: {:#}", folder, target, e);
+        }
+        info!("Renamed {:?} to {:?}", folder, target);
+        Ok(target)
+    }
+
+    fn record(&self, change: TagChange) -> anyhow::Result<()> {
+        let at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        let mut line = serde_json::to_string(&UndoEntry { at, change })?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.undo_log)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .with_context(|| format!("Failed to write the undo log {:?}", self.undo_log))
+    }
+
+    /// Reverts the changes in the undo log, newest first, and returns them. Changes that can't be
+    /// reverted, e.g. because a renamed folder was moved since, are logged and kept in the log.
+    pub fn undo(&self, dry_run: bool) -> anyhow::Result<Vec<TagChange>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let contents = match fs::read_to_string(&self.undo_log) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read the undo log {:?}", self.undo_log)),
+        };
+
+        let mut undone = vec![];
+        let mut kept = vec![];
+        for line in contents.lines().rev().filter(|line| !line.trim().is_empty()) {
+            let entry: UndoEntry = serde_json::from_str(line).with_context(|| format!("Invalid entry in the undo log: {}", line))?;
+            if dry_run {
+                info!("Dry run: would undo {:?}", entry.change);
+                undone.push(entry.change);
+                continue;
+            }
+            match revert(&entry.change) {
+                Ok(()) => undone.push(entry.change),
+                Err(e) => {
+                    warn!("Could not undo {:?}: {:#}", entry.change, e);
+                    kept.push(line);
+                }
+            }
+        }
+
+        if !dry_run {
+            let remaining: String = kept.iter().rev().map(|line| format!("{}\n", line)).collect();
+            fs::write(&self.undo_log, remaining).with_context(|| format!("Failed to update the undo log {:?}", self.undo_log))?;
+        }
+        Ok(undone)
+    }
+}
+
+fn revert(change: &TagChange) -> anyhow::Result<()> {
+    match change {
+        TagChange::Rename { from, to } => {
+            if from.exists() {
+                return Err(anyhow!("{:?} exists again", from));
+            }
+            fs::rename(to, from).with_context(|| format!("Failed to rename {:?} back to {:?}", to, from))
+        }
+        TagChange::Sidecar { path } => match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e).with_context(|| format!("Failed to remove {:?}", path)),
+            _ => Ok(()),
+        },
+    }
+}
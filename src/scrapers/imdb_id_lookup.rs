@@ -0,0 +1,51 @@
+//! Maps TMDB IDs to IMDb and TheTVDB IDs for the TMDB scraper and the title resolver.
+
+use tracing::{info, warn};
+use crate::caching::tmdb_to_imdb_cache::{ExternalTitleIds, TmdbToImdbCache};
+use crate::request_clients::client_settings::Upstream;
+use crate::request_clients::request_errors::error::Error;
+use crate::request_clients::tmdb_client::external_ids_endpoints::ExternalIds;
+use crate::request_clients::tmdb_client::tmdb_request_client::TmdbRequestClient;
+use crate::scrapers::media_directories::FolderType;
+use crate::scrapers::scrape_error::{ScrapeError, ScrapeErrorKind};
+
+/// The external IDs of a TMDB title. Looked up in the cache first, then in the title's external
+/// IDs on TMDB, which are cached for next time when they include an IMDb ID.
+pub async fn lookup_external_ids(
+    tmdb_client: &TmdbRequestClient,
+    cache: &TmdbToImdbCache,
+    tmdb_id: &str,
+    folder_type: &FolderType,
+) -> Result<ExternalTitleIds, ScrapeError> {
+    let media_type = folder_type.tmdb_media_type();
+    let cache_error = |e: anyhow::Error| ScrapeError::new(ScrapeErrorKind::Internal, "TMDB to IMDb cache failed").with_source(e.into_boxed_dyn_error());
+    if let Some(ids) = cache.try_get(media_type, tmdb_id).map_err(cache_error)? {
+        return Ok(ids);
+    }
+
+    info!("Fetching IMDB ID for TMDB {} {}", media_type, tmdb_id);
+    let lookup_error = |e: Error| ScrapeError::request(Upstream::Tmdb, format!("failed to fetch the external IDs of {}", tmdb_id), e);
+    let external_ids = tmdb_client.external_ids();
+    let external_ids = match folder_type {
+        FolderType::Movie => ExternalIds::Movie(external_ids.get_for_movie(tmdb_id).await.map_err(lookup_error)?),
+        FolderType::TvShow => ExternalIds::Tv(external_ids.get_for_tv(tmdb_id).await.map_err(lookup_error)?),
+    };
+
+    let ids = ExternalTitleIds { imdb_id: external_ids.imdb_id().map(str::to_string), tvdb_id: external_ids.tvdb_id() };
+    if ids.imdb_id.is_some() {
+        cache.add(media_type, tmdb_id, &ids).map_err(cache_error)?;
+    } else {
+        warn!("No IMDB ID found for TMDB {} {}", media_type, tmdb_id);
+    }
+    Ok(ids)
+}
+
+/// The IMDb ID of a TMDB title, or `None` when TMDB doesn't know one.
+pub async fn lookup_imdb_id(
+    tmdb_client: &TmdbRequestClient,
+    cache: &TmdbToImdbCache,
+    tmdb_id: &str,
+    folder_type: &FolderType,
+) -> Result<Option<String>, ScrapeError> {
+    Ok(lookup_external_ids(tmdb_client, cache, tmdb_id, folder_type).await?.imdb_id)
+}
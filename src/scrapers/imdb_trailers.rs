@@ -3,55 +3,66 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 use anyhow::Result;
-use regex::Regex;
-use scraper::{Html, Selector};
 use tracing::{error, info, warn};
 use url::Url;
 use std::fs::File;
 use std::io::{Read, Write};
 use std::sync::Arc;
 use async_trait::async_trait;
-use once_cell::sync::{Lazy};
-use crate::configuration::configuration_provider::AppConfig;
-use crate::request_clients::get_imdb_client;
+use crate::configuration::configuration_provider::LibraryProfile;
+use crate::request_clients::client_settings::Upstream;
 use crate::request_clients::imdb_client::imdb_request_client::ImdbRequestClient;
-use crate::request_clients::request_errors::error::Error;
-use crate::scrapers::media_directories::{process_media_folders, FolderType, BACKDROPS_FOLDER};
-
-const VIDEO_PROPS_PATH: &str = "/props/pageProps/videoPlaybackData/video/playbackURLs";
-const SCRIPT_SELECTOR: &str = "script#\\__NEXT_DATA__";
-const VIDEO_SELECTOR: &str = "a[href*=\"/video/vi\"]";
-const VIDEO_MIME_TYPE: &str = "videoMimeType";
-const HREF_ATTR: &str = "href";
-const VIDEO_DEFINITION_ATTR: &str = "videoDefinition";
-const TRAILER: &str = "trailer";
-const URL: &str = "url";
+use crate::request_clients::imdb_client::video_gallery_endpoints::VideoKind;
+use crate::request_clients::imdb_client::video_page_endpoints::best_rendition;
+use crate::scrapers::folder_tagger::{FolderIds, FolderTagger};
+use crate::scrapers::media_directories::{title_name, trailer_path, FolderType};
+use crate::scrapers::outcomes::TitleOutcome;
+use crate::scrapers::scrape_error::{ScrapeError, ScrapeErrorKind};
+use crate::scrapers::title_resolver::{Resolution, TitleResolver};
+
 const TYPE_QUERY: &str = "#t=8";
 
-static PARSED_VIDEO_SELECTOR: Lazy<Selector> = Lazy::new(|| {
-    Selector::parse(VIDEO_SELECTOR).expect("Invalid VIDEO_SELECTOR")
-});
+/// The trailer chosen for a title, and the direct playback URL that is written to the `.strm` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedTrailer {
+    pub imdb_id: String,
+    pub video_id: String,
+    pub url: String,
+}
 
 #[derive(Debug)]
-pub struct ImdbTrailerScraper;
-
-
-// This will be replaced with configurable regex
+pub struct ImdbTrailerScraper {
+    pub imdb_client: Arc<ImdbRequestClient>,
+    /// Looks up folders without an ID tag, for libraries that resolve untagged folders.
+    title_resolver: Option<Arc<TitleResolver>>,
+    /// Writes resolved IDs back to the folders, for libraries that tag them.
+    folder_tagger: Option<Arc<FolderTagger>>,
+}
 
 #[async_trait]
 impl TrailerScraper for ImdbTrailerScraper {
-    async fn scan_and_refresh_trailers(self: Arc<Self>, config: &Arc<AppConfig>) -> Result<()> {
-        self.perform_scan_and_refresh_imdb(config).await
+    async fn process_path(&self, path: PathBuf, library: Arc<LibraryProfile>, folder_type: FolderType) -> TitleOutcome {
+        self.process_path_internal(path, library, folder_type).await
     }
 
-    async fn process_path(&self, path: PathBuf, config: Arc<AppConfig>, _: FolderType) {
-        self.process_path_internal(path, config).await;
+    fn halted(&self) -> Option<String> {
+        self.imdb_client.paused_reason()
     }
 }
 
 impl ImdbTrailerScraper {
-    pub async fn perform_scan_and_refresh_imdb(self: Arc<Self>, app_config: &Arc<AppConfig>) -> Result<()> {
-        process_media_folders(app_config, self as Arc<dyn TrailerScraper>).await
+    pub fn new(imdb_client: Arc<ImdbRequestClient>) -> Self {
+        Self { imdb_client, title_resolver: None, folder_tagger: None }
+    }
+
+    pub fn with_title_resolver(mut self, title_resolver: Arc<TitleResolver>) -> Self {
+        self.title_resolver = Some(title_resolver);
+        self
+    }
+
+    pub fn with_folder_tagger(mut self, folder_tagger: Arc<FolderTagger>) -> Self {
+        self.folder_tagger = Some(folder_tagger);
+        self
     }
 
     pub fn is_strm_expired(&self, strm_path: &Path) -> Result<bool> {
@@ -78,177 +89,198 @@ impl ImdbTrailerScraper {
         }
     }
 
-    pub fn create_or_update_strm_file(&self, folder: &Path, app_config: &AppConfig, video_url: &str) -> Result<()> {
-        let backdrops = folder.join(BACKDROPS_FOLDER);
-        fs::create_dir_all(&backdrops)?;
-        let strm_path = backdrops.join(&app_config.video_filename);
+    /// Writes the trailer link of a title folder or movie file, see [trailer_path].
+    pub fn create_or_update_strm_file(&self, title: &Path, library: &LibraryProfile, video_url: &str) -> Result<()> {
+        let strm_path = trailer_path(title, library);
+        if let Some(parent) = strm_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
         let mut f = File::create(&strm_path)?;
         f.write_all(video_url.as_bytes())?;
         info!("Updated {:?}", strm_path);
         Ok(())
     }
 
-    pub async fn get_trailer_video_page_url(
+    /// The video ID of the title's trailer, or of its first video when none is labelled a trailer.
+    pub async fn find_trailer_video(
         &self,
         client: &ImdbRequestClient,
         imdb_id: &str,
-    ) -> Result<Option<String>> {
-        let path = format!("/title/{}/videogallery/?sort=date,asc", imdb_id);
-        let res = match client.get_raw(&path).await {
-            Ok(r) => r,
+    ) -> Result<Option<String>, ScrapeError> {
+        let videos = match client.video_gallery().get(imdb_id).await {
+            Ok(Some(videos)) => videos,
+            Ok(None) => {
+                warn!("Title {} not found on IMDb", imdb_id);
+                return Ok(None);
+            }
             Err(e) => {
-                error!("Request failed for {}: {e}", &path);
-                return Err(e.into());
+                error!("Failed to fetch trailers for {}: {e}", imdb_id);
+                return Err(ScrapeError::request(Upstream::Imdb, format!("failed to fetch the video gallery of {}", imdb_id), e));
             }
         };
 
-        if !res.status().is_success() {
-            error!("Failed to fetch trailers for {} (status {})", imdb_id, res.status());
-            return Ok(None);
+        let video = videos
+            .iter()
+            .find(|video| video.kind == VideoKind::Trailer)
+            .or_else(|| videos.first());
+
+        if video.is_none() {
+            warn!("No video found for {}", imdb_id);
         }
+        Ok(video.map(|video| video.id.clone()))
+    }
 
-        let body = res.text().await?;
-        let doc = Html::parse_document(&body);
+    async fn process_path_internal(
+        &self,
+        path: PathBuf,
+        library: Arc<LibraryProfile>,
+        folder_type: FolderType,
+    ) -> TitleOutcome {
+        let Some(name) = title_name(&path) else {
+            return TitleOutcome::NoId { reason: "title name is not valid UTF-8".to_string() };
+        };
+
+        let patterns = match library.id_patterns() {
+            Ok(patterns) => patterns,
+            Err(e) => {
+                error!("Invalid ID patterns: {:#}", e);
+                return TitleOutcome::NoId { reason: format!("invalid ID patterns: {:#}", e) };
+            }
+        };
 
-        for el in doc.select(&PARSED_VIDEO_SELECTOR) {
-            let text = el.text().collect::<String>().to_lowercase();
-            if text.contains(TRAILER) {
-                if let Some(href) = el.value().attr(HREF_ATTR) {
-                    return Ok(Some(href.to_string()));
+        let imdb_id = match patterns.imdb.find(name) {
+            Some(id) => id.to_string(),
+            None => match FolderIds::read(&path).and_then(|ids| ids.imdb) {
+                Some(imdb_id) => imdb_id,
+                None if library.resolve_untagged => return self.refresh_untagged(path, library, folder_type).await,
+                None => {
+                    warn!("No IMDB ID found in path: {:?}", path);
+                    return TitleOutcome::NoId { reason: "no IMDb ID in folder name".to_string() };
                 }
+            },
+        };
+
+        self.refresh_if_expired(&imdb_id, path, library).await
+    }
+
+    /// Resolves a folder without an ID tag on TMDB, tags the folder if its library is set up to,
+    /// and refreshes its trailer.
+    pub(crate) async fn refresh_untagged(&self, path: PathBuf, library: Arc<LibraryProfile>, folder_type: FolderType) -> TitleOutcome {
+        let (imdb_id, tmdb_id, tvdb_id) = match self.resolve_untagged(&path, folder_type).await {
+            Ok(ids) => ids,
+            Err(outcome) => return outcome,
+        };
+
+        // Movie files are never renamed, their folder may hold other titles
+        let path = match &self.folder_tagger {
+            Some(tagger) if path.is_dir() => tagger.tag(&path, &library, &FolderIds { imdb: Some(imdb_id.clone()), tmdb: Some(tmdb_id), tvdb: tvdb_id }),
+            _ => path,
+        };
+        self.refresh_if_expired(&imdb_id, path, library).await
+    }
+
+    /// The IMDb, TMDB and, for shows, TheTVDB IDs of a folder without an ID tag, found by searching
+    /// TMDB for its name. `Err` is the outcome to report for the folder instead.
+    async fn resolve_untagged(&self, path: &Path, folder_type: FolderType) -> Result<(String, u64, Option<u64>), TitleOutcome> {
+        let no_id = |reason: String| {
+            warn!("No ID found in path: {:?} ({})", path, reason);
+            Err(TitleOutcome::NoId { reason })
+        };
+        let Some(resolver) = &self.title_resolver else {
+            return no_id("no ID in folder name, and no TMDB client to resolve it".to_string());
+        };
+        let name = if path.is_file() { path.file_stem() } else { path.file_name() };
+        let folder_name = name.and_then(|name| name.to_str()).unwrap_or_default();
+
+        match resolver.resolve(folder_name, folder_type).await {
+            Ok(Resolution::Matched { tmdb_id, imdb_id: Some(imdb_id), tvdb_id }) => {
+                info!("Resolved {:?} to TMDB ID {} and IMDb ID {}", path, tmdb_id, imdb_id);
+                Ok((imdb_id, tmdb_id, tvdb_id))
+            }
+            Ok(Resolution::Matched { tmdb_id, imdb_id: None, .. }) => no_id(format!("TMDB has no IMDb ID for {}", tmdb_id)),
+            Ok(Resolution::Ambiguous { candidates }) => Err(TitleOutcome::NoId {
+                reason: format!("folder name matches {} TMDB titles, tag it by hand", candidates.len()),
+            }),
+            Ok(Resolution::NoMatch) => no_id("no TMDB title matches the folder's title and year".to_string()),
+            Ok(Resolution::Unparsable) => no_id("no ID, or title and year, in folder name".to_string()),
+            Err(e) => {
+                warn!("Failed to resolve {:?} on TMDB: {:#}", path, e);
+                Err(TitleOutcome::lookup_failed(None, &e))
             }
         }
+    }
 
-        if let Some(el) = doc.select(&PARSED_VIDEO_SELECTOR).next() {
-            if let Some(href) = el.value().attr(HREF_ATTR) {
-                return Ok(Some(href.to_string()));
+    /// Refreshes the trailer of a title folder or movie file unless its link is still valid.
+    pub(crate) async fn refresh_if_expired(&self, imdb_id: &str, path: PathBuf, library: Arc<LibraryProfile>) -> TitleOutcome {
+        let strm_path = trailer_path(&path, &library);
+
+        if let Ok(expired) = self.is_strm_expired(&strm_path) {
+            if !expired {
+                info!("Trailer still valid for {imdb_id} in {:?}", path);
+                return TitleOutcome::StillValid { id: imdb_id.to_string() };
             }
         }
 
-        warn!("No video found for {}", imdb_id);
-        Ok(None)
+        info!("Refreshing trailer for {imdb_id} in {:?}", path);
+        self.refresh_imdb_trailer(imdb_id, path.clone(), library).await
     }
 
-    async fn process_path_internal(
-        &self,
-        path: PathBuf,
-        config: Arc<AppConfig>,
-    ) {
-        if let Some(path_str) = path.to_str() {
-            // Use configurable regex pattern
-            let regex = match Regex::new(&config.imdb_id_regex) {
-                Ok(regex) => regex,
-                Err(e) => {
-                    error!("Invalid IMDb ID regex pattern: {}", e);
-                    return;
-                }
-            };
-            
-            if let Some(cap) = regex.captures(path_str) {
-                let imdb_id = &cap[1];
-                let backdrops_path = path.join(BACKDROPS_FOLDER);
-                let strm_path = backdrops_path.join(&config.video_filename);
-
-                if let Ok(expired) = self.is_strm_expired(&strm_path) {
-                    if !expired {
-                        info!("Trailer still valid for {imdb_id} in {:?}", path);
-                        return;
-                    }
-                }
+    pub(crate) async fn refresh_imdb_trailer(&self, imdb_id: &str, path: PathBuf, library: Arc<LibraryProfile>) -> TitleOutcome {
+        let id = imdb_id.to_string();
 
-                info!("Refreshing trailer for {imdb_id} in {:?}", path);
-                self.refresh_imdb_trailer(imdb_id, path.clone(), config).await;
-                
-            } else {
-                warn!("No IMDB ID found in path: {:?}", path);
+        let trailer = match self.resolve_trailer(imdb_id).await {
+            Ok(Some(trailer)) => trailer,
+            Ok(None) => return TitleOutcome::NoTrailer { id },
+            Err(e) => return TitleOutcome::lookup_failed(Some(id), &e),
+        };
+
+        match self.create_or_update_strm_file(&path, &library, &trailer.url) {
+            Ok(()) => TitleOutcome::Refreshed { id },
+            Err(e) => {
+                error!("Failed to write .strm file: {:?}", e);
+                TitleOutcome::WriteFailed { id, reason: format!("{:#}", e) }
             }
         }
     }
-    
-    pub(crate) async fn refresh_imdb_trailer(&self, imdb_id: &str, path: PathBuf, config: Arc<AppConfig>) {
-        let client = get_imdb_client().clone();
-
-        if let Ok(Some(video_page_url)) = self.get_trailer_video_page_url(&client, imdb_id).await {
-            if let Ok(Some(direct_url)) = self.get_direct_video_url_from_page(&client, &video_page_url).await {
-                if let Err(e) = self.create_or_update_strm_file(&path, &config, &direct_url) {
-                    error!("Failed to write .strm file: {:?}", e);
-                }
-            }
-        }
+
+    /// Looks up the current trailer for an IMDb title without touching the filesystem.
+    pub async fn resolve_trailer(&self, imdb_id: &str) -> Result<Option<ResolvedTrailer>, ScrapeError> {
+        let client = &self.imdb_client;
+
+        let Some(video_id) = self.find_trailer_video(client, imdb_id).await? else {
+            return Ok(None);
+        };
+
+        let url = self.get_direct_video_url(client, &video_id).await?;
+
+        Ok(Some(ResolvedTrailer {
+            imdb_id: imdb_id.to_string(),
+            video_id,
+            url,
+        }))
     }
 
-    pub async fn get_direct_video_url_from_page(
+    /// The playback URL of the best rendition of a video. A video page without any playback URLs
+    /// is reported as [ScrapeErrorKind::ParseChanged], since every video page should have some.
+    pub async fn get_direct_video_url(
         &self,
         client: &ImdbRequestClient,
-        video_page_path: &str,
-    ) -> Result<Option<String>, Error> {
-        let res = match client.get_raw(video_page_path).await {
-            Ok(r) => r,
+        video_id: &str,
+    ) -> Result<String, ScrapeError> {
+        let renditions = match client.video_page().get(video_id).await {
+            Ok(renditions) => renditions,
             Err(e) => {
-                error!("Request failed for {}: {e}", video_page_path);
-                return Err(e);
+                error!("Failed to fetch video page for {}: {e}", video_id);
+                return Err(ScrapeError::request(Upstream::Imdb, format!("failed to fetch the video page of {}", video_id), e));
             }
         };
 
-        if !res.status().is_success() {
-            error!("Failed to fetch video page: {} (status {})", video_page_path, res.status());
-            return Ok(None);
-        }
-
-        let body = res.text().await?;
-        let doc = Html::parse_document(&body);
-
-        let script_selector = Selector::parse(SCRIPT_SELECTOR).unwrap();
-        let script_tag = doc.select(&script_selector).next();
-
-        if let Some(tag) = script_tag {
-            if let Some(json_text) = tag.inner_html().lines().next() {
-                let data: serde_json::Value = serde_json::from_str(json_text.trim())?;
-
-                if let Some(playbacks) = data.pointer(VIDEO_PROPS_PATH) {
-                    let empty = vec![];
-                    let mp4_urls: Vec<_> = playbacks
-                        .as_array()
-                        .unwrap_or(&empty)
-                        .iter()
-                        .filter(|entry| {
-                            entry
-                                .get(VIDEO_MIME_TYPE)
-                                .and_then(|v| v.as_str())
-                                == Some("MP4")
-                        })
-                        .collect();
-
-                    if let Some(best_url) = mp4_urls
-                        .iter()
-                        .max_by_key(|e| {
-                            e.get(VIDEO_DEFINITION_ATTR)
-                                .and_then(|v| v.as_str())
-                                .map(|s| match s {
-                                    d if d.contains("1080") => 3,
-                                    d if d.contains("720") => 2,
-                                    d if d.contains("480") => 1,
-                                    _ => 0,
-                                })
-                                .unwrap_or(0)
-                        })
-                        .and_then(|e| e.get(URL).and_then(|u| u.as_str()))
-                    {
-                        return Ok(Some(format!("{}{}", best_url, TYPE_QUERY)));
-                    }
-
-                    if let Some(first) = playbacks
-                        .get(0)
-                        .and_then(|e| e.get(URL))
-                        .and_then(|u| u.as_str())
-                    {
-                        return Ok(Some(format!("{}{}", first, TYPE_QUERY)));
-                    }
-                }
+        match best_rendition(&renditions) {
+            Some(rendition) => Ok(format!("{}{}", rendition.url, TYPE_QUERY)),
+            None => {
+                warn!("No JSON playback URLs found for {}", video_id);
+                Err(ScrapeError::new(ScrapeErrorKind::ParseChanged, format!("no playback URLs found on the video page of {}", video_id)))
             }
         }
-
-        warn!("No JSON playback URLs found for {}", video_page_path);
-        Ok(None)
     }
 }
\ No newline at end of file
@@ -1,21 +1,65 @@
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use tokio::sync::Semaphore;
 use tracing::{error, warn};
 use walkdir::WalkDir;
-use crate::configuration::configuration_provider::AppConfig;
+use crate::configuration::configuration_provider::{AppConfig, LibraryProfile};
+use crate::scrapers::events::{emit, ScanEvent, ScanEventSender};
+use crate::scrapers::folder_tagger::SIDECAR_FILENAME;
+use crate::scrapers::outcomes::{RunSummary, TitleOutcome};
+use crate::scrapers::scrape_error::{ScrapeError, ScrapeErrorKind};
+use crate::scrapers::title_resolver::FolderTitle;
 use crate::scrapers::traits::TrailerScraper;
 
-pub const BACKDROPS_FOLDER: &str = "backdrops";
+/// Folders that hold a title's extras rather than titles of their own, compared case-insensitively.
+const EXTRAS_FOLDERS: [&str; 14] = [
+    "backdrops",
+    "extras",
+    "trailers",
+    "featurettes",
+    "behind the scenes",
+    "deleted scenes",
+    "interviews",
+    "scenes",
+    "shorts",
+    "specials",
+    "other",
+    "subs",
+    "subtitles",
+    "@eadir",
+];
 
-#[derive(Debug, Clone)]
+/// The extensions of the movie files that can be titles on their own, compared case-insensitively.
+const MEDIA_EXTENSIONS: [&str; 12] = ["mkv", "mp4", "m4v", "avi", "mov", "wmv", "webm", "mpg", "mpeg", "ts", "m2ts", "iso"];
+/// Appended to a movie file's name for the trailer link written next to it.
+pub const FILE_TRAILER_SUFFIX: &str = "-trailer.strm";
+
+static SEASON_FOLDER_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(season|series|staffel|saison|temporada)[\s._-]*\d+$|^s\d{1,3}$").expect("Invalid season folder regex")
+});
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum FolderType {
     TvShow,
     Movie,
 }
 
+impl FolderType {
+    /// The media type TMDB files the title under, `movie` or `tv`.
+    pub fn tmdb_media_type(&self) -> &'static str {
+        match self {
+            FolderType::Movie => "movie",
+            FolderType::TvShow => "tv",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct TaggedDir {
     pub path: PathBuf,
@@ -24,74 +68,235 @@ pub struct TaggedDir {
 
 pub async fn process_media_folders(
     app_config: &Arc<AppConfig>,
+    library: &Arc<LibraryProfile>,
     scraper: Arc<dyn TrailerScraper>,
-) -> anyhow::Result<()> {
-    let scan_path = PathBuf::from(&app_config.scan_path).canonicalize()?;
+    events: Option<&ScanEventSender>,
+) -> anyhow::Result<RunSummary> {
+    let started = Instant::now();
+    let scan_path = PathBuf::from(&library.scan_path).canonicalize()?;
     if !scan_path.exists() {
         error!("Provided path does not exist: {:?}", scan_path);
-        return Ok(());
+        return Ok(RunSummary::default());
     }
 
-    let tv_dirs: Vec<_> = app_config
+    let tv_dirs: Vec<_> = library
         .tv_folders
         .iter()
-        .flat_map(|f| scan_tagged_subdirs(&scan_path, f, FolderType::TvShow))
+        .flat_map(|f| scan_tagged_subdirs(&scan_path, f, FolderType::TvShow, library))
         .collect();
 
-    let movie_dirs: Vec<_> = app_config
+    let movie_dirs: Vec<_> = library
         .movie_folders
         .iter()
-        .flat_map(|f| scan_tagged_subdirs(&scan_path, f, FolderType::Movie))
+        .flat_map(|f| scan_tagged_subdirs(&scan_path, f, FolderType::Movie, library))
         .collect();
 
     let all_dirs = tv_dirs.into_iter().chain(movie_dirs).collect::<Vec<_>>();
 
     let total = all_dirs.len();
+    emit(events, ScanEvent::LibraryStarted { library: library.name.clone(), titles: total });
+
+    let mut summary = RunSummary::default();
+
     if total == 0 {
-        warn!("No valid media directories found.");
-        return Ok(());
+        warn!("No valid media directories found in library '{}'.", library.name);
+        summary.duration = started.elapsed();
+        emit(events, ScanEvent::LibraryFinished { library: library.name.clone(), summary: summary.clone() });
+        return Ok(summary);
     }
 
     let semaphore = Arc::new(Semaphore::new(app_config.threads));
     let mut tasks = FuturesUnordered::new();
 
-    for tagged_dir in all_dirs {
+    for (index, tagged_dir) in all_dirs.into_iter().enumerate() {
         let permit = semaphore.clone().acquire_owned().await?;
-        let config = Arc::clone(app_config);
+
+        if let Some(reason) = scraper.halted() {
+            warn!("Stopping scan of library '{}': {}", library.name, reason);
+            summary.skipped = total - index;
+            summary.cut_short = Some(reason);
+            break;
+        }
+        let library = Arc::clone(library);
         let service = Arc::clone(&scraper);
+        let events = events.cloned();
         let path = tagged_dir.path;
         let folder_type = tagged_dir.folder_type;
 
-        tasks.push(tokio::spawn(async move {
-            let _permit = permit;
-            service.process_path(path, config, folder_type).await;
-        }));
+        let handle = tokio::spawn({
+            let path = path.clone();
+            async move {
+                let _permit = permit;
+                process_title(service.as_ref(), path, library, folder_type, events.as_ref()).await
+            }
+        });
+        tasks.push(async move { (path, handle.await) });
     }
 
-    while let Some(res) = tasks.next().await {
-        if let Err(e) = res {
-            error!("A task panicked or failed: {:?}", e);
+    while let Some((path, res)) = tasks.next().await {
+        match res {
+            Ok(outcome) => summary.record(path, outcome),
+            Err(e) => {
+                error!("A task panicked or failed: {:?}", e);
+                let err = ScrapeError::new(ScrapeErrorKind::Internal, "task failed").with_source(e);
+                summary.record(path, TitleOutcome::lookup_failed(None, &err));
+            }
         }
     }
 
-    Ok(())
+    summary.duration = started.elapsed();
+    summary.log(&format!("Library '{}'", library.name));
+    emit(events, ScanEvent::LibraryFinished { library: library.name.clone(), summary: summary.clone() });
+
+    Ok(summary)
+}
+
+/// Processes a single title folder and publishes its start and finish events.
+pub async fn process_title(
+    scraper: &dyn TrailerScraper,
+    path: PathBuf,
+    library: Arc<LibraryProfile>,
+    folder_type: FolderType,
+    events: Option<&ScanEventSender>,
+) -> TitleOutcome {
+    emit(events, ScanEvent::TitleStarted { library: library.name.clone(), path: path.clone() });
+    let outcome = scraper.process_path(path.clone(), Arc::clone(&library), folder_type).await;
+    emit(events, ScanEvent::TitleFinished { library: library.name.clone(), path, outcome: outcome.clone() });
+    outcome
+}
+
+/// The part of a title path that is tagged with its ID: the folder name, or the name without
+/// its extension for a movie file. Parent folders never count, they may be tagged for another title.
+pub fn title_name(title: &Path) -> Option<&str> {
+    let name = if title.is_file() { title.file_stem() } else { title.file_name() };
+    name.and_then(|name| name.to_str())
+}
+
+/// Where the trailer link of a title is written: the library's video file in its trailer folder
+/// for a title folder, or `<name>-trailer.strm` next to a movie file, whose folder may hold others.
+pub fn trailer_path(title: &Path, library: &LibraryProfile) -> PathBuf {
+    if title.is_file() {
+        let stem = title.file_stem().unwrap_or_default().to_string_lossy();
+        title.with_file_name(format!("{}{}", stem, FILE_TRAILER_SUFFIX))
+    } else {
+        title.join(&library.trailer_folder).join(&library.video_filename)
+    }
+}
+
+/// Finds the library and folder type that a title folder belongs to.
+pub fn locate_title(
+    libraries: &[Arc<LibraryProfile>],
+    path: &Path,
+) -> Option<(Arc<LibraryProfile>, FolderType)> {
+    let path = path.canonicalize().ok()?;
+
+    libraries.iter().find_map(|library| {
+        let root = Path::new(&library.scan_path);
+        let folders = library
+            .tv_folders
+            .iter()
+            .map(|f| (f, FolderType::TvShow))
+            .chain(library.movie_folders.iter().map(|f| (f, FolderType::Movie)));
+
+        for (folder, folder_type) in folders {
+            if let Ok(folder_path) = root.join(folder).canonicalize() {
+                let depth = path.strip_prefix(&folder_path).map(|relative| relative.components().count()).unwrap_or(0);
+                if (1..=library.scan_depth.max(1)).contains(&depth) {
+                    return Some((Arc::clone(library), folder_type));
+                }
+            }
+        }
+        None
+    })
 }
 
-fn scan_tagged_subdirs(base: &std::path::Path, subfolder: &str, folder_type: FolderType) -> Vec<TaggedDir> {
+/// Finds the title folders in a TV or movie folder, down to the library's scan depth. A folder is
+/// a title when its name matches an ID pattern, it has an ID sidecar, its `Title (Year)` name can
+/// be resolved, or it is at the scan depth; other folders are taken to group titles, e.g. by
+/// letter or collection. The scan doesn't look inside titles, extras or season folders. Movie
+/// files whose names match an ID pattern are titles too, for libraries without a folder per movie.
+fn scan_tagged_subdirs(base: &Path, subfolder: &str, folder_type: FolderType, library: &LibraryProfile) -> Vec<TaggedDir> {
     let path = base.join(subfolder);
     if !path.exists() || !path.is_dir() {
         return vec![];
     }
 
-    WalkDir::new(&path)
+    let max_depth = library.scan_depth.max(1);
+    // Symlinks are followed; walkdir reports loops as errors, and folders reached twice are skipped
+    let mut visited = HashSet::new();
+    let mut titles = vec![];
+    let mut entries = WalkDir::new(&path)
         .min_depth(1)
-        .max_depth(1)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_dir())
-        .map(|e| TaggedDir {
-            path: e.into_path(),
-            folder_type: folder_type.clone(),
+        .max_depth(max_depth)
+        .follow_links(true)
+        .sort_by_file_name()
+        .into_iter();
+
+    while let Some(entry) = entries.next() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                match e.loop_ancestor() {
+                    Some(ancestor) => warn!("Skipping symlink loop at {:?} back to {:?}", e.path().unwrap_or(&path), ancestor),
+                    None => warn!("Skipping unreadable folder: {}", e),
+                }
+                continue;
+            }
+        };
+        let name = entry.file_name().to_string_lossy();
+        if !entry.file_type().is_dir() {
+            if folder_type == FolderType::Movie && is_title_file(entry.path(), library) {
+                titles.push(TaggedDir {
+                    path: entry.path().to_path_buf(),
+                    folder_type: folder_type.clone(),
+                });
+            }
+            continue;
+        }
+
+        let canonical = entry.path().canonicalize().unwrap_or_else(|_| entry.path().to_path_buf());
+        if is_extras_folder(&name, library) || !visited.insert(canonical) {
+            entries.skip_current_dir();
+            continue;
+        }
+
+        if entry.depth() == max_depth || is_title_folder(entry.path(), &name, library) {
+            titles.push(TaggedDir {
+                path: entry.path().to_path_buf(),
+                folder_type: folder_type.clone(),
+            });
+            entries.skip_current_dir();
+        }
+    }
+
+    titles
+}
+
+fn is_extras_folder(name: &str, library: &LibraryProfile) -> bool {
+    name.starts_with('.')
+        || name.eq_ignore_ascii_case(&library.trailer_folder)
+        || EXTRAS_FOLDERS.iter().any(|folder| name.eq_ignore_ascii_case(folder))
+        || SEASON_FOLDER_REGEX.is_match(name)
+}
+
+fn is_title_folder(path: &Path, name: &str, library: &LibraryProfile) -> bool {
+    let tagged = library
+        .id_patterns()
+        .is_ok_and(|patterns| patterns.imdb.find(name).is_some() || patterns.tmdb.find(name).is_some());
+
+    tagged || path.join(SIDECAR_FILENAME).is_file() || (library.resolve_untagged && FolderTitle::parse(name).is_some())
+}
+
+fn is_title_file(path: &Path, library: &LibraryProfile) -> bool {
+    let is_media = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| MEDIA_EXTENSIONS.iter().any(|media| extension.eq_ignore_ascii_case(media)));
+
+    is_media
+        && title_name(path).is_some_and(|name| {
+            library
+                .id_patterns()
+                .is_ok_and(|patterns| patterns.imdb.find(name).is_some() || patterns.tmdb.find(name).is_some())
         })
-        .collect()
-}
\ No newline at end of file
+}
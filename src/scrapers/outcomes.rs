@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{info, warn};
+use crate::scrapers::scrape_error::{ScrapeError, ScrapeErrorKind};
+
+/// Maximum number of individual failures listed when a summary is logged.
+const LOGGED_FAILURES: usize = 20;
+
+/// What happened to a single title during a scan.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TitleOutcome {
+    /// A new trailer link was written.
+    Refreshed { id: String },
+    /// The existing trailer link has not expired yet.
+    StillValid { id: String },
+    /// No usable ID could be found for the folder.
+    NoId { reason: String },
+    /// The title was found but has no trailer to link to.
+    NoTrailer { id: String },
+    /// Looking up the ID or trailer failed, e.g. because of a network or upstream error.
+    LookupFailed { id: Option<String>, kind: ScrapeErrorKind, reason: String },
+    /// The trailer was found but could not be written to disk.
+    WriteFailed { id: String, reason: String },
+}
+
+impl TitleOutcome {
+    pub fn lookup_failed(id: Option<String>, err: &ScrapeError) -> Self {
+        TitleOutcome::LookupFailed { id, kind: err.kind(), reason: format!("{:#}", err) }
+    }
+
+    /// The classification of a failed or trailer-less title.
+    pub fn error_kind(&self) -> Option<ScrapeErrorKind> {
+        match self {
+            TitleOutcome::NoTrailer { .. } => Some(ScrapeErrorKind::NoTrailer),
+            TitleOutcome::LookupFailed { kind, .. } => Some(*kind),
+            TitleOutcome::WriteFailed { .. } => Some(ScrapeErrorKind::FsWrite),
+            _ => None,
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(self, TitleOutcome::LookupFailed { .. } | TitleOutcome::WriteFailed { .. })
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            TitleOutcome::NoId { reason }
+            | TitleOutcome::LookupFailed { reason, .. }
+            | TitleOutcome::WriteFailed { reason, .. } => Some(reason),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TitleOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TitleOutcome::Refreshed { id } => write!(f, "refreshed trailer for {}", id),
+            TitleOutcome::StillValid { id } => write!(f, "trailer still valid for {}", id),
+            TitleOutcome::NoId { reason } => write!(f, "no ID: {}", reason),
+            TitleOutcome::NoTrailer { id } => write!(f, "no trailer found for {}", id),
+            TitleOutcome::LookupFailed { id: Some(id), kind, reason } => write!(f, "lookup failed for {} [{}]: {}", id, kind, reason),
+            TitleOutcome::LookupFailed { id: None, kind, reason } => write!(f, "lookup failed [{}]: {}", kind, reason),
+            TitleOutcome::WriteFailed { id, reason } => write!(f, "write failed for {}: {}", id, reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TitleFailure {
+    pub path: PathBuf,
+    pub outcome: TitleOutcome,
+}
+
+/// Aggregated outcomes of a scan of one or more libraries.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunSummary {
+    pub total: usize,
+    pub refreshed: usize,
+    pub still_valid: usize,
+    pub no_id: usize,
+    pub no_trailer: usize,
+    pub lookup_failed: usize,
+    pub write_failed: usize,
+    /// Titles that were never processed because the scan was cut short.
+    pub skipped: usize,
+    /// Why the scan stopped before processing every title, if it did.
+    pub cut_short: Option<String>,
+    pub duration: Duration,
+    pub failures: Vec<TitleFailure>,
+    /// Titles without a trailer, and failed titles, by [ScrapeErrorKind].
+    pub errors: BTreeMap<ScrapeErrorKind, usize>,
+    /// Libraries that could not be scanned at all, by name, with the reason.
+    pub failed_libraries: Vec<(String, String)>,
+}
+
+impl RunSummary {
+    pub fn record(&mut self, path: PathBuf, outcome: TitleOutcome) {
+        self.total += 1;
+        match &outcome {
+            TitleOutcome::Refreshed { .. } => self.refreshed += 1,
+            TitleOutcome::StillValid { .. } => self.still_valid += 1,
+            TitleOutcome::NoId { .. } => self.no_id += 1,
+            TitleOutcome::NoTrailer { .. } => self.no_trailer += 1,
+            TitleOutcome::LookupFailed { .. } => self.lookup_failed += 1,
+            TitleOutcome::WriteFailed { .. } => self.write_failed += 1,
+        }
+
+        if let Some(kind) = outcome.error_kind() {
+            *self.errors.entry(kind).or_default() += 1;
+        }
+        if outcome.is_failure() {
+            self.failures.push(TitleFailure { path, outcome });
+        }
+    }
+
+    pub fn merge(&mut self, other: RunSummary) {
+        self.total += other.total;
+        self.refreshed += other.refreshed;
+        self.still_valid += other.still_valid;
+        self.no_id += other.no_id;
+        self.no_trailer += other.no_trailer;
+        self.lookup_failed += other.lookup_failed;
+        self.write_failed += other.write_failed;
+        self.skipped += other.skipped;
+        self.cut_short = self.cut_short.take().or(other.cut_short);
+        self.duration += other.duration;
+        self.failures.extend(other.failures);
+        for (kind, count) in other.errors {
+            *self.errors.entry(kind).or_default() += count;
+        }
+        self.failed_libraries.extend(other.failed_libraries);
+    }
+
+    /// How many titles ended with an error of `kind`.
+    pub fn errors_of(&self, kind: ScrapeErrorKind) -> usize {
+        self.errors.get(&kind).copied().unwrap_or(0)
+    }
+
+    pub fn failed(&self) -> usize {
+        self.lookup_failed + self.write_failed
+    }
+
+    /// Failed titles as a percentage of all titles processed.
+    pub fn failure_rate(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.failed() as f64 * 100.0 / self.total as f64
+    }
+
+    /// Whether more than `threshold` percent of titles failed. With a threshold of `0`,
+    /// any failure counts.
+    pub fn exceeds_failure_threshold(&self, threshold: f64) -> bool {
+        self.failed() > 0 && self.failure_rate() > threshold
+    }
+
+    pub fn log(&self, scope: &str) {
+        info!("{} finished in {:.1?}: {}", scope, self.duration, self);
+
+        if let Some(reason) = &self.cut_short {
+            warn!("{} was cut short, {} titles were not processed: {}", scope, self.skipped, reason);
+        }
+
+        for (library, reason) in &self.failed_libraries {
+            warn!("{} could not scan library '{}': {}", scope, library, reason);
+        }
+
+        if !self.errors.is_empty() {
+            let errors = self.errors.iter().map(|(kind, count)| format!("{}={}", kind, count)).collect::<Vec<_>>().join(", ");
+            info!("{} errors by kind: {}", scope, errors);
+        }
+
+        for failure in self.failures.iter().take(LOGGED_FAILURES) {
+            warn!("{:?}: {}", failure.path, failure.outcome);
+        }
+        if self.failures.len() > LOGGED_FAILURES {
+            warn!("... and {} more failures", self.failures.len() - LOGGED_FAILURES);
+        }
+    }
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} titles, {} refreshed, {} still valid, {} without ID, {} without trailer, {} lookup failures, {} write failures",
+            self.total, self.refreshed, self.still_valid, self.no_id, self.no_trailer, self.lookup_failed, self.write_failed
+        )?;
+
+        if self.skipped > 0 {
+            write!(f, ", {} skipped", self.skipped)?;
+        }
+        if !self.failed_libraries.is_empty() {
+            write!(f, ", {} libraries failed", self.failed_libraries.len())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+use crate::request_clients::client_settings::Upstream;
+use crate::request_clients::request_errors::error::Error;
+use crate::request_clients::request_errors::tmdb_status::TmdbStatus;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Why looking up or writing a trailer failed. Each kind has a stable [code](ScrapeErrorKind::code)
+/// that the run summary, scan events and exit codes share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ScrapeErrorKind {
+    /// The upstream could not be reached or the connection broke.
+    Network,
+    /// The upstream asked us to slow down, or requests are paused after repeated failures.
+    Throttled,
+    /// IMDb served a bot wall or challenge page.
+    Blocked,
+    /// The upstream doesn't know the title.
+    NotFound,
+    /// The title exists but has no trailer.
+    NoTrailer,
+    /// A page or response no longer has the expected shape.
+    ParseChanged,
+    /// TMDB rejected the API key.
+    TmdbAuthInvalid,
+    /// The trailer link could not be written to disk.
+    FsWrite,
+    /// The upstream answered with a status that fits none of the other kinds.
+    Upstream,
+    /// A bug or local failure, e.g. a panicked task or an unreadable cache.
+    Internal,
+}
+
+impl ScrapeErrorKind {
+    pub const ALL: [ScrapeErrorKind; 10] = [
+        ScrapeErrorKind::Network,
+        ScrapeErrorKind::Throttled,
+        ScrapeErrorKind::Blocked,
+        ScrapeErrorKind::NotFound,
+        ScrapeErrorKind::NoTrailer,
+        ScrapeErrorKind::ParseChanged,
+        ScrapeErrorKind::TmdbAuthInvalid,
+        ScrapeErrorKind::FsWrite,
+        ScrapeErrorKind::Upstream,
+        ScrapeErrorKind::Internal,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ScrapeErrorKind::Network => "network",
+            ScrapeErrorKind::Throttled => "throttled",
+            ScrapeErrorKind::Blocked => "blocked",
+            ScrapeErrorKind::NotFound => "not_found",
+            ScrapeErrorKind::NoTrailer => "no_trailer",
+            ScrapeErrorKind::ParseChanged => "parse_changed",
+            ScrapeErrorKind::TmdbAuthInvalid => "tmdb_auth_invalid",
+            ScrapeErrorKind::FsWrite => "fs_write",
+            ScrapeErrorKind::Upstream => "upstream",
+            ScrapeErrorKind::Internal => "internal",
+        }
+    }
+
+    /// The kind of a request error from the given upstream.
+    pub fn of_request_error(err: &Error, upstream: Upstream) -> Self {
+        match err {
+            Error::Reqwest(e) => Self::of_reqwest_error(e),
+            Error::Request { source } | Error::Response { source } => {
+                if let Some(e) = source.downcast_ref::<reqwest::Error>() {
+                    Self::of_reqwest_error(e)
+                } else if source.is::<serde_json::Error>() {
+                    Self::ParseChanged
+                } else {
+                    Self::Network
+                }
+            }
+            Error::SerdeJson(_) => Self::ParseChanged,
+            Error::Blocked { .. } => Self::Blocked,
+            Error::CircuitOpen { .. } => Self::Throttled,
+            Error::Server { code, content } if upstream == Upstream::Tmdb => match content.tmdb_status() {
+                TmdbStatus::AuthRejected => Self::TmdbAuthInvalid,
+                TmdbStatus::NotFound => Self::NotFound,
+                TmdbStatus::RateLimited => Self::Throttled,
+                _ => Self::of_status(*code, Some(upstream)),
+            },
+            Error::Server { code, .. } | Error::UnparsedBody { code, .. } => Self::of_status(*code, Some(upstream)),
+            Error::UnexpectedStatus(code) => Self::of_status(*code, Some(upstream)),
+            Error::Validation(_) | Error::MissingFixture { .. } => Self::Upstream,
+            Error::UnsupportedOperation(_) => Self::Internal,
+        }
+    }
+
+    fn of_reqwest_error(err: &reqwest::Error) -> Self {
+        match err.status() {
+            Some(status) => Self::of_status(status.as_u16(), None),
+            None if err.is_decode() => Self::ParseChanged,
+            None => Self::Network,
+        }
+    }
+
+    fn of_status(status: u16, upstream: Option<Upstream>) -> Self {
+        match status {
+            401 if upstream == Some(Upstream::Tmdb) => Self::TmdbAuthInvalid,
+            404 => Self::NotFound,
+            429 => Self::Throttled,
+            _ => Self::Upstream,
+        }
+    }
+}
+
+impl fmt::Display for ScrapeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// A failed trailer lookup or write, classified by [ScrapeErrorKind], with its cause.
+#[derive(Debug)]
+pub struct ScrapeError {
+    kind: ScrapeErrorKind,
+    message: String,
+    source: Option<BoxError>,
+}
+
+impl ScrapeError {
+    pub fn new(kind: ScrapeErrorKind, message: impl Into<String>) -> Self {
+        Self { kind, message: message.into(), source: None }
+    }
+
+    pub fn with_source(mut self, source: impl Into<BoxError>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Classifies a request error from `upstream` and keeps it as the cause.
+    pub fn request(upstream: Upstream, message: impl Into<String>, err: Error) -> Self {
+        Self::new(ScrapeErrorKind::of_request_error(&err, upstream), message).with_source(err)
+    }
+
+    pub fn kind(&self) -> ScrapeErrorKind {
+        self.kind
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+}
+
+impl fmt::Display for ScrapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        // `{:#}` appends the chain of causes, the way anyhow does.
+        if f.alternate() {
+            let mut source = std::error::Error::source(self);
+            while let Some(cause) = source {
+                write!(f, ": {}", cause)?;
+                source = cause.source();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ScrapeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|e| e as &(dyn std::error::Error + 'static))
+    }
+}
@@ -0,0 +1,174 @@
+//! Matches folders without an ID tag, such as `The Matrix (1999)`, to a TMDB title.
+
+use std::fmt;
+use std::sync::Arc;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tracing::{debug, info, warn};
+use crate::caching::title_match_cache::{TitleMatch, TitleMatchCache};
+use crate::caching::tmdb_to_imdb_cache::TmdbToImdbCache;
+use crate::request_clients::client_settings::Upstream;
+use crate::request_clients::request_errors::error::Error;
+use crate::request_clients::tmdb_client::tmdb_request_client::TmdbRequestClient;
+use crate::scrapers::imdb_id_lookup::lookup_external_ids;
+use crate::scrapers::media_directories::FolderType;
+use crate::scrapers::scrape_error::{ScrapeError, ScrapeErrorKind};
+
+/// How long a title without a confident match is remembered before TMDB is searched again, since
+/// the title may have been added or corrected there in the meantime. Matches are kept for good.
+pub const UNMATCHED_RECHECK_SECS: u64 = 7 * 24 * 60 * 60;
+
+static TAG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[[^\]]*\]|\{[^}]*\}").expect("Invalid tag regex"));
+static TITLE_YEAR_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?P<title>.+?)\s*\((?P<year>\d{4})\)").expect("Invalid title regex"));
+
+/// The title and year in a folder name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FolderTitle {
+    pub title: String,
+    pub year: i32,
+}
+
+impl FolderTitle {
+    /// Parses names such as `The Matrix (1999)` or `Show Name (2008) [1080p]`; anything in square
+    /// or curly brackets is ignored. A name without a year isn't parsed, since a title alone is too
+    /// often shared by several films or shows to be matched safely.
+    pub fn parse(folder_name: &str) -> Option<Self> {
+        let name = TAG_REGEX.replace_all(folder_name, " ");
+        let captures = TITLE_YEAR_REGEX.captures(name.trim())?;
+        let title = captures["title"].trim().to_string();
+        let year = captures["year"].parse().ok()?;
+        (!normalize_title(&title).is_empty()).then_some(Self { title, year })
+    }
+}
+
+impl fmt::Display for FolderTitle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.title, self.year)
+    }
+}
+
+/// Lowercases a title and drops its punctuation, so that `Mission: Impossible` and the
+/// `Mission - Impossible` of a folder name compare equal.
+pub fn normalize_title(title: &str) -> String {
+    title
+        .to_lowercase()
+        .replace('&', " and ")
+        .chars()
+        .filter(|c| !matches!(c, '\'' | '’'))
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// What the resolver concluded for a folder.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution {
+    /// The folder is the TMDB title `tmdb_id`, whose IMDb and, for shows, TheTVDB IDs are
+    /// `imdb_id` and `tvdb_id` if TMDB knows them.
+    Matched { tmdb_id: u64, imdb_id: Option<String>, tvdb_id: Option<u64> },
+    /// Several TMDB titles have the folder's title and year.
+    Ambiguous { candidates: Vec<u64> },
+    /// No TMDB title has the folder's title and year.
+    NoMatch,
+    /// The folder name has no title and year to search for.
+    Unparsable,
+}
+
+/// Searches TMDB for untagged folders and only accepts a single result with exactly the folder's
+/// title and year. Every decision is cached, so each folder is searched for once.
+#[derive(Debug)]
+pub struct TitleResolver {
+    tmdb_client: Arc<TmdbRequestClient>,
+    tmdb_to_imdb_cache: Arc<TmdbToImdbCache>,
+    title_match_cache: Arc<TitleMatchCache>,
+}
+
+fn cache_error(e: anyhow::Error) -> ScrapeError {
+    ScrapeError::new(ScrapeErrorKind::Internal, "title match cache failed").with_source(e.into_boxed_dyn_error())
+}
+
+impl TitleResolver {
+    pub fn new(tmdb_client: Arc<TmdbRequestClient>, tmdb_to_imdb_cache: Arc<TmdbToImdbCache>, title_match_cache: Arc<TitleMatchCache>) -> Self {
+        Self { tmdb_client, tmdb_to_imdb_cache, title_match_cache }
+    }
+
+    pub async fn resolve(&self, folder_name: &str, folder_type: FolderType) -> Result<Resolution, ScrapeError> {
+        let Some(folder_title) = FolderTitle::parse(folder_name) else {
+            return Ok(Resolution::Unparsable);
+        };
+
+        match self.find_match(&folder_title, &folder_type).await? {
+            TitleMatch::Matched { tmdb_id } => {
+                let ids = lookup_external_ids(&self.tmdb_client, &self.tmdb_to_imdb_cache, &tmdb_id.to_string(), &folder_type).await?;
+                Ok(Resolution::Matched { tmdb_id, imdb_id: ids.imdb_id, tvdb_id: ids.tvdb_id })
+            }
+            TitleMatch::Ambiguous { candidates } => {
+                let ids: Vec<String> = candidates.iter().map(|id| format!("tmdb-{}", id)).collect();
+                warn!(
+                    "Ambiguous folder {:?} needs manual review: {} TMDB titles match '{}' ({}). Tag the folder with its ID",
+                    folder_name, candidates.len(), folder_title, ids.join(", ")
+                );
+                Ok(Resolution::Ambiguous { candidates })
+            }
+            TitleMatch::NoMatch => Ok(Resolution::NoMatch),
+        }
+    }
+
+    async fn find_match(&self, folder_title: &FolderTitle, folder_type: &FolderType) -> Result<TitleMatch, ScrapeError> {
+        let kind = folder_type.tmdb_media_type();
+        let key = format!("{}:{}:{}", kind, normalize_title(&folder_title.title), folder_title.year);
+
+        if let Some(cached) = self.title_match_cache.get(&key).map_err(cache_error)? {
+            if matches!(cached.title_match, TitleMatch::Matched { .. }) || cached.age_secs() < UNMATCHED_RECHECK_SECS {
+                debug!("Using cached TMDB match for '{}': {:?}", folder_title, cached.title_match);
+                return Ok(cached.title_match);
+            }
+        }
+
+        info!("Searching TMDB for {} '{}'", kind, folder_title);
+        let mut candidates = self.search(folder_title, folder_type).await?;
+        candidates.dedup();
+        let title_match = match candidates.as_slice() {
+            [] => TitleMatch::NoMatch,
+            [tmdb_id] => TitleMatch::Matched { tmdb_id: *tmdb_id },
+            _ => TitleMatch::Ambiguous { candidates },
+        };
+
+        self.title_match_cache.add(&key, title_match.clone()).map_err(cache_error)?;
+        Ok(title_match)
+    }
+
+    /// The IDs of the search results with exactly the folder's title, or original title, and year.
+    async fn search(&self, folder_title: &FolderTitle, folder_type: &FolderType) -> Result<Vec<u64>, ScrapeError> {
+        let wanted = normalize_title(&folder_title.title);
+        let is_match = |titles: [&str; 2], year: Option<i32>| {
+            year == Some(folder_title.year) && titles.iter().any(|title| normalize_title(title) == wanted)
+        };
+        let lookup_error = |e: Error| ScrapeError::request(Upstream::Tmdb, format!("failed to search TMDB for '{}'", folder_title), e);
+        let search = self.tmdb_client.search();
+
+        let ids = match folder_type {
+            FolderType::Movie => search
+                .movies(&folder_title.title, Some(folder_title.year), 1)
+                .await
+                .map_err(lookup_error)?
+                .results
+                .into_iter()
+                .filter(|movie| is_match([&movie.title, &movie.original_title], movie.year()))
+                .map(|movie| movie.id)
+                .collect(),
+            FolderType::TvShow => search
+                .tv(&folder_title.title, Some(folder_title.year), 1)
+                .await
+                .map_err(lookup_error)?
+                .results
+                .into_iter()
+                .filter(|show| is_match([&show.name, &show.original_name], show.year()))
+                .map(|show| show.id)
+                .collect(),
+        };
+        Ok(ids)
+    }
+}
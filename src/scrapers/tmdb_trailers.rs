@@ -2,114 +2,128 @@ use crate::scrapers::traits::TrailerScraper;
 use std::path::{PathBuf};
 
 use anyhow::Result;
-use regex::Regex;
 use std::sync::Arc;
 use async_trait::async_trait;
+use once_cell::sync::OnceCell;
 
 use tracing::{error, info, warn};
 use crate::caching::tmdb_to_imdb_cache::TmdbToImdbCache;
-use crate::configuration::configuration_provider::AppConfig;
-use crate::request_clients::get_tmdb_client;
-use crate::request_clients::tmdb_client::external_ids_endpoints;
-use crate::scrapers::media_directories::{process_media_folders, FolderType, BACKDROPS_FOLDER};
+use crate::configuration::configuration_provider::LibraryProfile;
+use crate::request_clients::tmdb_client::tmdb_request_client::TmdbRequestClient;
+use crate::scrapers::folder_tagger::FolderIds;
+use crate::scrapers::media_directories::{title_name, trailer_path, FolderType};
+use crate::scrapers::imdb_id_lookup::lookup_imdb_id;
 use crate::scrapers::imdb_trailers::ImdbTrailerScraper;
+use crate::scrapers::outcomes::TitleOutcome;
+use crate::scrapers::scrape_error::{ScrapeError, ScrapeErrorKind};
 
 #[derive(Debug)]
 pub struct TmdbTrailerScraper {
     pub imdb_trailer_scraper: Arc<ImdbTrailerScraper>,
     pub tmdb_to_imdb_cache: Arc<TmdbToImdbCache>,
+    pub tmdb_client: Arc<TmdbRequestClient>,
+    /// Why TMDB rejected the API key, once it has. No more TMDB requests are sent after that.
+    auth_rejected: OnceCell<String>,
 }
 
-// This will be replaced with configurable regex
-
 #[async_trait]
 impl TrailerScraper for TmdbTrailerScraper {
-    async fn scan_and_refresh_trailers(self: Arc<Self>, config: &Arc<AppConfig>) -> Result<()> {
-        self.perform_scan_and_refresh_tmdb(config).await
+    async fn process_path(&self, path: PathBuf, library: Arc<LibraryProfile>, folder_type: FolderType) -> TitleOutcome {
+        self.process_path_internal(path, library, folder_type).await
     }
 
-    async fn process_path(&self, path: PathBuf, config: Arc<AppConfig>, folder_type: FolderType) {
-        self.process_path_internal(path, config, folder_type).await;
+    fn halted(&self) -> Option<String> {
+        self.auth_rejected.get().cloned().or_else(|| self.imdb_trailer_scraper.halted())
     }
 }
 
 impl TmdbTrailerScraper {
-    pub async fn perform_scan_and_refresh_tmdb(self: Arc<Self>, app_config: &Arc<AppConfig>) -> Result<()> {
-        process_media_folders(app_config, self as Arc<dyn TrailerScraper>).await
+    pub fn new(imdb_trailer_scraper: Arc<ImdbTrailerScraper>, tmdb_to_imdb_cache: Arc<TmdbToImdbCache>, tmdb_client: Arc<TmdbRequestClient>) -> Self {
+        Self {
+            imdb_trailer_scraper,
+            tmdb_to_imdb_cache,
+            tmdb_client,
+            auth_rejected: OnceCell::new(),
+        }
     }
 
     async fn process_path_internal(
         &self,
         path: PathBuf,
-        config: Arc<AppConfig>,
+        library: Arc<LibraryProfile>,
         folder_type: FolderType,
-    ) {
-        if let Some(path_str) = path.to_str() {
-            // Use configurable regex pattern
-            let regex = match Regex::new(&config.tmdb_id_regex) {
-                Ok(regex) => regex,
-                Err(e) => {
-                    error!("Invalid TMDB ID regex pattern: {}", e);
-                    return;
-                }
-            };
-            
-            if let Some(cap) = regex.captures(path_str) {
-                let tmdb_id = &cap[1];
-                let backdrops_path = path.join(BACKDROPS_FOLDER);
-                let strm_path = backdrops_path.join(&config.video_filename);
-
-                if let Ok(expired) = self.imdb_trailer_scraper.is_strm_expired(&strm_path) {
-                    if !expired {
-                        info!("Trailer still valid for {} in {:?}", tmdb_id, path);
-                        return;
-                    }
-                }
+    ) -> TitleOutcome {
+        let Some(name) = title_name(&path) else {
+            return TitleOutcome::NoId { reason: "title name is not valid UTF-8".to_string() };
+        };
 
-                info!("Refreshing trailer for {} in {:?}", tmdb_id, path);
+        let patterns = match library.id_patterns() {
+            Ok(patterns) => patterns,
+            Err(e) => {
+                error!("Invalid ID patterns: {:#}", e);
+                return TitleOutcome::NoId { reason: format!("invalid ID patterns: {:#}", e) };
+            }
+        };
 
-                let imdb_id = match self.get_imdb_id(tmdb_id, folder_type).await {
-                    Ok(id) => id,
-                    Err(e) => {
-                        warn!("Failed to retrieve IMDb ID for {}: {:?}", tmdb_id, e);
-                        return; // just early-exit the method
-                    }
-                };
+        let tmdb_id = match patterns.tmdb.find(name) {
+            Some(id) => id.to_string(),
+            None => match FolderIds::read(&path) {
+                Some(FolderIds { tmdb: Some(tmdb_id), .. }) => tmdb_id.to_string(),
+                Some(FolderIds { imdb: Some(imdb_id), .. }) => {
+                    return self.imdb_trailer_scraper.refresh_if_expired(&imdb_id, path, library).await;
+                }
+                _ if library.resolve_untagged => {
+                    return self.imdb_trailer_scraper.refresh_untagged(path, library, folder_type).await;
+                }
+                _ => {
+                    warn!("No TMDB ID found in path: {:?}", path);
+                    return TitleOutcome::NoId { reason: "no TMDB ID in folder name".to_string() };
+                }
+            },
+        };
+        let tmdb_id = tmdb_id.as_str();
+        let strm_path = trailer_path(&path, &library);
 
-                self.imdb_trailer_scraper
-                    .refresh_imdb_trailer(&imdb_id, path.clone(), config)
-                    .await;
-            } else {
-                warn!("No TMDB ID found in path: {:?}", path);
+        if let Ok(expired) = self.imdb_trailer_scraper.is_strm_expired(&strm_path) {
+            if !expired {
+                info!("Trailer still valid for {} in {:?}", tmdb_id, path);
+                return TitleOutcome::StillValid { id: tmdb_id.to_string() };
             }
         }
-    }
-
-    async fn get_imdb_id(&self, tmdb_id: &str, folder_type: FolderType) -> Result<String> {
-        if let Some(imdb_id) = self.tmdb_to_imdb_cache.try_get_imdb_id(tmdb_id)? {
-            return Ok(imdb_id);
-        }
 
-        info!("No IMDB ID found in local cache for TMDB ID: {}", tmdb_id);
-        let client = get_tmdb_client();
+        info!("Refreshing trailer for {} in {:?}", tmdb_id, path);
 
-        let external_ids = match folder_type {
-            FolderType::TvShow => {
-                info!("Fetching IMDB ID for TV Show TMDB ID: {}", tmdb_id);
-                external_ids_endpoints::ExternalIds::Tv(client.external_ids().get_for_tv(tmdb_id).await?)
+        let imdb_id = match self.get_imdb_id(tmdb_id, folder_type).await {
+            Ok(Some(id)) => id,
+            Ok(None) => {
+                return TitleOutcome::NoId { reason: format!("TMDB has no IMDb ID for {}", tmdb_id) };
             }
-            FolderType::Movie => {
-                info!("Fetching IMDB ID for Movie TMDB ID: {}", tmdb_id);
-                external_ids_endpoints::ExternalIds::Movie(client.external_ids().get_for_movie(tmdb_id).await?)
+            Err(e) => {
+                if e.kind() != ScrapeErrorKind::TmdbAuthInvalid {
+                    warn!("Failed to retrieve IMDb ID for {}: {:#}", tmdb_id, e);
+                }
+                return TitleOutcome::lookup_failed(Some(tmdb_id.to_string()), &e);
             }
         };
 
-        if let Some(imdb_id) = external_ids.imdb_id() {
-            self.tmdb_to_imdb_cache.add(tmdb_id, imdb_id)?;
-            Ok(imdb_id.to_string())
-        } else {
-            warn!("No IMDB ID found for TMDB ID: {}", tmdb_id);
-            Err(anyhow::anyhow!("No IMDB ID found for TMDB ID: {}", tmdb_id))
+        self.imdb_trailer_scraper
+            .refresh_imdb_trailer(&imdb_id, path.clone(), library)
+            .await
+    }
+
+    /// Maps a TMDB ID to an IMDb ID, or `None` when TMDB doesn't know one. Stops asking TMDB once
+    /// it has rejected the API key.
+    pub async fn get_imdb_id(&self, tmdb_id: &str, folder_type: FolderType) -> Result<Option<String>, ScrapeError> {
+        if let Some(reason) = self.auth_rejected.get() {
+            return Err(ScrapeError::new(ScrapeErrorKind::TmdbAuthInvalid, reason.clone()));
         }
+
+        let result = lookup_imdb_id(&self.tmdb_client, &self.tmdb_to_imdb_cache, tmdb_id, &folder_type).await;
+        if let Err(e) = &result {
+            if e.kind() == ScrapeErrorKind::TmdbAuthInvalid && self.auth_rejected.set(format!("TMDB rejected the API key: {}", e)).is_ok() {
+                error!("TMDB rejected the API key ({}); check TRAILERFIN_TMDB_API_KEY. No more TMDB lookups will be made", e);
+            }
+        }
+        result
     }
-}
\ No newline at end of file
+}
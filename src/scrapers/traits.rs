@@ -2,11 +2,17 @@ use std::fmt::Debug;
 use std::path::PathBuf;
 use std::sync::Arc;
 use async_trait::async_trait;
-use crate::configuration::configuration_provider::AppConfig;
+use crate::configuration::configuration_provider::LibraryProfile;
 use crate::scrapers::media_directories::FolderType;
+use crate::scrapers::outcomes::TitleOutcome;
 
 #[async_trait]
 pub trait TrailerScraper: Send + Sync + Debug {
-    async fn scan_and_refresh_trailers(self: Arc<Self>, config: &Arc<AppConfig>) -> anyhow::Result<()>;
-    async fn process_path(&self, path: PathBuf, config: Arc<AppConfig>, folder_type: FolderType);
-}
\ No newline at end of file
+    async fn process_path(&self, path: PathBuf, library: Arc<LibraryProfile>, folder_type: FolderType) -> TitleOutcome;
+
+    /// Why the scraper can't process titles right now, e.g. because its upstream service is
+    /// blocking requests. Scans stop dispatching titles while this returns `Some`.
+    fn halted(&self) -> Option<String> {
+        None
+    }
+}
@@ -16,7 +16,7 @@ where
 pub(crate) fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
 where
     D: Deserializer<'de>,
-    T: Deserialize<'de> + FromStr,
+    T: FromStr,
     T::Err: std::fmt::Display,
 {
     let value = Option::<String>::deserialize(deserializer)?;
@@ -0,0 +1,92 @@
+//! Helpers shared by the integration tests. Each test crate uses only some of them.
+#![allow(dead_code)]
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use tempfile::{tempdir, TempDir};
+use trailerfin_rust::configuration::configuration_provider::LibraryProfile;
+use trailerfin_rust::scrapers::media_directories::FolderType;
+use trailerfin_rust::scrapers::outcomes::TitleOutcome;
+use trailerfin_rust::scrapers::scrape_error::ScrapeErrorKind;
+use trailerfin_rust::scrapers::traits::TrailerScraper;
+
+/// Records the titles it is given and answers with [scripted_outcome].
+#[derive(Debug, Default)]
+pub struct RecordingScraper {
+    pub processed: Mutex<Vec<(String, PathBuf, FolderType)>>,
+}
+
+#[async_trait]
+impl TrailerScraper for RecordingScraper {
+    async fn process_path(&self, path: PathBuf, library: Arc<LibraryProfile>, folder_type: FolderType) -> TitleOutcome {
+        let outcome = scripted_outcome(&path);
+        self.processed.lock().unwrap().push((library.name.clone(), path, folder_type));
+        outcome
+    }
+}
+
+impl RecordingScraper {
+    /// The processed paths, in the order they were processed.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.processed.lock().unwrap().iter().map(|(_, path, _)| path.clone()).collect()
+    }
+
+    /// The libraries of the processed titles, sorted.
+    pub fn libraries(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.processed.lock().unwrap().iter().map(|(l, _, _)| l.clone()).collect();
+        names.sort();
+        names
+    }
+
+    /// The processed paths relative to `root`, sorted.
+    pub fn titles(&self, root: &Path) -> Vec<String> {
+        let mut titles: Vec<_> = self
+            .paths()
+            .iter()
+            .map(|path| path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect();
+        titles.sort();
+        titles
+    }
+}
+
+/// Picks an outcome from the folder name, so tests can stage every kind of result.
+pub fn scripted_outcome(path: &Path) -> TitleOutcome {
+    let name = path.file_name().unwrap().to_string_lossy();
+    let id = "tt1".to_string();
+    match name.split_whitespace().next().unwrap() {
+        "valid" => TitleOutcome::StillValid { id },
+        "noid" => TitleOutcome::NoId { reason: "no IMDb ID in folder name".to_string() },
+        "notrailer" => TitleOutcome::NoTrailer { id },
+        "lookup" => TitleOutcome::LookupFailed { id: Some(id), kind: ScrapeErrorKind::Upstream, reason: "unexpected status code 503".to_string() },
+        "write" => TitleOutcome::WriteFailed { id, reason: "permission denied".to_string() },
+        _ => TitleOutcome::Refreshed { id },
+    }
+}
+
+/// A temporary scan path with the TV and movie folders of the environment tests, and a cache
+/// path, both set in the `TRAILERFIN_*` environment.
+pub fn setup_empty_dir() -> TempDir {
+    let temp = tempdir().unwrap();
+
+    let scan_path = temp.path().join("scan-me");
+    let cache_path = temp.path().join("cache-me");
+
+    for subdir in ["Tv Shows", "Kids TV", "Movies", "Kids"] {
+        std::fs::create_dir_all(scan_path.join(subdir)).unwrap();
+    }
+
+    std::fs::create_dir_all(&cache_path).unwrap();
+    std::fs::create_dir_all(&scan_path).unwrap();
+
+    unsafe {
+        env::set_var("TRAILERFIN_SCAN_PATH", scan_path);
+        env::set_var("TRAILERFIN_CACHE_PATH", cache_path);
+        env::set_var("TRAILERFIN_TV_FOLDERS", "Tv Shows, Kids TV");
+        env::set_var("TRAILERFIN_MOVIE_FOLDERS", "Movies, Kids");
+    }
+
+    temp
+}
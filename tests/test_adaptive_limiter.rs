@@ -0,0 +1,83 @@
+use std::num::NonZeroU32;
+use std::time::{Duration, Instant};
+use governor::Quota;
+use trailerfin_rust::request_clients::adaptive_limiter::AdaptiveRateLimiter;
+
+fn limiter(per_second: u32, adaptive: bool) -> AdaptiveRateLimiter {
+    AdaptiveRateLimiter::new("test", Quota::per_second(NonZeroU32::new(per_second).unwrap()), adaptive)
+}
+
+#[test]
+fn test_throttling_halves_the_rate_down_to_a_floor() {
+    let limiter = limiter(64, true);
+    assert_eq!(limiter.current_rate(), 64.0);
+
+    limiter.on_throttled();
+    assert_eq!(limiter.current_rate(), 32.0);
+    limiter.on_throttled();
+    assert_eq!(limiter.current_rate(), 16.0);
+
+    for _ in 0..20 {
+        limiter.on_throttled();
+    }
+    assert_eq!(limiter.current_rate(), 2.0);
+}
+
+#[test]
+fn test_rate_recovers_slowly_and_never_exceeds_the_ceiling() {
+    let limiter = limiter(10, true);
+    limiter.on_throttled();
+    assert_eq!(limiter.current_rate(), 5.0);
+
+    for _ in 0..9 {
+        limiter.on_success();
+    }
+    assert_eq!(limiter.current_rate(), 5.0);
+
+    limiter.on_success();
+    assert!((limiter.current_rate() - 6.0).abs() < 1e-9);
+
+    for _ in 0..1000 {
+        limiter.on_success();
+    }
+    assert_eq!(limiter.current_rate(), limiter.ceiling_rate());
+}
+
+#[test]
+fn test_throttling_resets_recovery_progress() {
+    let limiter = limiter(10, true);
+    limiter.on_throttled();
+    limiter.on_throttled();
+
+    for _ in 0..9 {
+        limiter.on_success();
+    }
+    limiter.on_throttled();
+    for _ in 0..9 {
+        limiter.on_success();
+    }
+
+    assert_eq!(limiter.current_rate(), 1.25);
+}
+
+#[test]
+fn test_non_adaptive_limiter_keeps_its_rate() {
+    let limiter = limiter(10, false);
+    limiter.on_throttled();
+    assert_eq!(limiter.current_rate(), 10.0);
+}
+
+#[tokio::test]
+async fn test_throttled_limiter_paces_requests() {
+    let limiter = limiter(100, true);
+    for _ in 0..3 {
+        limiter.on_throttled();
+    }
+
+    // 100/second slowed to an eighth is one request every 80ms.
+    let started = Instant::now();
+    for _ in 0..3 {
+        limiter.until_ready().await;
+    }
+    assert!(started.elapsed() >= Duration::from_millis(160));
+}
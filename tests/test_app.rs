@@ -0,0 +1,297 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use async_trait::async_trait;
+use tempfile::tempdir;
+use trailerfin_rust::app::{App, TitleId};
+use trailerfin_rust::caching::tmdb_to_imdb_cache::ExternalTitleIds;
+use trailerfin_rust::configuration::configuration_provider::{AppConfig, ConfigurationProvider, DataSource, LibraryProfile};
+use trailerfin_rust::scrapers::events::ScanEvent;
+use trailerfin_rust::scrapers::media_directories::FolderType;
+use trailerfin_rust::scrapers::outcomes::{RunSummary, TitleOutcome};
+use trailerfin_rust::scrapers::scrape_error::ScrapeErrorKind;
+use trailerfin_rust::scrapers::traits::TrailerScraper;
+use common::RecordingScraper;
+
+/// Stops accepting titles once it has processed `halt_after` of them, like a scraper whose
+/// upstream service started blocking requests.
+#[derive(Debug)]
+struct HaltingScraper {
+    processed: AtomicUsize,
+    halt_after: usize,
+}
+
+#[async_trait]
+impl TrailerScraper for HaltingScraper {
+    async fn process_path(&self, _path: PathBuf, _library: Arc<LibraryProfile>, _folder_type: FolderType) -> TitleOutcome {
+        self.processed.fetch_add(1, Ordering::SeqCst);
+        TitleOutcome::LookupFailed { id: None, kind: ScrapeErrorKind::Blocked, reason: "request blocked by bot protection".to_string() }
+    }
+
+    fn halted(&self) -> Option<String> {
+        (self.processed.load(Ordering::SeqCst) >= self.halt_after).then(|| "IMDb requests paused".to_string())
+    }
+}
+
+fn make_titles(root: &Path, folder: &str, titles: &[&str]) {
+    std::fs::create_dir_all(root.join(folder)).unwrap();
+    for title in titles {
+        std::fs::create_dir_all(root.join(folder).join(title)).unwrap();
+    }
+}
+
+fn library(name: &str, root: &Path, data_source: DataSource) -> LibraryProfile {
+    LibraryProfile::new(name, root.to_string_lossy())
+        .with_movie_folders(["Movies"])
+        .with_tv_folders(["Shows"])
+        .with_data_source(data_source)
+}
+
+fn config(cache_path: &Path, libraries: Vec<LibraryProfile>) -> Arc<AppConfig> {
+    let config = libraries
+        .into_iter()
+        .fold(AppConfig::new(cache_path.to_string_lossy()), |config, library| config.with_library(library))
+        .with_user_agent("TestAgent")
+        .with_tmdb_api_key("ham-and-cheese-sandwich");
+    ConfigurationProvider::validate_config(config).expect("Expected config to validate")
+}
+
+#[tokio::test]
+async fn test_app_scans_every_library_with_injected_scrapers() {
+    let cache = tempdir().unwrap();
+    let movies = tempdir().unwrap();
+    let anime = tempdir().unwrap();
+    make_titles(movies.path(), "Movies", &["A {imdb-tt1}", "B {imdb-tt2}"]);
+    make_titles(movies.path(), "Shows", &[]);
+    make_titles(anime.path(), "Shows", &["C {tmdb-3}"]);
+    make_titles(anime.path(), "Movies", &[]);
+
+    let imdb = Arc::new(RecordingScraper::default());
+    let tmdb = Arc::new(RecordingScraper::default());
+
+    let app = App::builder(config(cache.path(), vec![
+        library("movies", movies.path(), DataSource::Imdb),
+        library("anime", anime.path(), DataSource::Tmdb),
+    ]))
+        .with_scraper(DataSource::Imdb, imdb.clone())
+        .with_scraper(DataSource::Tmdb, tmdb.clone())
+        .build()
+        .unwrap();
+
+    let mut events = app.subscribe();
+    app.scan().await;
+
+    assert_eq!(imdb.libraries(), vec!["movies", "movies"]);
+    assert_eq!(tmdb.libraries(), vec!["anime"]);
+    assert!(app.tmdb_client().is_none());
+
+    let mut received = vec![];
+    while let Ok(event) = events.try_recv() {
+        received.push(event);
+    }
+    assert_eq!(received.first(), Some(&ScanEvent::LibraryStarted { library: "movies".to_string(), titles: 2 }));
+    assert_eq!(received.iter().filter(|e| matches!(e, ScanEvent::TitleFinished { .. })).count(), 3);
+    assert!(matches!(received.last(), Some(ScanEvent::LibraryFinished { library, .. }) if library == "anime"));
+}
+
+#[tokio::test]
+async fn test_scan_returns_summary_of_title_outcomes() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    make_titles(media.path(), "Movies", &["A {imdb-tt1}", "valid {imdb-tt2}", "noid", "notrailer {imdb-tt4}"]);
+    make_titles(media.path(), "Shows", &["lookup {imdb-tt5}", "write {imdb-tt6}", "B {imdb-tt7}"]);
+
+    let app = App::builder(config(cache.path(), vec![library("media", media.path(), DataSource::Imdb)]))
+        .with_scraper(DataSource::Imdb, Arc::new(RecordingScraper::default()))
+        .build()
+        .unwrap();
+
+    let mut events = app.subscribe();
+    let summary = app.scan().await;
+
+    assert_eq!(summary.total, 7);
+    assert_eq!(summary.refreshed, 2);
+    assert_eq!(summary.still_valid, 1);
+    assert_eq!(summary.no_id, 1);
+    assert_eq!(summary.no_trailer, 1);
+    assert_eq!(summary.lookup_failed, 1);
+    assert_eq!(summary.write_failed, 1);
+    assert_eq!(summary.failed(), 2);
+
+    let mut failed_paths: Vec<_> = summary.failures.iter().map(|f| f.path.clone()).collect();
+    failed_paths.sort();
+    assert_eq!(failed_paths, vec![
+        media.path().join("Shows").join("lookup {imdb-tt5}"),
+        media.path().join("Shows").join("write {imdb-tt6}"),
+    ]);
+
+    let mut finished = None;
+    let mut refreshed_events = 0;
+    while let Ok(event) = events.try_recv() {
+        match event {
+            ScanEvent::TitleFinished { outcome: TitleOutcome::Refreshed { .. }, .. } => refreshed_events += 1,
+            ScanEvent::LibraryFinished { summary, .. } => finished = Some(summary),
+            _ => {}
+        }
+    }
+    assert_eq!(refreshed_events, 2);
+    assert_eq!(finished.as_ref(), Some(&summary));
+}
+
+#[tokio::test]
+async fn test_failed_libraries_are_listed_in_the_summary() {
+    let cache = tempdir().unwrap();
+    let movies = tempdir().unwrap();
+    let gone = tempdir().unwrap();
+    make_titles(movies.path(), "Movies", &["A {imdb-tt1}"]);
+    make_titles(movies.path(), "Shows", &[]);
+    make_titles(gone.path(), "Movies", &[]);
+    make_titles(gone.path(), "Shows", &[]);
+
+    let app = App::builder(config(cache.path(), vec![
+        library("gone", gone.path(), DataSource::Imdb),
+        library("movies", movies.path(), DataSource::Imdb),
+    ]))
+        .with_scraper(DataSource::Imdb, Arc::new(RecordingScraper::default()))
+        .build()
+        .unwrap();
+    std::fs::remove_dir_all(gone.path()).unwrap();
+
+    let summary = app.scan().await;
+
+    assert_eq!(summary.total, 1);
+    assert_eq!(summary.refreshed, 1);
+    let failed: Vec<&str> = summary.failed_libraries.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(failed, ["gone"]);
+    assert!(summary.to_string().ends_with(", 1 libraries failed"));
+}
+
+#[test]
+fn test_run_summaries_merge() {
+    let mut first = RunSummary::default();
+    first.record(PathBuf::from("/a"), TitleOutcome::Refreshed { id: "tt1".to_string() });
+    first.record(PathBuf::from("/b"), TitleOutcome::WriteFailed { id: "tt2".to_string(), reason: "disk full".to_string() });
+
+    let mut second = RunSummary::default();
+    second.record(PathBuf::from("/c"), TitleOutcome::LookupFailed { id: None, kind: ScrapeErrorKind::Network, reason: "timed out".to_string() });
+    second.record(PathBuf::from("/d"), TitleOutcome::NoTrailer { id: "tt4".to_string() });
+
+    first.merge(second);
+
+    assert_eq!(first.total, 4);
+    assert_eq!(first.failed(), 2);
+    assert_eq!(first.failures.len(), 2);
+    assert_eq!(first.failures[1].outcome.reason(), Some("timed out"));
+    assert_eq!(first.errors_of(ScrapeErrorKind::Network), 1);
+    assert_eq!(first.errors_of(ScrapeErrorKind::FsWrite), 1);
+    assert_eq!(first.errors_of(ScrapeErrorKind::NoTrailer), 1);
+    assert_eq!(
+        first.to_string(),
+        "4 titles, 1 refreshed, 0 still valid, 0 without ID, 1 without trailer, 1 lookup failures, 1 write failures"
+    );
+}
+
+#[tokio::test]
+async fn test_refresh_path_processes_only_that_title() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    make_titles(media.path(), "Movies", &["A {imdb-tt1}", "B {imdb-tt2}"]);
+    make_titles(media.path(), "Shows", &["C {imdb-tt3}"]);
+
+    let imdb = Arc::new(RecordingScraper::default());
+    let app = App::builder(config(cache.path(), vec![library("media", media.path(), DataSource::Imdb)]))
+        .with_scraper(DataSource::Imdb, imdb.clone())
+        .build()
+        .unwrap();
+
+    let show = media.path().join("Shows").join("C {imdb-tt3}");
+    let outcome = app.refresh_path(&show).await.unwrap();
+    assert_eq!(outcome, TitleOutcome::Refreshed { id: "tt1".to_string() });
+
+    {
+        let processed = imdb.processed.lock().unwrap();
+        assert_eq!(processed.len(), 1);
+        assert_eq!(processed[0].1, show);
+        assert_eq!(processed[0].2, FolderType::TvShow);
+    }
+
+    assert!(app.refresh_path(media.path()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_two_apps_can_coexist_in_one_process() {
+    let first_cache = tempdir().unwrap();
+    let second_cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    make_titles(media.path(), "Movies", &[]);
+    make_titles(media.path(), "Shows", &[]);
+
+    let first = App::from_config(config(first_cache.path(), vec![library("first", media.path(), DataSource::Imdb)])).unwrap();
+    let second = App::from_config(config(second_cache.path(), vec![library("second", media.path(), DataSource::Imdb)])).unwrap();
+
+    first.tmdb_to_imdb_cache().add("movie", "603", &ExternalTitleIds { imdb_id: Some("tt0133093".to_string()), tvdb_id: None }).unwrap();
+
+    assert_eq!(first.tmdb_to_imdb_cache().try_get_imdb_id("movie", "603").unwrap().as_deref(), Some("tt0133093"));
+    assert_eq!(second.tmdb_to_imdb_cache().try_get_imdb_id("movie", "603").unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_missing_tmdb_services_are_errors_not_panics() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    make_titles(media.path(), "Movies", &[]);
+    make_titles(media.path(), "Shows", &[]);
+
+    let app = App::from_config(config(cache.path(), vec![library("movies", media.path(), DataSource::Imdb)])).unwrap();
+
+    assert!(app.scraper(&DataSource::Tmdb).is_err());
+    assert!(app.resolve(&TitleId::TmdbMovie("603".to_string())).await.is_err());
+}
+
+#[test]
+fn test_typed_config_is_validated() {
+    let cache = tempdir().unwrap();
+    let config = AppConfig::new(cache.path().to_string_lossy())
+        .with_library(LibraryProfile::new("missing", "/definitely/not/here").with_movie_folders(["Movies"]));
+
+    assert!(ConfigurationProvider::validate_config(config).is_err());
+}
+
+#[test]
+fn test_failure_threshold() {
+    let mut summary = RunSummary::default();
+    for i in 0..9 {
+        summary.record(PathBuf::from(format!("/ok/{i}")), TitleOutcome::Refreshed { id: format!("tt{i}") });
+    }
+    summary.record(PathBuf::from("/no-id"), TitleOutcome::NoId { reason: "no IMDb ID in folder name".to_string() });
+    assert!(!summary.exceeds_failure_threshold(0.0));
+
+    summary.record(PathBuf::from("/failed"), TitleOutcome::LookupFailed { id: None, kind: ScrapeErrorKind::Network, reason: "timed out".to_string() });
+    assert!(summary.exceeds_failure_threshold(0.0));
+    assert!(summary.exceeds_failure_threshold(5.0));
+    assert!(!summary.exceeds_failure_threshold(10.0));
+}
+
+#[tokio::test]
+async fn test_scan_is_cut_short_when_scraper_halts() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    make_titles(media.path(), "Movies", &["A {imdb-tt1}", "B {imdb-tt2}", "C {imdb-tt3}", "D {imdb-tt4}", "E {imdb-tt5}"]);
+    make_titles(media.path(), "Shows", &[]);
+
+    let scraper = Arc::new(HaltingScraper { processed: AtomicUsize::new(0), halt_after: 2 });
+    let app = App::builder(config(cache.path(), vec![library("media", media.path(), DataSource::Imdb)]))
+        .with_scraper(DataSource::Imdb, scraper.clone())
+        .build()
+        .unwrap();
+
+    let summary = app.scan().await;
+
+    assert_eq!(scraper.processed.load(Ordering::SeqCst), 2);
+    assert_eq!(summary.total, 2);
+    assert_eq!(summary.skipped, 3);
+    assert_eq!(summary.cut_short.as_deref(), Some("IMDb requests paused"));
+    assert!(summary.to_string().ends_with(", 3 skipped"));
+}
@@ -0,0 +1,181 @@
+use std::sync::{Arc, Mutex};
+use tempfile::tempdir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use trailerfin_rust::configuration::configuration_provider::AppConfig;
+use trailerfin_rust::request_clients::client_settings::{ClientSettings, Upstream, UserAgentRotation, IMDB_COOKIE_JAR_FILE};
+use trailerfin_rust::request_clients::rate_limited_client::{Executor, RateLimitedClient};
+use trailerfin_rust::request_clients::retry_policy::RetryPolicy;
+
+/// Answers every request with `response` and records each request head, lowercased.
+async fn serve(response: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(vec![]));
+    let recorded = requests.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let read = socket.read(&mut buf).await.unwrap_or(0);
+            recorded.lock().unwrap().push(String::from_utf8_lossy(&buf[..read]).to_lowercase());
+            _ = socket.write_all(response.as_bytes()).await;
+            _ = socket.shutdown().await;
+        }
+    });
+
+    (url, requests)
+}
+
+const OK: &str = "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+const SETS_SESSION: &str = "HTTP/1.1 200 OK\r\nSet-Cookie: session-id=abc123; Max-Age=3600; Path=/\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+fn settings() -> ClientSettings {
+    ClientSettings::new("TestAgent", "100/second").with_retry_policy(RetryPolicy::none())
+}
+
+fn user_agent(request: &str) -> &str {
+    request.lines().find_map(|l| l.strip_prefix("user-agent: ")).unwrap()
+}
+
+#[tokio::test]
+async fn test_imdb_requests_send_browser_headers() {
+    let cache = tempdir().unwrap();
+    let (url, requests) = serve(OK).await;
+    let settings = ClientSettings::for_upstream(&AppConfig::new(cache.path().to_string_lossy()), Upstream::Imdb)
+        .with_retry_policy(RetryPolicy::none());
+    let client = RateLimitedClient::from_settings(&settings).unwrap();
+
+    client.execute_raw(&url).await.unwrap();
+
+    let request = &requests.lock().unwrap()[0];
+    assert!(request.contains("accept-language: en-us,en;q=0.9"));
+    assert!(request.contains("accept: text/html,application/xhtml+xml"));
+}
+
+#[test]
+fn test_browser_headers_only_apply_to_imdb_and_can_be_disabled() {
+    let cache = tempdir().unwrap();
+    let mut config = AppConfig::new(cache.path().to_string_lossy());
+    config.imdb_accept = String::new();
+    config.imdb_accept_language = "de-DE".to_string();
+
+    let imdb = ClientSettings::for_upstream(&config, Upstream::Imdb);
+    let tmdb = ClientSettings::for_upstream(&config, Upstream::Tmdb);
+
+    assert_eq!(imdb.headers, vec![("Accept-Language".to_string(), "de-DE".to_string())]);
+    assert!(tmdb.headers.is_empty());
+    assert_eq!(imdb.cookie_jar, Some(cache.path().join(IMDB_COOKIE_JAR_FILE)));
+    assert_eq!(tmdb.cookie_jar, None);
+}
+
+#[tokio::test]
+async fn test_user_agents_rotate_per_request() {
+    let (url, requests) = serve(OK).await;
+    let settings = settings().with_user_agents(["Agent/A", "Agent/B"], UserAgentRotation::PerRequest);
+    let client = RateLimitedClient::from_settings(&settings).unwrap();
+
+    for _ in 0..4 {
+        client.execute_raw(&url).await.unwrap();
+    }
+
+    let requests = requests.lock().unwrap();
+    let agents: Vec<&str> = requests.iter().map(|r| user_agent(r)).collect();
+    assert_eq!(agents[0], agents[2]);
+    assert_eq!(agents[1], agents[3]);
+    assert_ne!(agents[0], agents[1]);
+}
+
+#[tokio::test]
+async fn test_one_user_agent_is_used_per_session() {
+    let (url, requests) = serve(OK).await;
+    let settings = settings().with_user_agents(["Agent/A", "Agent/B"], UserAgentRotation::PerSession);
+    let client = RateLimitedClient::from_settings(&settings).unwrap();
+
+    for _ in 0..4 {
+        client.execute_raw(&url).await.unwrap();
+    }
+
+    let requests = requests.lock().unwrap();
+    let first = user_agent(&requests[0]);
+    assert!(["agent/a", "agent/b"].contains(&first));
+    assert!(requests.iter().all(|r| user_agent(r) == first));
+}
+
+#[tokio::test]
+async fn test_cookies_are_kept_across_runs() {
+    let cache = tempdir().unwrap();
+    let jar = cache.path().join(IMDB_COOKIE_JAR_FILE);
+    let (setter, _) = serve(SETS_SESSION).await;
+
+    let first_run = RateLimitedClient::from_settings(&settings().with_cookie_jar(&jar)).unwrap();
+    first_run.execute_raw(&setter).await.unwrap();
+    assert!(jar.is_file());
+    drop(first_run);
+
+    let second_run = RateLimitedClient::from_settings(&settings().with_cookie_jar(&jar)).unwrap();
+    assert_eq!(
+        second_run.cookie_jar().unwrap().cookies(),
+        vec![("session-id".to_string(), "abc123".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn test_saved_cookies_are_sent() {
+    let (url, requests) = serve(SETS_SESSION).await;
+    let cache = tempdir().unwrap();
+    let client = RateLimitedClient::from_settings(&settings().with_cookie_jar(cache.path().join("cookies.json"))).unwrap();
+
+    client.execute_raw(&url).await.unwrap();
+    client.execute_raw(&url).await.unwrap();
+
+    let requests = requests.lock().unwrap();
+    assert!(!requests[0].contains("cookie: "));
+    assert!(requests[1].contains("cookie: session-id=abc123"));
+}
+
+#[tokio::test]
+async fn test_cookie_saves_are_debounced_and_flushed_on_drop() {
+    let cache = tempdir().unwrap();
+    let jar = cache.path().join("cookies.json");
+    let (url, _) = serve(SETS_SESSION).await;
+    let client = RateLimitedClient::from_settings(&settings().with_cookie_jar(&jar)).unwrap();
+
+    client.execute_raw(&url).await.unwrap();
+    assert!(jar.is_file());
+    std::fs::remove_file(&jar).unwrap();
+
+    client.execute_raw(&url).await.unwrap();
+    assert!(!jar.exists());
+
+    drop(client);
+    assert!(std::fs::read_to_string(&jar).unwrap().contains("abc123"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_responses_leave_a_readable_cookie_jar() {
+    let cache = tempdir().unwrap();
+    let jar = cache.path().join("cookies.json");
+    let (url, _) = serve(SETS_SESSION).await;
+    let client = RateLimitedClient::from_settings(&settings().with_cookie_jar(&jar)).unwrap();
+
+    let results = futures::future::join_all((0..8).map(|_| client.execute_raw(&url))).await;
+    assert!(results.iter().all(Result::is_ok));
+    drop(client);
+
+    let reloaded = RateLimitedClient::from_settings(&settings().with_cookie_jar(&jar)).unwrap();
+    assert_eq!(reloaded.cookie_jar().unwrap().cookies(), vec![("session-id".to_string(), "abc123".to_string())]);
+    assert!(!jar.with_extension("json.tmp").exists());
+}
+
+#[test]
+fn test_unreadable_cookie_jar_starts_empty() {
+    let cache = tempdir().unwrap();
+    let jar = cache.path().join("cookies.json");
+    std::fs::write(&jar, "not json").unwrap();
+
+    let client = RateLimitedClient::from_settings(&settings().with_cookie_jar(&jar)).unwrap();
+
+    assert!(client.cookie_jar().unwrap().cookies().is_empty());
+}
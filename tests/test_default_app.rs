@@ -0,0 +1,29 @@
+use std::sync::Arc;
+use tempfile::tempdir;
+use trailerfin_rust::app::default_app;
+use trailerfin_rust::caching::{get_title_match_cache, get_tmdb_to_imdb_cache, initialize_caching};
+use trailerfin_rust::configuration::configuration_provider::{AppConfig, ConfigurationProvider, DataSource, LibraryProfile};
+use trailerfin_rust::request_clients::{get_imdb_client, initialize_request_clients};
+use trailerfin_rust::scrapers::{get_scraper, initialize_scrapers, scan_all_libraries};
+
+#[tokio::test]
+async fn test_free_functions_share_the_default_app() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    std::fs::create_dir_all(media.path().join("Movies")).unwrap();
+    let config = AppConfig::new(cache.path().to_string_lossy())
+        .with_user_agent("TestAgent")
+        .with_library(LibraryProfile::new("movies", media.path().to_string_lossy()).with_movie_folders(["Movies"]));
+    let config = ConfigurationProvider::validate_config(config).unwrap();
+
+    initialize_caching(config.clone());
+    initialize_request_clients(config.clone());
+    initialize_scrapers(config.clone());
+
+    let app = default_app();
+    assert!(Arc::ptr_eq(&get_tmdb_to_imdb_cache(), app.tmdb_to_imdb_cache()));
+    assert!(Arc::ptr_eq(&get_title_match_cache(), app.title_match_cache()));
+    assert!(Arc::ptr_eq(&get_imdb_client(), app.imdb_client()));
+    assert!(Arc::ptr_eq(&get_scraper(&DataSource::Imdb), &app.scraper(&DataSource::Imdb).unwrap()));
+    assert_eq!(scan_all_libraries(&config).await.unwrap().total, 0);
+}
@@ -0,0 +1,190 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tempfile::tempdir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use trailerfin_rust::app::App;
+use trailerfin_rust::configuration::configuration_provider::{AppConfig, ConfigurationProvider, LibraryProfile};
+use trailerfin_rust::request_clients::client_settings::ClientSettings;
+use trailerfin_rust::request_clients::fixtures::{Fixture, FixtureSet, RecordingExecutor, ReplayExecutor};
+use trailerfin_rust::request_clients::imdb_client::imdb_request_client::{self, ImdbRequestClient};
+use trailerfin_rust::request_clients::rate_limited_client::RateLimitedClient;
+use trailerfin_rust::request_clients::request_errors::error::Error;
+use trailerfin_rust::request_clients::retry_policy::RetryPolicy;
+use trailerfin_rust::request_clients::tmdb_client::tmdb_request_client::{self, TmdbRequestClient};
+
+const GALLERY: &str = "<html><a href=\"/video/vi42/\">Official Trailer</a></html>";
+
+/// Serves the same response to every connection and counts the requests.
+async fn serve(response: String) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            counter.fetch_add(1, Ordering::SeqCst);
+            let mut buf = [0u8; 4096];
+            _ = socket.read(&mut buf).await;
+            _ = socket.write_all(response.as_bytes()).await;
+            _ = socket.shutdown().await;
+        }
+    });
+
+    (url, hits)
+}
+
+fn ok(content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nSet-Cookie: session-id=1\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+fn live_executor() -> RateLimitedClient {
+    RateLimitedClient::from_settings(&ClientSettings::new("TestAgent", "100/second").with_retry_policy(RetryPolicy::none())).unwrap()
+}
+
+#[tokio::test]
+async fn test_recorded_imdb_pages_replay_without_the_network() {
+    let fixtures = tempdir().unwrap();
+    let (url, hits) = serve(ok("text/html", GALLERY)).await;
+
+    let recording = ImdbRequestClient(
+        imdb_request_client::ClientBuilder::default()
+            .with_base_url(url)
+            .with_executor(RecordingExecutor::new(live_executor(), FixtureSet::new(fixtures.path())))
+            .build()
+            .unwrap(),
+    );
+    let recorded = recording.get_raw("/title/tt1/videogallery/?sort=date,asc").await.unwrap();
+    assert_eq!(recorded.text(), GALLERY);
+
+    let replaying = ImdbRequestClient(
+        imdb_request_client::ClientBuilder::default()
+            .with_base_url("http://imdb.invalid")
+            .with_executor(ReplayExecutor::new(FixtureSet::new(fixtures.path())))
+            .build()
+            .unwrap(),
+    );
+    let replayed = replaying.get_raw("/title/tt1/videogallery/?sort=date,asc").await.unwrap();
+
+    assert_eq!(replayed.status(), 200);
+    assert_eq!(replayed.text(), GALLERY);
+    assert_eq!(replayed.headers["content-type"], "text/html");
+    assert!(!replayed.headers.contains_key("set-cookie"));
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_recorded_tmdb_responses_leave_out_the_api_key() {
+    let fixtures = tempdir().unwrap();
+    let (url, _) = serve(ok("application/json", "{\"id\":603,\"imdb_id\":\"tt0133093\"}")).await;
+    let tmdb_client = |base_url: String, api_key: &str, executor: RecordingOrReplay| {
+        let builder = tmdb_request_client::ClientBuilder::default()
+            .with_base_url(base_url)
+            .with_api_key(api_key.to_string());
+        let builder = match executor {
+            RecordingOrReplay::Recording => builder.with_executor(RecordingExecutor::new(live_executor(), FixtureSet::new(fixtures.path()))),
+            RecordingOrReplay::Replay => builder.with_executor(ReplayExecutor::new(FixtureSet::new(fixtures.path()))),
+        };
+        TmdbRequestClient(builder.build().unwrap())
+    };
+
+    let recorded = tmdb_client(format!("{url}/3"), "secret-key", RecordingOrReplay::Recording).external_ids().get_for_movie("603").await.unwrap();
+    let replayed = tmdb_client("http://tmdb.invalid/3".to_string(), "other-key", RecordingOrReplay::Replay)
+        .external_ids()
+        .get_for_movie("603")
+        .await
+        .unwrap();
+
+    assert_eq!(recorded.imdb_id.as_deref(), Some("tt0133093"));
+    assert_eq!(replayed.imdb_id.as_deref(), Some("tt0133093"));
+
+    let files: Vec<_> = std::fs::read_dir(fixtures.path()).unwrap().map(|e| e.unwrap().path()).collect();
+    assert_eq!(files.len(), 1);
+    let contents = std::fs::read_to_string(&files[0]).unwrap();
+    assert!(contents.contains("\"request\": \"/3/movie/603/external_ids\""));
+    assert!(!contents.contains("secret-key"));
+}
+
+enum RecordingOrReplay {
+    Recording,
+    Replay,
+}
+
+#[tokio::test]
+async fn test_requests_without_a_fixture_fail() {
+    let fixtures = tempdir().unwrap();
+    let client = ImdbRequestClient(
+        imdb_request_client::ClientBuilder::default()
+            .with_executor(ReplayExecutor::new(FixtureSet::new(fixtures.path())))
+            .build()
+            .unwrap(),
+    );
+
+    let result = client.get_raw("/title/tt1/videogallery/").await;
+
+    assert!(matches!(result, Err(Error::MissingFixture { request }) if request == "/title/tt1/videogallery/"));
+}
+
+#[test]
+fn test_request_keys_ignore_the_host_and_secrets() {
+    assert_eq!(
+        FixtureSet::request_key("https://api.themoviedb.org/3/movie/1/external_ids?api_key=abc&language=en"),
+        "/3/movie/1/external_ids?language=en"
+    );
+    assert_eq!(FixtureSet::request_key("http://imdb-cache:8080/title/tt1/"), "/title/tt1/");
+}
+
+#[tokio::test]
+async fn test_the_app_can_scan_against_a_fixture_set() {
+    let fixtures = tempdir().unwrap();
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let title = media.path().join("Movies").join("The Matrix (1999) {imdb-tt0133093}");
+    std::fs::create_dir_all(&title).unwrap();
+
+    let imdb_fixtures = FixtureSet::new(fixtures.path().join("imdb"));
+    let fixture = |request: &str, body: &str| Fixture {
+        request: request.to_string(),
+        status: 200,
+        headers: vec![("content-type".to_string(), "text/html".to_string())],
+        body: body.to_string(),
+    };
+    imdb_fixtures.save(&fixture("/title/tt0133093/videogallery/?sort=date,asc", GALLERY)).unwrap();
+    imdb_fixtures.save(&fixture(
+        "/video/vi42/",
+        concat!(
+            "<script id=\"__NEXT_DATA__\" type=\"application/json\">",
+            r#"{"props":{"pageProps":{"videoPlaybackData":{"video":{"playbackURLs":[{"videoMimeType":"MP4","videoDefinition":"DEF_1080p","url":"https://video.example/vi42.mp4?Expires=1"}]}}}}}"#,
+            "</script>"
+        ),
+    )).unwrap();
+
+    let mut config = AppConfig::new(cache.path().to_string_lossy())
+        .with_library(LibraryProfile::new("movies", media.path().to_string_lossy()).with_movie_folders(["Movies"]));
+    config.replay_fixtures = Some(fixtures.path().to_string_lossy().to_string());
+    let app = App::builder(ConfigurationProvider::validate_config(config).unwrap()).build().unwrap();
+
+    let summary = app.scan().await;
+
+    assert_eq!(summary.refreshed, 1, "{summary}");
+    let strm = std::fs::read_to_string(title.join("backdrops").join("video1.strm")).unwrap();
+    assert_eq!(strm, "https://video.example/vi42.mp4?Expires=1#t=8");
+}
+
+#[test]
+fn test_record_and_replay_are_exclusive() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    std::fs::create_dir_all(media.path().join("Movies")).unwrap();
+    let mut config = AppConfig::new(cache.path().to_string_lossy())
+        .with_library(LibraryProfile::new("movies", media.path().to_string_lossy()).with_movie_folders(["Movies"]));
+    config.record_fixtures = Some(cache.path().to_string_lossy().to_string());
+    config.replay_fixtures = Some(cache.path().to_string_lossy().to_string());
+
+    assert!(ConfigurationProvider::validate_config(config).is_err());
+}
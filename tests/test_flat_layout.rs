@@ -0,0 +1,95 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::tempdir;
+use trailerfin_rust::app::App;
+use trailerfin_rust::configuration::configuration_provider::{AppConfig, ConfigurationProvider, DataSource, LibraryProfile};
+use trailerfin_rust::request_clients::create_imdb_request_client;
+use trailerfin_rust::scrapers::imdb_trailers::ImdbTrailerScraper;
+use trailerfin_rust::scrapers::media_directories::trailer_path;
+use trailerfin_rust::scrapers::outcomes::TitleOutcome;
+use common::RecordingScraper;
+
+const VALID_TRAILER: &str = "https://example.com/trailer.mp4?Expires=99999999999";
+
+fn touch(root: &Path, files: &[&str]) {
+    for file in files {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+    }
+}
+
+fn library(root: &Path) -> LibraryProfile {
+    LibraryProfile::new("media", root.to_string_lossy()).with_movie_folders(["Movies"]).with_tv_folders(["Shows"])
+}
+
+fn config(cache: &Path, library: LibraryProfile) -> Arc<AppConfig> {
+    ConfigurationProvider::validate_config(AppConfig::new(cache.to_string_lossy()).with_user_agent("TestAgent").with_library(library)).unwrap()
+}
+
+#[tokio::test]
+async fn test_tagged_movie_files_are_titles() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let root = media.path().canonicalize().unwrap();
+    touch(&root, &[
+        "Movies/Inception (2010) {imdb-tt1375666}.mkv",
+        "Movies/Inception (2010) {imdb-tt1375666}-trailer.strm",
+        "Movies/Heat (1995) {imdb-tt0113277}.MP4",
+        "Movies/Heat (1995).nfo",
+        "Movies/Untagged (2001).mkv",
+        "Movies/Poster {imdb-tt0000001}.jpg",
+        "Shows/Episode {imdb-tt0000002}.mkv",
+    ]);
+    fs::create_dir_all(root.join("Shows/Breaking Bad {imdb-tt0903747}")).unwrap();
+
+    let scraper = Arc::new(RecordingScraper::default());
+    let app = App::builder(config(cache.path(), library(&root)))
+        .with_scraper(DataSource::Imdb, scraper.clone())
+        .build()
+        .unwrap();
+    app.scan().await;
+
+    let mut processed = scraper.paths();
+    processed.sort();
+    assert_eq!(processed, vec![
+        root.join("Movies/Heat (1995) {imdb-tt0113277}.MP4"),
+        root.join("Movies/Inception (2010) {imdb-tt1375666}.mkv"),
+        root.join("Shows/Breaking Bad {imdb-tt0903747}"),
+    ]);
+}
+
+#[test]
+fn test_movie_files_get_their_own_trailer_file() {
+    let media = tempdir().unwrap();
+    touch(media.path(), &["Movies/Inception (2010) {imdb-tt1375666}.mkv", "Movies/Heat (1995) {imdb-tt0113277}.mkv"]);
+    let library = library(media.path());
+    let scraper = ImdbTrailerScraper::new(create_imdb_request_client(&AppConfig::new("/tmp").with_user_agent("TestAgent")).unwrap());
+
+    let inception = media.path().join("Movies/Inception (2010) {imdb-tt1375666}.mkv");
+    let heat = media.path().join("Movies/Heat (1995) {imdb-tt0113277}.mkv");
+    scraper.create_or_update_strm_file(&inception, &library, "https://example.com/inception.mp4").unwrap();
+    scraper.create_or_update_strm_file(&heat, &library, "https://example.com/heat.mp4").unwrap();
+
+    assert_eq!(trailer_path(&inception, &library), media.path().join("Movies/Inception (2010) {imdb-tt1375666}-trailer.strm"));
+    assert_eq!(fs::read_to_string(trailer_path(&inception, &library)).unwrap(), "https://example.com/inception.mp4");
+    assert_eq!(fs::read_to_string(trailer_path(&heat, &library)).unwrap(), "https://example.com/heat.mp4");
+    assert!(!media.path().join("Movies/backdrops").exists());
+}
+
+#[tokio::test]
+async fn test_movie_files_with_a_valid_trailer_are_left_alone() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    touch(media.path(), &["Movies/Inception (2010) {imdb-tt1375666}.mkv"]);
+    fs::create_dir_all(media.path().join("Shows")).unwrap();
+    fs::write(media.path().join("Movies/Inception (2010) {imdb-tt1375666}-trailer.strm"), VALID_TRAILER).unwrap();
+    let app = App::from_config(config(cache.path(), library(media.path()))).unwrap();
+
+    let outcome = app.refresh_path(&media.path().join("Movies/Inception (2010) {imdb-tt1375666}.mkv")).await.unwrap();
+
+    assert_eq!(outcome, TitleOutcome::StillValid { id: "tt1375666".to_string() });
+}
@@ -0,0 +1,286 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::tempdir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use trailerfin_rust::app::App;
+use trailerfin_rust::configuration::configuration_provider::{AppConfig, ConfigurationProvider, DataSource, FolderTagging, LibraryProfile};
+use trailerfin_rust::configuration::id_patterns::IdPreset;
+use trailerfin_rust::request_clients::client_settings::ClientSettings;
+use trailerfin_rust::request_clients::rate_limited_client::RateLimitedClient;
+use trailerfin_rust::request_clients::retry_policy::RetryPolicy;
+use trailerfin_rust::request_clients::tmdb_client::tmdb_request_client::{ClientBuilder, TmdbRequestClient};
+use trailerfin_rust::scrapers::folder_tagger::{FolderIds, FolderTagger, TagChange, SIDECAR_FILENAME};
+use trailerfin_rust::scrapers::outcomes::TitleOutcome;
+
+const VALID_TRAILER: &str = "https://example.com/trailer.mp4?Expires=99999999999";
+
+fn matrix_ids() -> FolderIds {
+    FolderIds { imdb: Some("tt0133093".to_string()), tmdb: Some(603), tvdb: None }
+}
+
+fn library(root: &Path, tagging: FolderTagging, dry_run: bool) -> LibraryProfile {
+    LibraryProfile::new("movies", root.to_string_lossy())
+        .with_movie_folders(["Movies"])
+        .with_resolve_untagged(true)
+        .with_tag_folders(tagging, dry_run)
+}
+
+fn title_folder(root: &Path, name: &str) -> std::path::PathBuf {
+    let folder = root.join("Movies").join(name);
+    fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+#[test]
+fn test_sidecar_is_written_read_back_and_undone() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let folder = title_folder(media.path(), "The Matrix (1999)");
+    let tagger = FolderTagger::new(cache.path());
+
+    let tagged = tagger.tag(&folder, &library(media.path(), FolderTagging::Sidecar, false), &matrix_ids());
+
+    assert_eq!(tagged, folder);
+    assert_eq!(FolderIds::read(&folder), Some(matrix_ids()));
+
+    let undone = tagger.undo(false).unwrap();
+    assert_eq!(undone, vec![TagChange::Sidecar { path: folder.join(SIDECAR_FILENAME) }]);
+    assert!(!folder.join(SIDECAR_FILENAME).exists());
+    assert!(tagger.undo(false).unwrap().is_empty());
+}
+
+#[test]
+fn test_rename_appends_a_tag_the_id_regex_reads_back() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let folder = title_folder(media.path(), "The Matrix (1999) [1080p]");
+    let tagger = FolderTagger::new(cache.path());
+
+    let tagged = tagger.tag(&folder, &library(media.path(), FolderTagging::Rename, false), &matrix_ids());
+
+    assert_eq!(tagged, media.path().join("Movies").join("The Matrix (1999) [1080p] {imdb-tt0133093}"));
+    assert!(tagged.is_dir());
+    assert!(!folder.exists());
+
+    let tmdb_library = library(media.path(), FolderTagging::Rename, false).with_data_source(DataSource::Tmdb);
+    let retagged = tagger.tag(&tagged, &tmdb_library, &matrix_ids());
+    assert!(retagged.ends_with("The Matrix (1999) [1080p] {imdb-tt0133093} {tmdb-603}"));
+
+    tagger.undo(false).unwrap();
+    assert!(folder.is_dir());
+    assert!(!tagged.exists() && !retagged.exists());
+}
+
+#[test]
+fn test_rename_reads_the_tag_back_from_the_folder_name_only() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let root = media.path().join("Backup of tt0000001");
+    let folder = title_folder(&root, "The Matrix (1999)");
+    let tagger = FolderTagger::new(cache.path());
+
+    let tagged = tagger.tag(&folder, &library(&root, FolderTagging::Rename, false), &matrix_ids());
+
+    assert_eq!(tagged, root.join("Movies").join("The Matrix (1999) {imdb-tt0133093}"));
+}
+
+#[test]
+fn test_renamed_folders_are_returned_when_the_undo_log_cannot_be_written() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let folder = title_folder(media.path(), "The Matrix (1999)");
+    let tagger = FolderTagger::new(cache.path());
+    fs::create_dir(tagger.undo_log()).unwrap();
+
+    let tagged = tagger.tag(&folder, &library(media.path(), FolderTagging::Rename, false), &matrix_ids());
+
+    assert_eq!(tagged, media.path().join("Movies").join("The Matrix (1999) {imdb-tt0133093}"));
+    assert!(tagged.is_dir());
+}
+
+#[test]
+fn test_rename_uses_the_naming_convention_of_the_library_preset() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let tagger = FolderTagger::new(cache.path());
+    let preset_library = |preset: IdPreset| library(media.path(), FolderTagging::Rename, false).with_id_regexes("", "").with_id_presets([preset]);
+
+    let jellyfin = tagger.tag(&title_folder(media.path(), "The Matrix (1999)"), &preset_library(IdPreset::Jellyfin), &matrix_ids());
+    assert_eq!(jellyfin, media.path().join("Movies").join("The Matrix (1999) [imdbid-tt0133093]"));
+
+    let emby = tagger.tag(&title_folder(media.path(), "Heat (1995)"), &preset_library(IdPreset::Emby).with_data_source(DataSource::Tmdb), &matrix_ids());
+    assert_eq!(emby, media.path().join("Movies").join("Heat (1995) [tmdbid=603]"));
+}
+
+#[test]
+fn test_dry_run_changes_nothing() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let folder = title_folder(media.path(), "The Matrix (1999)");
+    let tagger = FolderTagger::new(cache.path());
+
+    assert_eq!(tagger.tag(&folder, &library(media.path(), FolderTagging::Rename, true), &matrix_ids()), folder);
+    assert_eq!(tagger.tag(&folder, &library(media.path(), FolderTagging::Sidecar, true), &matrix_ids()), folder);
+
+    assert!(folder.is_dir());
+    assert!(!folder.join(SIDECAR_FILENAME).exists());
+    assert!(!tagger.undo_log().exists());
+}
+
+#[test]
+fn test_collisions_and_unreadable_tags_are_left_alone() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let tagger = FolderTagger::new(cache.path());
+
+    let folder = title_folder(media.path(), "The Matrix (1999)");
+    let existing = title_folder(media.path(), "The Matrix (1999) {imdb-tt0133093}");
+    assert_eq!(tagger.tag(&folder, &library(media.path(), FolderTagging::Rename, false), &matrix_ids()), folder);
+    assert!(existing.is_dir() && folder.is_dir());
+
+    let other_ids = r#"{"imdb":"tt0234215"}"#;
+    fs::write(folder.join(SIDECAR_FILENAME), other_ids).unwrap();
+    tagger.tag(&folder, &library(media.path(), FolderTagging::Sidecar, false), &matrix_ids());
+    assert_eq!(fs::read_to_string(folder.join(SIDECAR_FILENAME)).unwrap(), other_ids);
+
+    let brackets = library(media.path(), FolderTagging::Rename, false).with_id_regexes(r"\[imdbid-(tt\d+)\]", r"\[tmdbid-(\d+)\]");
+    let untouched = title_folder(media.path(), "Hackers (1995)");
+    assert_eq!(tagger.tag(&untouched, &brackets, &matrix_ids()), untouched);
+
+    assert!(!tagger.undo_log().exists());
+}
+
+#[tokio::test]
+async fn test_scans_read_ids_from_sidecars() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let folder = title_folder(media.path(), "The Matrix");
+    fs::create_dir_all(folder.join("backdrops")).unwrap();
+    fs::write(folder.join("backdrops").join("video1.strm"), VALID_TRAILER).unwrap();
+    fs::write(folder.join(SIDECAR_FILENAME), r#"{"imdb":"tt0133093","tmdb":603}"#).unwrap();
+
+    let config = AppConfig::new(cache.path().to_string_lossy())
+        .with_user_agent("TestAgent")
+        .with_library(LibraryProfile::new("movies", media.path().to_string_lossy()).with_movie_folders(["Movies"]));
+    let app = App::from_config(ConfigurationProvider::validate_config(config).unwrap()).unwrap();
+
+    let outcome = app.refresh_path(&folder).await.unwrap();
+
+    assert_eq!(outcome, TitleOutcome::StillValid { id: "tt0133093".to_string() });
+}
+
+async fn serve_matrix() -> String {
+    serve(
+        "GET /search/movie?",
+        r#"{"page":1,"results":[{"id":603,"title":"The Matrix","original_title":"The Matrix","release_date":"1999-03-31"}],"total_pages":1,"total_results":1}"#,
+        r#"{"id":603,"imdb_id":"tt0133093"}"#,
+    )
+    .await
+}
+
+/// Answers searches starting with `search` with `search_body`, and everything else with `external_ids`.
+async fn serve(search: &'static str, search_body: &'static str, external_ids: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let read = socket.read(&mut buf).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buf[..read]).to_string();
+            let body = if request.starts_with(search) { search_body } else { external_ids };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            _ = socket.write_all(response.as_bytes()).await;
+            _ = socket.shutdown().await;
+        }
+    });
+    url
+}
+
+#[tokio::test]
+async fn test_resolved_folders_are_renamed_before_their_trailer_is_refreshed() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let folder = title_folder(media.path(), "The Matrix (1999)");
+    fs::create_dir_all(folder.join("backdrops")).unwrap();
+    fs::write(folder.join("backdrops").join("video1.strm"), VALID_TRAILER).unwrap();
+
+    let config = AppConfig::new(cache.path().to_string_lossy())
+        .with_user_agent("TestAgent")
+        .with_tmdb_api_key("key")
+        .with_library(library(media.path(), FolderTagging::Rename, false));
+    let app = App::builder(ConfigurationProvider::validate_config(config).unwrap())
+        .with_tmdb_client(tmdb_client(serve_matrix().await))
+        .build()
+        .unwrap();
+
+    let outcome = app.refresh_path(&folder).await.unwrap();
+
+    assert_eq!(outcome, TitleOutcome::StillValid { id: "tt0133093".to_string() });
+    let renamed = media.path().join("Movies").join("The Matrix (1999) {imdb-tt0133093}");
+    assert!(renamed.join("backdrops").join("video1.strm").is_file());
+    assert!(!folder.exists());
+}
+
+fn tmdb_client(base_url: String) -> Arc<TmdbRequestClient> {
+    let executor = RateLimitedClient::from_settings(&ClientSettings::new("TestAgent", "100/second").with_retry_policy(RetryPolicy::none())).unwrap();
+    Arc::new(TmdbRequestClient(
+        ClientBuilder::default()
+            .with_base_url(base_url)
+            .with_api_key("key".to_string())
+            .with_executor(executor)
+            .build()
+            .unwrap(),
+    ))
+}
+
+#[tokio::test]
+async fn test_resolved_shows_get_their_tvdb_id_in_the_sidecar() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    fs::create_dir_all(media.path().join("Movies")).unwrap();
+    let folder = media.path().join("Shows").join("Breaking Bad (2008)");
+    fs::create_dir_all(folder.join("backdrops")).unwrap();
+    fs::write(folder.join("backdrops").join("video1.strm"), VALID_TRAILER).unwrap();
+    let url = serve(
+        "GET /search/tv?",
+        r#"{"page":1,"results":[{"id":1396,"name":"Breaking Bad","original_name":"Breaking Bad","first_air_date":"2008-01-20"}],"total_pages":1,"total_results":1}"#,
+        r#"{"id":1396,"imdb_id":"tt0903747","tvdb_id":81189}"#,
+    )
+    .await;
+
+    let config = AppConfig::new(cache.path().to_string_lossy())
+        .with_user_agent("TestAgent")
+        .with_tmdb_api_key("key")
+        .with_library(library(media.path(), FolderTagging::Sidecar, false).with_tv_folders(["Shows"]));
+    let app = App::builder(ConfigurationProvider::validate_config(config).unwrap())
+        .with_tmdb_client(tmdb_client(url))
+        .build()
+        .unwrap();
+
+    let outcome = app.refresh_path(&folder).await.unwrap();
+
+    assert_eq!(outcome, TitleOutcome::StillValid { id: "tt0903747".to_string() });
+    assert_eq!(
+        FolderIds::read(&folder),
+        Some(FolderIds { imdb: Some("tt0903747".to_string()), tmdb: Some(1396), tvdb: Some(81189) })
+    );
+}
+
+#[test]
+fn test_tagging_needs_resolving_untagged_folders() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    title_folder(media.path(), "");
+
+    let config = AppConfig::new(cache.path().to_string_lossy())
+        .with_library(library(media.path(), FolderTagging::Sidecar, false).with_resolve_untagged(false));
+
+    let err = ConfigurationProvider::validate_config(config).unwrap_err();
+    assert!(err.to_string().contains("TRAILERFIN_TAG_FOLDERS"));
+}
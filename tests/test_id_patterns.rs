@@ -0,0 +1,82 @@
+use std::fs;
+use tempfile::tempdir;
+use trailerfin_rust::app::App;
+use trailerfin_rust::configuration::configuration_provider::{AppConfig, ConfigurationProvider, LibraryProfile};
+use trailerfin_rust::configuration::id_patterns::{IdKind, IdPatterns, IdPreset};
+use trailerfin_rust::scrapers::outcomes::TitleOutcome;
+
+#[test]
+fn test_presets_read_their_naming_conventions() {
+    let library = LibraryProfile::new("movies", "/media").with_id_regexes("", "").with_id_presets(IdPreset::ALL);
+    let patterns = library.id_patterns().unwrap();
+
+    assert_eq!(patterns.imdb.find("The Matrix (1999) {imdb-tt0133093}"), Some("tt0133093"));
+    assert_eq!(patterns.imdb.find("The Matrix (1999) [imdbid-tt0133093]"), Some("tt0133093"));
+    assert_eq!(patterns.imdb.find("The Matrix (1999) [imdbid=tt0133093]"), Some("tt0133093"));
+    assert_eq!(patterns.tmdb.find("The Matrix (1999) {tmdb-603}"), Some("603"));
+    assert_eq!(patterns.tmdb.find("The Matrix (1999) [tmdbid-603]"), Some("603"));
+    assert_eq!(patterns.tmdb.find("The Matrix (1999) [tmdbid=603]"), Some("603"));
+    assert_eq!(patterns.imdb.find("The Matrix (1999)"), None);
+}
+
+#[test]
+fn test_named_groups_win_over_the_first_group() {
+    let patterns = IdPatterns::new(IdKind::Imdb, &[r"(\d{4}) .*\[imdb (?P<imdb>tt\d+)\]", r"(tt\d+)"]).unwrap();
+
+    assert_eq!(patterns.find("1999 The Matrix [imdb tt0133093]"), Some("tt0133093"));
+    assert_eq!(patterns.find("The Matrix tt0133093"), Some("tt0133093"));
+}
+
+#[test]
+fn test_patterns_are_tried_in_order() {
+    let library = LibraryProfile::new("movies", "/media")
+        .with_additional_id_regexes([r"\(imdb (tt\d+)\)"], Vec::<String>::new())
+        .with_id_presets([IdPreset::Jellyfin]);
+    let patterns = library.id_patterns().unwrap();
+
+    assert_eq!(
+        patterns.imdb.patterns().collect::<Vec<_>>(),
+        vec![r"\{imdb-(tt\d+)}", r"\(imdb (tt\d+)\)", IdPreset::Jellyfin.imdb_pattern()]
+    );
+    assert_eq!(patterns.imdb.find("Heat {imdb-tt0113277} [imdbid-tt0000001]"), Some("tt0113277"));
+    assert_eq!(patterns.imdb.find("Heat (imdb tt0113277) [imdbid-tt0000001]"), Some("tt0113277"));
+}
+
+#[test]
+fn test_invalid_patterns_fail_validation() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    fs::create_dir_all(media.path().join("Movies")).unwrap();
+    let validate = |library: LibraryProfile| {
+        let config = AppConfig::new(cache.path().to_string_lossy()).with_library(library.with_movie_folders(["Movies"]));
+        ConfigurationProvider::validate_config(config).map(|_| ()).unwrap_err().to_string()
+    };
+    let library = || LibraryProfile::new("movies", media.path().to_string_lossy());
+
+    assert!(validate(library().with_id_regexes("", "")).contains("TRAILERFIN_IMDB_ID_REGEX must be set and cannot be empty (library 'movies')"));
+    assert!(validate(library().with_additional_id_regexes(["[imdb"], Vec::<String>::new())).contains("Invalid TRAILERFIN_IMDB_ID_REGEX pattern '[imdb'"));
+    assert!(validate(library().with_additional_id_regexes(Vec::<&str>::new(), vec![r"tmdb-\d+".to_string()])).contains("needs a capture group"));
+
+    let config = AppConfig::new(cache.path().to_string_lossy())
+        .with_library(library().with_movie_folders(["Movies"]).with_id_regexes("", "").with_id_presets([IdPreset::Plex]));
+    assert!(ConfigurationProvider::validate_config(config).is_ok());
+}
+
+#[tokio::test]
+async fn test_scans_find_ids_with_any_pattern() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let folder = media.path().join("Movies").join("The Matrix (1999) [imdbid-tt0133093]");
+    fs::create_dir_all(folder.join("backdrops")).unwrap();
+    fs::write(folder.join("backdrops").join("video1.strm"), "https://example.com/trailer.mp4?Expires=99999999999").unwrap();
+
+    let library = LibraryProfile::new("movies", media.path().to_string_lossy())
+        .with_movie_folders(["Movies"])
+        .with_id_presets([IdPreset::Jellyfin]);
+    let config = AppConfig::new(cache.path().to_string_lossy()).with_user_agent("TestAgent").with_library(library);
+    let app = App::from_config(ConfigurationProvider::validate_config(config).unwrap()).unwrap();
+
+    let outcome = app.refresh_path(&folder).await.unwrap();
+
+    assert_eq!(outcome, TitleOutcome::StillValid { id: "tt0133093".to_string() });
+}
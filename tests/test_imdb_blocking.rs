@@ -0,0 +1,137 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::StatusCode;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use trailerfin_rust::request_clients::circuit_breaker::CircuitBreaker;
+use trailerfin_rust::request_clients::client_settings::ClientSettings;
+use trailerfin_rust::request_clients::imdb_client::bot_wall::detect_block;
+use trailerfin_rust::request_clients::imdb_client::imdb_request_client::{ClientBuilder, ImdbRequestClient};
+use trailerfin_rust::request_clients::rate_limited_client::RateLimitedClient;
+use trailerfin_rust::request_clients::raw_response::RawResponse;
+use trailerfin_rust::request_clients::request_errors::error::Error;
+use trailerfin_rust::request_clients::retry_policy::RetryPolicy;
+
+/// Serves the same response to every connection and counts the requests.
+async fn serve(response: &'static str) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            counter.fetch_add(1, Ordering::SeqCst);
+
+            let mut buf = [0u8; 4096];
+            _ = socket.read(&mut buf).await;
+            _ = socket.write_all(response.as_bytes()).await;
+            _ = socket.shutdown().await;
+        }
+    });
+
+    (url, hits)
+}
+
+fn imdb_client(base_url: String, circuit_breaker: CircuitBreaker) -> ImdbRequestClient {
+    let settings = ClientSettings::new("TestAgent", "100/second").with_retry_policy(RetryPolicy::none());
+    let inner = ClientBuilder::default()
+        .with_base_url(base_url)
+        .with_executor(RateLimitedClient::from_settings(&settings).unwrap())
+        .with_circuit_breaker(circuit_breaker)
+        .build()
+        .unwrap();
+    ImdbRequestClient(inner)
+}
+
+fn raw(status: StatusCode, headers: &[(&'static str, &'static str)], body: &str) -> RawResponse {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        map.insert(*name, HeaderValue::from_static(value));
+    }
+    RawResponse { status, headers: map, body: body.to_string() }
+}
+
+#[test]
+fn test_detects_bot_walls_and_challenges() {
+    assert!(detect_block(&raw(StatusCode::ACCEPTED, &[], "")).is_some());
+    assert!(detect_block(&raw(StatusCode::ACCEPTED, &[("x-amzn-waf-action", "challenge")], "")).is_some());
+    assert!(detect_block(&raw(StatusCode::METHOD_NOT_ALLOWED, &[], "<div id=\"captcha-container\"></div>")).is_some());
+    assert!(detect_block(&raw(StatusCode::OK, &[], "<script src=\"https://x.token.awswaf.com/challenge.js\"></script>")).is_some());
+    assert!(detect_block(&raw(StatusCode::FORBIDDEN, &[], "Forbidden")).is_some());
+}
+
+#[test]
+fn test_regular_responses_are_not_blocks() {
+    assert_eq!(detect_block(&raw(StatusCode::OK, &[], "<html><a href=\"/video/vi123\">Trailer</a></html>")), None);
+    assert_eq!(detect_block(&raw(StatusCode::NOT_FOUND, &[], "Not found")), None);
+    assert_eq!(detect_block(&raw(StatusCode::ACCEPTED, &[], "{\"queued\":true}")), None);
+}
+
+#[test]
+fn test_circuit_opens_after_consecutive_failures_and_probes_after_cooldown() {
+    let breaker = CircuitBreaker::new("IMDb", 3, Duration::from_millis(50));
+
+    breaker.record_failure();
+    breaker.record_failure();
+    breaker.record_success();
+    breaker.record_failure();
+    breaker.record_failure();
+    assert!(breaker.acquire().is_ok());
+
+    breaker.record_failure();
+    assert!(matches!(breaker.acquire(), Err(Error::CircuitOpen { .. })));
+    assert!(breaker.open_reason().is_some());
+
+    std::thread::sleep(Duration::from_millis(60));
+    assert!(breaker.acquire().is_ok(), "the probe request is let through");
+    assert!(breaker.acquire().is_err(), "only one probe at a time");
+
+    breaker.record_failure();
+    assert!(breaker.acquire().is_err(), "a failed probe reopens the circuit");
+
+    std::thread::sleep(Duration::from_millis(60));
+    assert!(breaker.acquire().is_ok());
+    breaker.record_success();
+    assert!(breaker.acquire().is_ok());
+    assert!(breaker.acquire().is_ok());
+    assert_eq!(breaker.open_reason(), None);
+}
+
+#[test]
+fn test_disabled_circuit_never_opens() {
+    let breaker = CircuitBreaker::disabled();
+    for _ in 0..100 {
+        breaker.record_failure();
+    }
+    assert!(breaker.acquire().is_ok());
+}
+
+#[tokio::test]
+async fn test_blocked_responses_trip_the_circuit_breaker() {
+    let (url, hits) = serve("HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+    let client = imdb_client(url, CircuitBreaker::new("IMDb", 2, Duration::from_secs(60)));
+
+    for _ in 0..2 {
+        assert!(matches!(client.get_raw("/title/tt1/videogallery/").await, Err(Error::Blocked { status: 202, .. })));
+    }
+
+    assert!(matches!(client.get_raw("/title/tt1/videogallery/").await, Err(Error::CircuitOpen { .. })));
+    assert!(client.paused_reason().is_some());
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_successful_responses_are_returned_with_their_body() {
+    let (url, _) = serve("HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello").await;
+    let client = imdb_client(url, CircuitBreaker::new("IMDb", 1, Duration::from_secs(60)));
+
+    let res = client.get_raw("/").await.unwrap();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.text(), "hello");
+    assert_eq!(client.paused_reason(), None);
+}
@@ -1,6 +1,7 @@
 use std::fs::{self};
 use tempfile::tempdir;
-use trailerfin_rust::configuration::configuration_provider::{AppConfig, DataSource};
+use trailerfin_rust::configuration::configuration_provider::{AppConfig, DataSource, LibraryProfile};
+use trailerfin_rust::request_clients::create_imdb_request_client;
 use trailerfin_rust::scrapers::imdb_trailers::ImdbTrailerScraper;
 
 #[tokio::test]
@@ -8,27 +9,18 @@ async fn test_create_strm_file_writes_correct_url() {
     let dir = tempdir().unwrap();
     let folder = dir.path();
 
-    let config = AppConfig {
-        scan_path: folder.to_string_lossy().to_string(),
-        video_filename: "video1.strm".to_string(),
-        should_schedule: false,
-        schedule: Some("* * * * *".to_string()),
-        user_agent: "TestAgent".to_string(),
-        threads: 1,
-        cache_path: "./data/cache".to_string(),
-        data_source: DataSource::Imdb,
-        imdb_rate_limit: "30/minute".to_string(),
-        tmdb_rate_limit: "50/second".to_string(),
-        tmdb_api_key: Some("ham-and-cheese-sandwich".to_string()),
-        imdb_id_regex: r"\{imdb-(tt\d+)}".to_string(),
-        tmdb_id_regex: r"\{tmdb-(\d+)}".to_string(),
-        tv_folders: vec!["shows", "kids tv"].iter().map(|s| s.to_string()).collect(),
-        movie_folders: vec!["movies", "kids"].iter().map(|s| s.to_string()).collect(),
-    };
+    let library = LibraryProfile::new("default", folder.to_string_lossy())
+        .with_video_filename("video1.strm")
+        .with_trailer_folder("backdrops")
+        .with_schedule("* * * * *")
+        .with_data_source(DataSource::Imdb)
+        .with_id_regexes(r"\{imdb-(tt\d+)}", r"\{tmdb-(\d+)}")
+        .with_tv_folders(["shows", "kids tv"])
+        .with_movie_folders(["movies", "kids"]);
 
     let url = "https://example.com/video.mp4";
-    let scraper = ImdbTrailerScraper {};
-    scraper.create_or_update_strm_file(folder, &config, url).unwrap();
+    let scraper = imdb_scraper();
+    scraper.create_or_update_strm_file(folder, &library, url).unwrap();
 
     let written = fs::read_to_string(folder.join("backdrops").join("video1.strm")).unwrap();
     assert_eq!(written, url);
@@ -44,28 +36,28 @@ async fn test_overwrite_existing_strm_file() {
     let file_path = backdrops.join("video1.strm");
     fs::write(&file_path, "old_url").unwrap();
 
-    let config = AppConfig {
-        scan_path: folder.to_string_lossy().to_string(),
-        video_filename: "video1.strm".to_string(),
-        should_schedule: false,
-        schedule: Some("* * * * *".to_string()),
-        user_agent: "TestAgent".to_string(),
-        threads: 1,
-        cache_path: "./data/cache".to_string(),
-        data_source: DataSource::Imdb,
-        imdb_rate_limit: "30/minute".to_string(),
-        tmdb_rate_limit: "50/second".to_string(),
-        tmdb_api_key: Some("ham-and-cheese-sandwich".to_string()),
-        imdb_id_regex: r"\{imdb-(tt\d+)}".to_string(),
-        tmdb_id_regex: r"\{tmdb-(\d+)}".to_string(),
-        tv_folders: vec!["shows", "kids tv"].iter().map(|s| s.to_string()).collect(),
-        movie_folders: vec!["movies", "kids"].iter().map(|s| s.to_string()).collect(),
-    };
+    let library = LibraryProfile::new("default", folder.to_string_lossy())
+        .with_video_filename("video1.strm")
+        .with_trailer_folder("backdrops")
+        .with_schedule("* * * * *")
+        .with_data_source(DataSource::Imdb)
+        .with_id_regexes(r"\{imdb-(tt\d+)}", r"\{tmdb-(\d+)}")
+        .with_tv_folders(["shows", "kids tv"])
+        .with_movie_folders(["movies", "kids"]);
 
     let new_url = "https://example.com/new_video.mp4";
-    let scraper = ImdbTrailerScraper {};
-    scraper.create_or_update_strm_file(folder, &config, new_url).unwrap();
+    let scraper = imdb_scraper();
+    scraper.create_or_update_strm_file(folder, &library, new_url).unwrap();
 
     let written = fs::read_to_string(file_path).unwrap();
     assert_eq!(written, new_url);
-}
\ No newline at end of file
+}
+
+fn imdb_scraper() -> ImdbTrailerScraper {
+    let config = AppConfig {
+        user_agent: "TestAgent".to_string(),
+        imdb_rate_limit: "30/minute".to_string(),
+        ..Default::default()
+    };
+    ImdbTrailerScraper::new(create_imdb_request_client(&config).unwrap())
+}
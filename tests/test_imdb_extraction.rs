@@ -0,0 +1,121 @@
+use std::time::Duration;
+use chrono::NaiveDate;
+use trailerfin_rust::request_clients::imdb_client::extraction_stats::{ExtractionStats, PageKind, DRIFT_BASELINE, DRIFT_WINDOW};
+use trailerfin_rust::request_clients::imdb_client::video_gallery_endpoints::{parse_video_gallery, VideoGalleryStrategy, VideoKind};
+use trailerfin_rust::request_clients::imdb_client::video_page_endpoints::{best_rendition, parse_video_page, VideoPageStrategy};
+
+#[test]
+fn test_current_video_pages_use_the_first_line_of_next_data() {
+    let page = parse_video_page(include_str!("fixtures/imdb/video_vi1032782617.html"));
+
+    assert_eq!(page.strategy, Some(VideoPageStrategy::NextDataFirstLine));
+}
+
+#[test]
+fn test_video_pages_fall_back_to_the_whole_next_data_script() {
+    let page = parse_video_page(include_str!("fixtures/imdb/video_pretty_next_data.html"));
+
+    assert_eq!(page.strategy, Some(VideoPageStrategy::NextDataScript));
+    assert_eq!(page.renditions[0].definition.as_deref(), Some("DEF_720p"));
+}
+
+#[test]
+fn test_video_pages_fall_back_to_alternative_paths() {
+    let page = parse_video_page(include_str!("fixtures/imdb/video_alternative_path.html"));
+
+    assert_eq!(page.strategy, Some(VideoPageStrategy::AlternativePath));
+    assert_eq!(page.renditions[0].url, "https://imdb-video.media-imdb.com/vi1/480.mp4?Expires=1760000000");
+}
+
+#[test]
+fn test_video_pages_fall_back_to_embedded_graphql_responses() {
+    let page = parse_video_page(include_str!("fixtures/imdb/video_graphql.html"));
+
+    assert_eq!(page.strategy, Some(VideoPageStrategy::GraphQl));
+    assert!(page.renditions[0].is_mp4());
+    assert_eq!(page.renditions[0].quality_rank(), 3);
+}
+
+#[test]
+fn test_video_pages_fall_back_to_json_ld_video_objects() {
+    let page = parse_video_page(include_str!("fixtures/imdb/video_json_ld.html"));
+
+    assert_eq!(page.strategy, Some(VideoPageStrategy::JsonLdVideoObject));
+    let best = best_rendition(&page.renditions).unwrap();
+    assert_eq!(best.url, "https://imdb-video.media-imdb.com/vi1/trailer.mp4?Expires=1760000000");
+    assert!(best.is_mp4());
+    assert_eq!(best.definition.as_deref(), Some("DEF_720p"));
+}
+
+#[test]
+fn test_galleries_fall_back_to_page_data() {
+    let gallery = parse_video_gallery(include_str!("fixtures/imdb/videogallery_next_data.html"));
+
+    assert_eq!(gallery.strategy, Some(VideoGalleryStrategy::PageData));
+    let trailer = &gallery.items[1];
+    assert_eq!(trailer.id, "vi1032782617");
+    assert_eq!(trailer.title, "Official Trailer");
+    assert_eq!(trailer.kind, VideoKind::Trailer);
+    assert_eq!(trailer.duration, Some(Duration::from_secs(146)));
+    assert_eq!(trailer.date, NaiveDate::from_ymd_opt(1999, 3, 2));
+    assert_eq!(gallery.items[0].kind, VideoKind::Featurette);
+}
+
+#[test]
+fn test_galleries_fall_back_to_raw_video_ids() {
+    let gallery = parse_video_gallery("<div data-href=\"/video/vi7/\"></div><div data-href=\"/video/vi7/\"></div><div data-href=\"/video/vi8/\"></div>");
+
+    assert_eq!(gallery.strategy, Some(VideoGalleryStrategy::RawIds));
+    let ids: Vec<&str> = gallery.items.iter().map(|item| item.id.as_str()).collect();
+    assert_eq!(ids, ["vi7", "vi8"]);
+}
+
+#[test]
+fn test_pages_without_data_have_no_strategy() {
+    assert_eq!(parse_video_gallery("<html></html>").strategy, None);
+    assert_eq!(parse_video_page("<html></html>").strategy, None);
+}
+
+#[test]
+fn test_stats_count_successes_per_strategy() {
+    let stats = ExtractionStats::new();
+    stats.record(PageKind::VideoPage, Some("next_data_first_line"));
+    stats.record(PageKind::VideoPage, Some("next_data_first_line"));
+    stats.record(PageKind::VideoPage, Some("graphql"));
+    stats.record(PageKind::VideoPage, None);
+
+    assert_eq!(stats.successes(PageKind::VideoPage, "next_data_first_line"), 2);
+    assert_eq!(stats.successes(PageKind::VideoPage, "graphql"), 1);
+    assert_eq!(stats.misses(PageKind::VideoPage), 1);
+    assert_eq!(stats.misses(PageKind::VideoGallery), 0);
+}
+
+#[test]
+fn test_a_sharp_drop_in_success_is_reported_as_drift() {
+    let stats = ExtractionStats::new();
+    for _ in 0..DRIFT_BASELINE + DRIFT_WINDOW as u64 {
+        stats.record(PageKind::VideoPage, Some("next_data_first_line"));
+    }
+    assert!(!stats.is_drifting(PageKind::VideoPage));
+
+    for _ in 0..DRIFT_WINDOW {
+        stats.record(PageKind::VideoPage, None);
+    }
+    assert!(stats.is_drifting(PageKind::VideoPage));
+    assert!(!stats.is_drifting(PageKind::VideoGallery));
+
+    for _ in 0..DRIFT_WINDOW {
+        stats.record(PageKind::VideoPage, Some("graphql"));
+    }
+    assert!(!stats.is_drifting(PageKind::VideoPage));
+}
+
+#[test]
+fn test_early_misses_are_not_drift() {
+    let stats = ExtractionStats::new();
+    for _ in 0..DRIFT_WINDOW {
+        stats.record(PageKind::VideoGallery, None);
+    }
+
+    assert!(!stats.is_drifting(PageKind::VideoGallery));
+}
@@ -0,0 +1,150 @@
+use std::time::Duration;
+use chrono::NaiveDate;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use trailerfin_rust::request_clients::client_settings::ClientSettings;
+use trailerfin_rust::request_clients::imdb_client::extraction_stats::PageKind;
+use trailerfin_rust::request_clients::imdb_client::imdb_request_client::{ClientBuilder, ImdbRequestClient};
+use trailerfin_rust::request_clients::imdb_client::title_endpoints::parse_title_page;
+use trailerfin_rust::request_clients::imdb_client::video_gallery_endpoints::{parse_video_gallery, VideoKind};
+use trailerfin_rust::request_clients::imdb_client::video_page_endpoints::{best_rendition, parse_video_page};
+use trailerfin_rust::request_clients::rate_limited_client::RateLimitedClient;
+use trailerfin_rust::request_clients::retry_policy::RetryPolicy;
+
+const GALLERY: &str = include_str!("fixtures/imdb/videogallery_tt0133093.html");
+const VIDEO_PAGE: &str = include_str!("fixtures/imdb/video_vi1032782617.html");
+const TITLE_PAGE: &str = include_str!("fixtures/imdb/title_tt0133093.html");
+
+/// Serves `status` and `body` to every connection.
+async fn serve(status: &'static str, body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            _ = socket.read(&mut buf).await;
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            _ = socket.write_all(response.as_bytes()).await;
+            _ = socket.shutdown().await;
+        }
+    });
+
+    url
+}
+
+fn imdb_client(base_url: String) -> ImdbRequestClient {
+    let executor = RateLimitedClient::from_settings(&ClientSettings::new("TestAgent", "100/second").with_retry_policy(RetryPolicy::none())).unwrap();
+    ImdbRequestClient(ClientBuilder::default().with_base_url(base_url).with_executor(executor).build().unwrap())
+}
+
+#[test]
+fn test_gallery_items_are_parsed_in_page_order() {
+    let items = parse_video_gallery(GALLERY).items;
+
+    let ids: Vec<&str> = items.iter().map(|item| item.id.as_str()).collect();
+    assert_eq!(ids, ["vi2048898073", "vi1032782617", "vi3316697369"]);
+
+    let trailer = &items[1];
+    assert_eq!(trailer.title, "Official Trailer");
+    assert_eq!(trailer.kind, VideoKind::Trailer);
+    assert_eq!(trailer.duration, Some(Duration::from_secs(146)));
+    assert_eq!(trailer.date, NaiveDate::from_ymd_opt(1999, 3, 2));
+
+    assert_eq!(items[0].kind, VideoKind::Featurette);
+    assert_eq!(items[2].kind, VideoKind::Clip);
+    assert_eq!(items[2].duration, Some(Duration::from_secs(3725)));
+    assert_eq!(items[2].date, None);
+}
+
+#[test]
+fn test_gallery_kind_falls_back_to_the_title() {
+    let items = parse_video_gallery("<html><a href=\"/video/vi42/\">Official Teaser #2</a><a href=\"/video/vi43/\">Making of</a></html>").items;
+
+    assert_eq!(items[0].kind, VideoKind::Teaser);
+    assert_eq!(items[1].kind, VideoKind::Other);
+}
+
+#[test]
+fn test_video_page_renditions_are_parsed() {
+    let renditions = parse_video_page(VIDEO_PAGE).renditions;
+
+    assert_eq!(renditions.len(), 4);
+    assert_eq!(renditions[0].mime_type.as_deref(), Some("M3U8"));
+    assert!(!renditions[0].is_mp4());
+
+    let best = best_rendition(&renditions).unwrap();
+    assert_eq!(best.definition.as_deref(), Some("DEF_1080p"));
+    assert_eq!(best.url, "https://imdb-video.media-imdb.com/vi1032782617/1080.mp4?Expires=1760000000");
+}
+
+#[test]
+fn test_video_pages_without_playback_data_have_no_renditions() {
+    assert!(parse_video_page("<html><body>No video</body></html>").renditions.is_empty());
+    assert!(best_rendition(&[]).is_none());
+}
+
+#[test]
+fn test_title_metadata_is_parsed_from_json_ld() {
+    let title = parse_title_page("tt0133093", TITLE_PAGE).unwrap();
+
+    assert_eq!(title.id, "tt0133093");
+    assert_eq!(title.title, "The Matrix");
+    assert_eq!(title.kind, "Movie");
+    assert_eq!(title.year, Some(1999));
+    assert_eq!(title.genres, ["Action", "Sci-Fi"]);
+    assert!(title.description.unwrap().starts_with("When a beautiful stranger"));
+}
+
+#[test]
+fn test_title_metadata_unescapes_names_and_accepts_a_single_genre() {
+    let html = r#"<script type="application/ld+json">{"@type":"TVSeries","name":"Schindler&apos;s List","genre":"Drama"}</script>"#;
+    let title = parse_title_page("tt1", html).unwrap();
+
+    assert_eq!(title.title, "Schindler's List");
+    assert_eq!(title.genres, ["Drama"]);
+    assert_eq!(title.year, None);
+    assert!(parse_title_page("tt1", "<html></html>").is_none());
+}
+
+#[tokio::test]
+async fn test_services_fetch_and_parse_pages() {
+    let client = imdb_client(serve("200 OK", GALLERY).await);
+    let items = client.video_gallery().get("tt0133093").await.unwrap().unwrap();
+    assert_eq!(items.len(), 3);
+
+    let client = imdb_client(serve("200 OK", VIDEO_PAGE).await);
+    assert_eq!(client.video_page().get("vi1032782617").await.unwrap().len(), 4);
+
+    let client = imdb_client(serve("200 OK", TITLE_PAGE).await);
+    assert_eq!(client.title().get("tt0133093").await.unwrap().unwrap().year, Some(1999));
+}
+
+#[tokio::test]
+async fn test_unknown_titles_are_none() {
+    let client = imdb_client(serve("404 Not Found", "<html>Not found</html>").await);
+
+    assert!(client.video_gallery().get("tt0").await.unwrap().is_none());
+    assert!(client.title().get("tt0").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_empty_galleries_do_not_count_towards_drift() {
+    let client = imdb_client(serve("200 OK", "<html><body><h2>No videos yet</h2></body></html>").await);
+
+    assert_eq!(client.video_gallery().get("tt1").await.unwrap(), Some(vec![]));
+    assert_eq!(client.extraction_stats().misses(PageKind::VideoGallery), 0);
+    assert!(!client.extraction_stats().is_drifting(PageKind::VideoGallery));
+}
+
+#[tokio::test]
+async fn test_galleries_with_unparsed_videos_are_misses() {
+    let client = imdb_client(serve("200 OK", "<html><body><div data-video=\"vi1032782617\"></div></body></html>").await);
+
+    assert_eq!(client.video_gallery().get("tt1").await.unwrap(), Some(vec![]));
+    assert_eq!(client.extraction_stats().misses(PageKind::VideoGallery), 1);
+}
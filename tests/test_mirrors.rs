@@ -0,0 +1,118 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tempfile::tempdir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use trailerfin_rust::configuration::configuration_provider::{AppConfig, ConfigurationProvider, LibraryProfile};
+use trailerfin_rust::request_clients::create_imdb_request_client;
+use trailerfin_rust::request_clients::mirrors::BaseUrls;
+use trailerfin_rust::request_clients::request_errors::error::Error;
+
+/// Answers every request with `status` and counts the requests.
+async fn serve(status: &'static str) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            counter.fetch_add(1, Ordering::SeqCst);
+            let mut buf = [0u8; 4096];
+            _ = socket.read(&mut buf).await;
+            let response = format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+            _ = socket.write_all(response.as_bytes()).await;
+            _ = socket.shutdown().await;
+        }
+    });
+
+    (url, hits)
+}
+
+async fn unreachable_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    url
+}
+
+fn config(cache: &std::path::Path, base_url: String, mirrors: Vec<String>) -> AppConfig {
+    let mut config = AppConfig::new(cache.to_string_lossy());
+    config.http_max_retries = 0;
+    config.imdb_base_url = base_url.into();
+    config.imdb_mirrors = mirrors.into_iter().map(Into::into).collect();
+    config
+}
+
+#[tokio::test]
+async fn test_unreachable_base_url_fails_over_to_mirrors_in_order() {
+    let cache = tempdir().unwrap();
+    let (first_mirror, first_hits) = serve("200 OK").await;
+    let (second_mirror, second_hits) = serve("200 OK").await;
+    let client = create_imdb_request_client(&config(
+        cache.path(),
+        unreachable_url().await,
+        vec![first_mirror, second_mirror],
+    )).unwrap();
+
+    let res = client.get_raw("/title/tt1/").await.unwrap();
+    assert_eq!(res.status(), 200);
+    client.get_raw("/title/tt2/").await.unwrap();
+
+    assert_eq!(first_hits.load(Ordering::SeqCst), 2, "the working mirror is remembered");
+    assert_eq!(second_hits.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_other_errors_do_not_fail_over() {
+    let cache = tempdir().unwrap();
+    let (primary, primary_hits) = serve("404 Not Found").await;
+    let (mirror, mirror_hits) = serve("200 OK").await;
+    let client = create_imdb_request_client(&config(cache.path(), primary, vec![mirror])).unwrap();
+
+    let res = client.get_raw("/title/tt1/").await.unwrap();
+
+    assert_eq!(res.status(), 404);
+    assert_eq!(primary_hits.load(Ordering::SeqCst), 1);
+    assert_eq!(mirror_hits.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_connection_error_is_returned_when_every_mirror_is_down() {
+    let base_urls = BaseUrls::new(unreachable_url().await, [unreachable_url().await]);
+    let client = reqwest::Client::new();
+
+    let result = base_urls
+        .request("/", |url| {
+            let client = client.clone();
+            async move { client.get(url).send().await.map_err(Error::from) }
+        })
+        .await;
+
+    assert!(result.unwrap_err().is_connection_error());
+}
+
+#[test]
+fn test_trailing_slashes_are_trimmed() {
+    let base_urls = BaseUrls::new("http://imdb-cache:8080/", ["http://mirror/"]);
+    assert_eq!(base_urls.all(), ["http://imdb-cache:8080", "http://mirror"]);
+}
+
+#[test]
+fn test_base_urls_are_validated() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    std::fs::create_dir_all(media.path().join("Movies")).unwrap();
+    let validate = |base_url: &str, mirrors: &[&str]| {
+        let config = config(cache.path(), base_url.to_string(), mirrors.iter().map(|m| m.to_string()).collect())
+            .with_library(LibraryProfile::new("movies", media.path().to_string_lossy()).with_movie_folders(["Movies"]));
+        ConfigurationProvider::validate_config(config)
+    };
+
+    assert!(validate("http://imdb-cache:8080", &["https://imdb-mirror.example"]).is_ok());
+    assert!(validate("imdb-cache:8080", &[]).is_err());
+    assert!(validate("ftp://imdb-cache", &[]).is_err());
+    assert!(validate("https://imdb-cache/?token=1", &[]).is_err());
+    assert!(validate("https://www.imdb.com", &["not a url"]).is_err());
+}
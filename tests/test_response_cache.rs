@@ -0,0 +1,186 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::{tempdir, TempDir};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use trailerfin_rust::configuration::configuration_provider::AppConfig;
+use trailerfin_rust::request_clients::client_settings::{ClientSettings, Upstream};
+use trailerfin_rust::request_clients::imdb_client::imdb_request_client::{ClientBuilder, ImdbRequestClient};
+use trailerfin_rust::request_clients::rate_limited_client::{Executor, RateLimitedClient};
+use trailerfin_rust::request_clients::request_errors::error::Error;
+use trailerfin_rust::request_clients::response_cache::{CacheRule, CachedResponse, ResponseCache, HTTP_CACHE_DIR};
+use trailerfin_rust::request_clients::retry_policy::RetryPolicy;
+
+/// Answers every request with `respond(request head)` and records the lowercased request heads.
+async fn serve(respond: fn(&str) -> String) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(vec![]));
+    let recorded = requests.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let read = socket.read(&mut buf).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buf[..read]).to_lowercase();
+            let response = respond(&request);
+            recorded.lock().unwrap().push(request);
+            _ = socket.write_all(response.as_bytes()).await;
+            _ = socket.shutdown().await;
+        }
+    });
+
+    (url, requests)
+}
+
+fn response(status: &str, headers: &str, body: &str) -> String {
+    format!("HTTP/1.1 {status}\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len())
+}
+
+/// Serves a gallery with an ETag and answers matching `If-None-Match` requests with 304.
+fn etag_server(request: &str) -> String {
+    if request.contains("if-none-match: \"v1\"") {
+        response("304 Not Modified", "ETag: \"v1\"\r\n", "")
+    } else {
+        response("200 OK", "ETag: \"v1\"\r\nContent-Type: text/html\r\n", "<a href=\"/video/vi1\">Trailer</a>")
+    }
+}
+
+fn last_modified_server(request: &str) -> String {
+    if request.contains("if-modified-since: wed, 21 oct 2015 07:28:00 gmt") {
+        response("304 Not Modified", "", "")
+    } else {
+        response("200 OK", "Last-Modified: Wed, 21 Oct 2015 07:28:00 GMT\r\n", "{\"imdb_id\":\"tt1\"}")
+    }
+}
+
+fn client(cache: &TempDir, ttl: Duration) -> RateLimitedClient {
+    let settings = ClientSettings::new("TestAgent", "100/second")
+        .with_retry_policy(RetryPolicy::none())
+        .with_response_cache(cache.path(), [CacheRule::new("gallery", "/videogallery", ttl)]);
+    RateLimitedClient::from_settings(&settings).unwrap()
+}
+
+#[tokio::test]
+async fn test_fresh_responses_are_served_from_the_cache() {
+    let cache = tempdir().unwrap();
+    let (url, requests) = serve(etag_server).await;
+    let client = client(&cache, Duration::from_secs(3600));
+
+    let first = client.execute_raw(&format!("{url}/title/tt1/videogallery/")).await.unwrap();
+    assert_eq!(first.text().await.unwrap(), "<a href=\"/video/vi1\">Trailer</a>");
+
+    let second = client.execute_raw(&format!("{url}/title/tt1/videogallery/")).await.unwrap();
+    assert_eq!(second.status(), 200);
+    assert_eq!(second.headers()["content-type"], "text/html");
+    assert_eq!(second.text().await.unwrap(), "<a href=\"/video/vi1\">Trailer</a>");
+
+    assert_eq!(requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_stale_responses_are_revalidated_with_their_etag() {
+    let cache = tempdir().unwrap();
+    let (url, requests) = serve(etag_server).await;
+    let client = client(&cache, Duration::ZERO);
+
+    client.execute_raw(&format!("{url}/title/tt1/videogallery/")).await.unwrap();
+    let revalidated = client.execute_raw(&format!("{url}/title/tt1/videogallery/")).await.unwrap();
+
+    assert_eq!(revalidated.status(), 200);
+    assert_eq!(revalidated.text().await.unwrap(), "<a href=\"/video/vi1\">Trailer</a>");
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(!requests[0].contains("if-none-match"));
+    assert!(requests[1].contains("if-none-match: \"v1\""));
+}
+
+#[tokio::test]
+async fn test_json_responses_are_revalidated_with_their_last_modified_date() {
+    let cache = tempdir().unwrap();
+    let (url, requests) = serve(last_modified_server).await;
+    let settings = ClientSettings::new("TestAgent", "100/second")
+        .with_retry_policy(RetryPolicy::none())
+        .with_response_cache(cache.path(), [CacheRule::new("external IDs", "/external_ids", Duration::ZERO)]);
+    let client = RateLimitedClient::from_settings(&settings).unwrap();
+
+    for _ in 0..2 {
+        let ids: serde_json::Value = client.execute(&format!("{url}/movie/1/external_ids"), [("api_key", "secret")]).await.unwrap();
+        assert_eq!(ids["imdb_id"], "tt1");
+    }
+
+    assert!(requests.lock().unwrap()[1].contains("if-modified-since: wed, 21 oct 2015 07:28:00 gmt"));
+    let file_names: Vec<String> = std::fs::read_dir(cache.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(file_names.len(), 1);
+    assert!(!file_names[0].contains("secret"));
+}
+
+#[tokio::test]
+async fn test_endpoints_without_a_rule_are_not_cached() {
+    let cache = tempdir().unwrap();
+    let (url, requests) = serve(etag_server).await;
+    let client = client(&cache, Duration::from_secs(3600));
+
+    client.execute_raw(&format!("{url}/video/vi1/")).await.unwrap();
+    client.execute_raw(&format!("{url}/video/vi1/")).await.unwrap();
+
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    assert!(!requests.lock().unwrap()[1].contains("if-none-match"));
+}
+
+#[tokio::test]
+async fn test_block_pages_are_evicted_from_the_cache() {
+    let cache = tempdir().unwrap();
+    let (url, requests) = serve(|_| response("200 OK", "ETag: \"wall\"\r\n", "<div id=\"captcha-container\"></div>")).await;
+    let inner = ClientBuilder::default()
+        .with_base_url(url)
+        .with_executor(client(&cache, Duration::from_secs(3600)))
+        .build()
+        .unwrap();
+    let imdb = ImdbRequestClient(inner);
+
+    for _ in 0..2 {
+        assert!(matches!(imdb.get_raw("/title/tt1/videogallery/").await, Err(Error::Blocked { .. })));
+    }
+
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    assert_eq!(std::fs::read_dir(cache.path()).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_concurrent_writes_of_one_url_each_leave_a_whole_entry() {
+    let dir = tempdir().unwrap();
+    let cache = Arc::new(ResponseCache::new(dir.path(), vec![]).unwrap());
+    let url = url::Url::parse("https://www.imdb.com/title/tt1/videogallery/").unwrap();
+
+    let writes = (0..8).map(|i| {
+        let (cache, url) = (cache.clone(), url.clone());
+        let entry = CachedResponse::new(reqwest::StatusCode::OK, &Default::default(), format!("gallery {i}"));
+        tokio::spawn(async move { cache.put(&url, &entry).await })
+    });
+    for write in futures::future::join_all(writes).await {
+        write.unwrap().unwrap();
+    }
+
+    assert!(cache.get(&url).await.unwrap().body.starts_with("gallery "));
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn test_cache_rules_are_configured_per_upstream() {
+    let cache = tempdir().unwrap();
+    let mut config = AppConfig::new(cache.path().to_string_lossy());
+    config.imdb_gallery_cache_ttl_secs = 60;
+
+    let imdb = ClientSettings::for_upstream(&config, Upstream::Imdb);
+    assert_eq!(imdb.response_cache, Some(cache.path().join(HTTP_CACHE_DIR)));
+    assert_eq!(imdb.cache_rules, vec![CacheRule::new("IMDb video gallery", "/videogallery", Duration::from_secs(60))]);
+    assert_eq!(ClientSettings::for_upstream(&config, Upstream::Tmdb).response_cache, None);
+
+    config.http_cache = false;
+    assert_eq!(ClientSettings::for_upstream(&config, Upstream::Imdb).response_cache, None);
+}
@@ -0,0 +1,166 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use reqwest::StatusCode;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use trailerfin_rust::request_clients::client_settings::ClientSettings;
+use trailerfin_rust::request_clients::rate_limited_client::{Executor, RateLimitedClient};
+use trailerfin_rust::request_clients::retry_policy::{parse_retry_after, RetryPolicy};
+
+/// Serves the scripted responses in order, one per connection, and repeats the last one.
+async fn serve(responses: Vec<&'static str>) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let hit = counter.fetch_add(1, Ordering::SeqCst);
+            let response = responses[hit.min(responses.len() - 1)];
+
+            let mut buf = [0u8; 4096];
+            _ = socket.read(&mut buf).await;
+            _ = socket.write_all(response.as_bytes()).await;
+            _ = socket.shutdown().await;
+        }
+    });
+
+    (url, hits)
+}
+
+fn response(status: &str, extra_headers: &str, body: &str) -> &'static str {
+    Box::leak(
+        format!(
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n{extra_headers}\r\n{body}",
+            body.len()
+        )
+        .into_boxed_str(),
+    )
+}
+
+fn client(retry_policy: RetryPolicy) -> RateLimitedClient {
+    let settings = ClientSettings::new("TestAgent", "100/second").with_retry_policy(retry_policy);
+    RateLimitedClient::from_settings(&settings).unwrap()
+}
+
+fn fast_retries(max_retries: u32) -> RetryPolicy {
+    RetryPolicy::default()
+        .with_max_retries(max_retries)
+        .with_backoff(Duration::from_millis(1), Duration::from_millis(10))
+}
+
+#[tokio::test]
+async fn test_retries_transient_statuses_until_success() {
+    let unavailable = response("503 Service Unavailable", "", "");
+    let (url, hits) = serve(vec![unavailable, unavailable, response("200 OK", "", "{\"ok\":true}")]).await;
+
+    let value: serde_json::Value = client(fast_retries(3)).execute(&url, ()).await.unwrap();
+
+    assert_eq!(value["ok"], true);
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_retries() {
+    let (url, hits) = serve(vec![response("502 Bad Gateway", "", "")]).await;
+
+    let res = client(fast_retries(2)).execute_raw(&url).await.unwrap();
+
+    assert_eq!(res.status(), StatusCode::BAD_GATEWAY);
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_does_not_retry_other_statuses() {
+    let (url, hits) = serve(vec![response("404 Not Found", "", "")]).await;
+
+    let res = client(fast_retries(3)).execute_raw(&url).await.unwrap();
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_honors_retry_after() {
+    let throttled = response("429 Too Many Requests", "Retry-After: 1\r\n", "");
+    let (url, hits) = serve(vec![throttled, response("200 OK", "", "")]).await;
+
+    let policy = fast_retries(1).with_backoff(Duration::from_millis(1), Duration::from_secs(5));
+    let started = Instant::now();
+    let res = client(policy).execute_raw(&url).await.unwrap();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn test_retry_after_longer_than_max_backoff_is_not_waited_for() {
+    let (url, hits) = serve(vec![response("429 Too Many Requests", "Retry-After: 3600\r\n", "")]).await;
+
+    let res = client(fast_retries(3)).execute_raw(&url).await.unwrap();
+
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_connection_errors_are_retried() {
+    // Grab a free port and close it again, so connecting is refused.
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let started = Instant::now();
+    let policy = fast_retries(2).with_backoff(Duration::from_millis(50), Duration::from_millis(50));
+    let res = client(policy).execute_raw(&url).await;
+
+    assert!(res.is_err());
+    // Two retries, each waiting at least half of the 50ms backoff.
+    assert!(started.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn test_backoff_grows_exponentially_with_jitter_and_is_capped() {
+    let policy = RetryPolicy::default().with_backoff(Duration::from_millis(100), Duration::from_millis(1000));
+
+    for _ in 0..50 {
+        let first = policy.backoff(1);
+        assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+
+        let third = policy.backoff(3);
+        assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+
+        let capped = policy.backoff(20);
+        assert!(capped >= Duration::from_millis(500) && capped <= Duration::from_millis(1000));
+    }
+}
+
+#[test]
+fn test_parse_retry_after() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(parse_retry_after(&headers), None);
+
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+    assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(120)));
+
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+    assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+    assert_eq!(parse_retry_after(&headers), None);
+}
+
+#[tokio::test]
+async fn test_throttling_responses_slow_the_client_down() {
+    let (url, _) = serve(vec![response("429 Too Many Requests", "", ""), response("200 OK", "", "")]).await;
+    let client = client(fast_retries(1));
+
+    client.execute_raw(&url).await.unwrap();
+
+    assert_eq!(client.limiter().current_rate(), client.limiter().ceiling_rate() / 2.0);
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::tempdir;
+use trailerfin_rust::app::App;
+use trailerfin_rust::configuration::configuration_provider::{AppConfig, ConfigurationProvider, DataSource, LibraryProfile};
+use trailerfin_rust::scrapers::outcomes::TitleOutcome;
+use common::RecordingScraper;
+
+fn make_dirs(root: &Path, dirs: &[&str]) {
+    for dir in dirs {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+}
+
+async fn scan(cache: &Path, library: LibraryProfile) -> (Arc<RecordingScraper>, App) {
+    let config = AppConfig::new(cache.to_string_lossy()).with_user_agent("TestAgent").with_library(library);
+    let scraper = Arc::new(RecordingScraper::default());
+    let app = App::builder(ConfigurationProvider::validate_config(config).unwrap())
+        .with_scraper(DataSource::Imdb, scraper.clone())
+        .build()
+        .unwrap();
+    app.scan().await;
+    (scraper, app)
+}
+
+#[tokio::test]
+async fn test_default_depth_only_scans_direct_subfolders() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let root = media.path().canonicalize().unwrap();
+    make_dirs(&root, &["Movies/Heat", "Movies/M/The Matrix {imdb-tt0133093}"]);
+
+    let (scraper, _) = scan(cache.path(), LibraryProfile::new("movies", root.to_string_lossy()).with_movie_folders(["Movies"])).await;
+
+    assert_eq!(scraper.titles(&root), vec!["Movies/Heat", "Movies/M"]);
+}
+
+#[tokio::test]
+async fn test_nested_titles_are_found_down_to_the_scan_depth() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let root = media.path().canonicalize().unwrap();
+    make_dirs(&root, &[
+        "Movies/M/The Matrix {imdb-tt0133093}/Extras/Making Of {imdb-tt0295432}",
+        "Movies/M/The Matrix {imdb-tt0133093}/backdrops",
+        "Movies/Collections/Alien/Alien {imdb-tt0078748}",
+        "Movies/Collections/Alien/Aliens",
+        "Movies/Heat {imdb-tt0113277}",
+        "Movies/.cache/Hidden {imdb-tt0000001}",
+        "Shows/B/Breaking Bad {imdb-tt0903747}/Season 01",
+        "Shows/B/Breaking Bad {imdb-tt0903747}/Specials",
+    ]);
+
+    let library = LibraryProfile::new("media", root.to_string_lossy())
+        .with_movie_folders(["Movies"])
+        .with_tv_folders(["Shows"])
+        .with_scan_depth(3);
+    let (scraper, _) = scan(cache.path(), library).await;
+
+    assert_eq!(
+        scraper.titles(&root),
+        vec![
+            "Movies/Collections/Alien/Alien {imdb-tt0078748}",
+            "Movies/Collections/Alien/Aliens",
+            "Movies/Heat {imdb-tt0113277}",
+            "Movies/M/The Matrix {imdb-tt0133093}",
+            "Shows/B/Breaking Bad {imdb-tt0903747}",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_nested_titles_can_be_refreshed_by_path() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let root = media.path().canonicalize().unwrap();
+    make_dirs(&root, &["Movies/M/The Matrix {imdb-tt0133093}"]);
+
+    let library = LibraryProfile::new("movies", root.to_string_lossy()).with_movie_folders(["Movies"]).with_scan_depth(2);
+    let (scraper, app) = scan(cache.path(), library).await;
+    let title = root.join("Movies/M/The Matrix {imdb-tt0133093}");
+
+    assert_eq!(app.refresh_path(&title).await.unwrap(), TitleOutcome::Refreshed { id: "tt1".to_string() });
+    assert_eq!(scraper.paths().last(), Some(&title));
+    assert!(app.refresh_path(&title.join("backdrops")).await.is_err());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_symlink_loops_and_duplicates_are_skipped() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    let root = media.path().canonicalize().unwrap();
+    make_dirs(&root, &["Movies/A/Heat {imdb-tt0113277}"]);
+    std::os::unix::fs::symlink(root.join("Movies/A"), root.join("Movies/A/Again")).unwrap();
+    std::os::unix::fs::symlink(root.join("Movies/A/Heat {imdb-tt0113277}"), root.join("Movies/Heat {imdb-tt0113277}")).unwrap();
+
+    let library = LibraryProfile::new("movies", root.to_string_lossy()).with_movie_folders(["Movies"]).with_scan_depth(5);
+    let (scraper, _) = scan(cache.path(), library).await;
+
+    assert_eq!(scraper.titles(&root), vec!["Movies/A/Heat {imdb-tt0113277}"]);
+}
+
+#[test]
+fn test_scan_depth_must_be_positive() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    fs::create_dir_all(media.path().join("Movies")).unwrap();
+
+    let config = AppConfig::new(cache.path().to_string_lossy())
+        .with_library(LibraryProfile::new("movies", media.path().to_string_lossy()).with_movie_folders(["Movies"]).with_scan_depth(0));
+
+    let err = ConfigurationProvider::validate_config(config).unwrap_err();
+    assert!(err.to_string().contains("TRAILERFIN_SCAN_DEPTH"));
+}
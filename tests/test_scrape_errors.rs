@@ -0,0 +1,68 @@
+use std::error::Error as _;
+use std::time::Duration;
+use trailerfin_rust::request_clients::client_settings::Upstream;
+use trailerfin_rust::request_clients::request_errors::error::Error;
+use trailerfin_rust::scrapers::outcomes::TitleOutcome;
+use trailerfin_rust::scrapers::scrape_error::{ScrapeError, ScrapeErrorKind};
+
+fn kind_of(err: Error, upstream: Upstream) -> ScrapeErrorKind {
+    ScrapeErrorKind::of_request_error(&err, upstream)
+}
+
+#[test]
+fn test_request_errors_are_classified() {
+    assert_eq!(kind_of(Error::Blocked { status: 200, reason: "captcha".to_string() }, Upstream::Imdb), ScrapeErrorKind::Blocked);
+    assert_eq!(
+        kind_of(Error::CircuitOpen { service: "IMDb".to_string(), retry_in: Duration::from_secs(1) }, Upstream::Imdb),
+        ScrapeErrorKind::Throttled
+    );
+    assert_eq!(kind_of(Error::UnexpectedStatus(429), Upstream::Imdb), ScrapeErrorKind::Throttled);
+    assert_eq!(kind_of(Error::UnexpectedStatus(404), Upstream::Imdb), ScrapeErrorKind::NotFound);
+    assert_eq!(kind_of(Error::UnexpectedStatus(503), Upstream::Imdb), ScrapeErrorKind::Upstream);
+    assert_eq!(kind_of(Error::UnexpectedStatus(401), Upstream::Imdb), ScrapeErrorKind::Upstream);
+    assert_eq!(kind_of(Error::UnexpectedStatus(401), Upstream::Tmdb), ScrapeErrorKind::TmdbAuthInvalid);
+
+    let invalid_json = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+    assert_eq!(kind_of(Error::Response { source: Box::new(invalid_json) }, Upstream::Tmdb), ScrapeErrorKind::ParseChanged);
+}
+
+#[tokio::test]
+async fn test_connection_failures_are_network_errors() {
+    let err = reqwest::get("http://127.0.0.1:1/").await.unwrap_err();
+
+    assert_eq!(kind_of(Error::Reqwest(err), Upstream::Imdb), ScrapeErrorKind::Network);
+}
+
+#[test]
+fn test_codes_are_stable_and_unique() {
+    let codes: Vec<&str> = ScrapeErrorKind::ALL.iter().map(|kind| kind.code()).collect();
+
+    assert_eq!(
+        codes,
+        ["network", "throttled", "blocked", "not_found", "no_trailer", "parse_changed", "tmdb_auth_invalid", "fs_write", "upstream", "internal"]
+    );
+}
+
+#[test]
+fn test_causes_are_chained() {
+    let invalid_json = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+    let err = ScrapeError::request(Upstream::Tmdb, "failed to fetch the external IDs of 603", Error::SerdeJson(invalid_json));
+
+    assert_eq!(err.code(), "parse_changed");
+    assert_eq!(err.to_string(), "failed to fetch the external IDs of 603");
+    let cause = err.source().unwrap();
+    assert_eq!(cause.to_string(), "invalid JSON");
+    assert!(cause.source().unwrap().is::<serde_json::Error>());
+    assert!(format!("{:#}", err).starts_with("failed to fetch the external IDs of 603: invalid JSON: EOF"));
+}
+
+#[test]
+fn test_outcomes_carry_the_classification() {
+    let err = ScrapeError::new(ScrapeErrorKind::ParseChanged, "no playback URLs found on the video page of vi1");
+    let outcome = TitleOutcome::lookup_failed(Some("tt1".to_string()), &err);
+
+    assert_eq!(outcome.error_kind(), Some(ScrapeErrorKind::ParseChanged));
+    assert_eq!(outcome.to_string(), "lookup failed for tt1 [parse_changed]: no playback URLs found on the video page of vi1");
+    assert_eq!(TitleOutcome::NoTrailer { id: "tt1".to_string() }.error_kind(), Some(ScrapeErrorKind::NoTrailer));
+    assert_eq!(TitleOutcome::Refreshed { id: "tt1".to_string() }.error_kind(), None);
+}
@@ -1,14 +1,15 @@
-use std::env;
-use tempfile::tempdir;
+mod common;
+
+use common::setup_empty_dir;
 
 #[tokio::test]
 async fn test_scheduler_triggers_scan() {
-    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::time::Duration;
     use std::{env, sync::Arc};
     use trailerfin_rust::configuration::configuration_provider::ConfigurationProvider;
     use trailerfin_rust::schedulers::{get_scraping_scheduler, initialize_schedulers};
-    use trailerfin_rust::schedulers::types::ScanFn;
+    use trailerfin_rust::schedulers::types::{ScanFn, ScanLocks};
 
     initialize_schedulers();
 
@@ -25,17 +26,17 @@ async fn test_scheduler_triggers_scan() {
 
     let config = ConfigurationProvider::load_config().expect("Expected config to load");
 
-    let mock_scan: ScanFn = Arc::new(|_cfg| {
+    let mock_scan: ScanFn = Arc::new(|_cfg, _library| {
         Box::pin(async {
             CALLED.fetch_add(1, Ordering::SeqCst);
             Ok(())
         })
     });
 
-    let is_running = Arc::new(AtomicBool::new(false));
+    let scan_locks = Arc::new(ScanLocks::for_config(&config));
 
     let sched = get_scraping_scheduler()
-        .setup_scheduler_with_lock(config, Some(mock_scan), is_running)
+        .setup_scheduler_with_lock(config, Some(mock_scan), scan_locks)
         .await
         .unwrap();
 
@@ -48,26 +49,3 @@ async fn test_scheduler_triggers_scan() {
     assert_eq!(&config.scan_path, temp.path().join("scan-me").to_str().unwrap());
     assert!(CALLED.load(Ordering::SeqCst) >= 1);
 }
-
-fn setup_empty_dir() -> tempfile::TempDir {
-    let temp = tempdir().unwrap();
-
-    let scan_path = temp.path().join("scan-me");
-    let cache_path = temp.path().join("cache-me");
-
-    for subdir in ["Tv Shows", "Kids TV", "Movies", "Kids"] {
-        std::fs::create_dir_all(scan_path.join(subdir)).unwrap();
-    }
-
-    std::fs::create_dir_all(&cache_path).unwrap();
-    std::fs::create_dir_all(&scan_path).unwrap();
-
-    unsafe {
-        env::set_var("TRAILERFIN_SCAN_PATH", scan_path);
-        env::set_var("TRAILERFIN_CACHE_PATH", cache_path);
-        env::set_var("TRAILERFIN_TV_FOLDERS", "Tv Shows, Kids TV");
-        env::set_var("TRAILERFIN_MOVIE_FOLDERS", "Movies, Kids");
-    }
-
-    temp
-}
\ No newline at end of file
@@ -1,10 +1,16 @@
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use tempfile::tempdir;
+use trailerfin_rust::configuration::configuration_provider::{AppConfig, LibraryProfile};
+use trailerfin_rust::request_clients::create_imdb_request_client;
 use trailerfin_rust::scrapers::imdb_trailers::ImdbTrailerScraper;
+use trailerfin_rust::scrapers::media_directories::FolderType;
+use trailerfin_rust::scrapers::outcomes::TitleOutcome;
+use trailerfin_rust::scrapers::traits::TrailerScraper;
 
 #[tokio::test]
 async fn test_expired_strm_file_with_expired_timestamp() {
@@ -13,7 +19,7 @@ async fn test_expired_strm_file_with_expired_timestamp() {
 
     let expired_ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64 - 100;
     let url = format!("https://video.test/video.mp4?Expires={}", expired_ts);
-    let scraper = ImdbTrailerScraper {};
+    let scraper = imdb_scraper();
     let mut file = File::create(&file_path).unwrap();
     writeln!(file, "{}", url).unwrap();
 
@@ -27,7 +33,7 @@ async fn test_valid_strm_file_with_future_expiry() {
 
     let future_ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64 + 99999;
     let url = format!("https://video.test/video.mp4?Expires={}", future_ts);
-    let scraper = ImdbTrailerScraper {};
+    let scraper = imdb_scraper();
     let mut file = File::create(&file_path).unwrap();
     writeln!(file, "{}", url).unwrap();
 
@@ -38,7 +44,7 @@ async fn test_valid_strm_file_with_future_expiry() {
 async fn test_strm_file_missing_expiry() {
     let dir = tempdir().unwrap();
     let file_path = dir.path().join("no_expiry.strm");
-    let scraper = ImdbTrailerScraper {};
+    let scraper = imdb_scraper();
     let mut file = File::create(&file_path).unwrap();
     writeln!(file, "https://video.test/video.mp4").unwrap();
 
@@ -48,6 +54,55 @@ async fn test_strm_file_missing_expiry() {
 #[tokio::test]
 async fn test_strm_file_missing_entirely() {
     let bogus_path = PathBuf::from("totally_missing.strm");
-    let scraper = ImdbTrailerScraper {};
+    let scraper = imdb_scraper();
     assert!(scraper.is_strm_expired(&bogus_path).unwrap());
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_unexpired_trailer_is_reported_still_valid() {
+    let dir = tempdir().unwrap();
+    let title = dir.path().join("The Matrix (1999) {imdb-tt0133093}");
+    let library = Arc::new(library(dir.path()));
+    std::fs::create_dir_all(title.join(&library.trailer_folder)).unwrap();
+
+    let future_ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64 + 99999;
+    let mut file = File::create(title.join(&library.trailer_folder).join(&library.video_filename)).unwrap();
+    writeln!(file, "https://video.test/video.mp4?Expires={}", future_ts).unwrap();
+
+    let outcome = imdb_scraper().process_path(title, library, FolderType::Movie).await;
+    assert_eq!(outcome, TitleOutcome::StillValid { id: "tt0133093".to_string() });
+}
+
+#[tokio::test]
+async fn test_folder_without_id_is_reported_no_id() {
+    let dir = tempdir().unwrap();
+    let title = dir.path().join("The Matrix (1999)");
+    std::fs::create_dir_all(&title).unwrap();
+
+    let outcome = imdb_scraper().process_path(title, Arc::new(library(dir.path())), FolderType::Movie).await;
+    assert!(matches!(outcome, TitleOutcome::NoId { .. }));
+    assert!(!outcome.is_failure());
+}
+
+#[tokio::test]
+async fn test_ids_in_parent_folders_are_not_read_as_the_title_id() {
+    let dir = tempdir().unwrap();
+    let title = dir.path().join("Collection {imdb-tt0234215}").join("The Matrix (1999)");
+    std::fs::create_dir_all(&title).unwrap();
+
+    let outcome = imdb_scraper().process_path(title, Arc::new(library(dir.path())), FolderType::Movie).await;
+    assert!(matches!(outcome, TitleOutcome::NoId { .. }));
+}
+
+fn library(root: &std::path::Path) -> LibraryProfile {
+    LibraryProfile::new("test", root.to_string_lossy()).with_movie_folders(["."])
+}
+
+fn imdb_scraper() -> ImdbTrailerScraper {
+    let config = AppConfig {
+        user_agent: "TestAgent".to_string(),
+        imdb_rate_limit: "30/minute".to_string(),
+        ..Default::default()
+    };
+    ImdbTrailerScraper::new(create_imdb_request_client(&config).unwrap())
+}
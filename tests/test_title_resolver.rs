@@ -0,0 +1,217 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use redb::TableDefinition;
+use tempfile::tempdir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use trailerfin_rust::app::App;
+use trailerfin_rust::caching::{create_database, create_title_match_cache, create_tmdb_to_imdb_cache};
+use trailerfin_rust::configuration::configuration_provider::{AppConfig, ConfigurationProvider, LibraryProfile};
+use trailerfin_rust::request_clients::client_settings::ClientSettings;
+use trailerfin_rust::request_clients::rate_limited_client::RateLimitedClient;
+use trailerfin_rust::request_clients::retry_policy::RetryPolicy;
+use trailerfin_rust::request_clients::tmdb_client::tmdb_request_client::{ClientBuilder, TmdbRequestClient};
+use trailerfin_rust::scrapers::imdb_id_lookup::lookup_imdb_id;
+use trailerfin_rust::scrapers::media_directories::FolderType;
+use trailerfin_rust::scrapers::outcomes::TitleOutcome;
+use trailerfin_rust::scrapers::title_resolver::{normalize_title, FolderTitle, Resolution, TitleResolver};
+
+const MATRIX_SEARCH: &str = r#"{"page":1,"results":[
+    {"id":603,"title":"The Matrix","original_title":"The Matrix","release_date":"1999-03-31"},
+    {"id":14543,"title":"The Matrix Revisited","original_title":"The Matrix Revisited","release_date":"1999-11-19"},
+    {"id":1000,"title":"The Matrix","original_title":"The Matrix","release_date":"2012-01-01"}
+],"total_pages":1,"total_results":3}"#;
+const MATRIX_EXTERNAL_IDS: &str = r#"{"id":603,"imdb_id":"tt0133093"}"#;
+const HAMLET_SEARCH: &str = r#"{"page":1,"results":[
+    {"id":10264,"title":"Hamlet","original_title":"Hamlet","release_date":"1990-12-19"},
+    {"id":48286,"title":"Hamlet","original_title":"Hamlet","release_date":"1990-09-01"}
+],"total_pages":1,"total_results":2}"#;
+const SHOW_SEARCH: &str = r#"{"page":1,"results":[
+    {"id":1396,"name":"Breaking Bad","original_name":"Breaking Bad","first_air_date":"2008-01-20"}
+],"total_pages":1,"total_results":1}"#;
+const SHOW_EXTERNAL_IDS: &str = r#"{"id":1396,"imdb_id":"tt0903747","tvdb_id":81189}"#;
+
+/// Answers each request with the body of the first route whose needle is part of the request
+/// line, or an empty search result, and keeps the request lines.
+async fn serve(routes: Vec<(&'static str, &'static str)>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(vec![]));
+    let received = requests.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let read = socket.read(&mut buf).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buf[..read]);
+            let request_line = request.lines().next().unwrap_or_default().to_string();
+            let body = routes
+                .iter()
+                .find(|(needle, _)| request_line.contains(needle))
+                .map(|(_, body)| *body)
+                .unwrap_or(r#"{"page":1,"results":[],"total_pages":1,"total_results":0}"#);
+            received.lock().unwrap().push(request_line);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            _ = socket.write_all(response.as_bytes()).await;
+            _ = socket.shutdown().await;
+        }
+    });
+
+    (url, requests)
+}
+
+fn tmdb_client(base_url: String) -> Arc<TmdbRequestClient> {
+    let executor = RateLimitedClient::from_settings(&ClientSettings::new("TestAgent", "100/second").with_retry_policy(RetryPolicy::none())).unwrap();
+    Arc::new(TmdbRequestClient(
+        ClientBuilder::default()
+            .with_base_url(base_url)
+            .with_api_key("key".to_string())
+            .with_executor(executor)
+            .build()
+            .unwrap(),
+    ))
+}
+
+fn resolver(cache_dir: &Path, base_url: String) -> TitleResolver {
+    let config = AppConfig::new(cache_dir.to_string_lossy());
+    let db = create_database(&config).unwrap();
+    TitleResolver::new(tmdb_client(base_url), create_tmdb_to_imdb_cache(db.clone()).unwrap(), create_title_match_cache(db).unwrap())
+}
+
+fn request_count(requests: &Arc<Mutex<Vec<String>>>) -> usize {
+    requests.lock().unwrap().len()
+}
+
+#[test]
+fn test_folder_names_are_parsed_into_title_and_year() {
+    let parse = |name: &str| FolderTitle::parse(name).map(|title| (title.title, title.year));
+
+    assert_eq!(parse("The Matrix (1999)"), Some(("The Matrix".to_string(), 1999)));
+    assert_eq!(parse("Show Name (2008) [1080p]"), Some(("Show Name".to_string(), 2008)));
+    assert_eq!(parse("[Group] Amélie (2001) {edition-Director's Cut}"), Some(("Amélie".to_string(), 2001)));
+    assert_eq!(parse("2001: A Space Odyssey (1968)"), Some(("2001: A Space Odyssey".to_string(), 1968)));
+    assert_eq!(parse("The Matrix"), None);
+    assert_eq!(parse("(1999)"), None);
+}
+
+#[test]
+fn test_titles_are_compared_without_punctuation_or_case() {
+    assert_eq!(normalize_title("Mission: Impossible"), normalize_title("mission - impossible"));
+    assert_eq!(normalize_title("Schindler's List"), "schindlers list");
+    assert_eq!(normalize_title("Fast & Furious"), normalize_title("Fast and Furious"));
+    assert_ne!(normalize_title("The Matrix"), normalize_title("Matrix"));
+}
+
+#[tokio::test]
+async fn test_single_exact_match_is_resolved_and_cached() {
+    let cache = tempdir().unwrap();
+    let (url, requests) = serve(vec![("/search/movie?", MATRIX_SEARCH), ("/movie/603/external_ids?", MATRIX_EXTERNAL_IDS)]).await;
+    let resolver = resolver(cache.path(), url);
+
+    let resolution = resolver.resolve("The Matrix (1999) [1080p]", FolderType::Movie).await.unwrap();
+
+    assert_eq!(resolution, Resolution::Matched { tmdb_id: 603, imdb_id: Some("tt0133093".to_string()), tvdb_id: None });
+    assert!(requests.lock().unwrap()[0].contains("query=The+Matrix&page=1&include_adult=false&year=1999"));
+    assert_eq!(request_count(&requests), 2);
+
+    let again = resolver.resolve("The Matrix (1999)", FolderType::Movie).await.unwrap();
+    assert_eq!(again, resolution);
+    assert_eq!(request_count(&requests), 2);
+}
+
+#[tokio::test]
+async fn test_tv_folders_search_tv_shows() {
+    let cache = tempdir().unwrap();
+    let (url, requests) = serve(vec![("/search/tv?", SHOW_SEARCH), ("/tv/1396/external_ids?", SHOW_EXTERNAL_IDS)]).await;
+    let resolver = resolver(cache.path(), url);
+
+    let resolution = resolver.resolve("Breaking Bad (2008)", FolderType::TvShow).await.unwrap();
+
+    assert_eq!(resolution, Resolution::Matched { tmdb_id: 1396, imdb_id: Some("tt0903747".to_string()), tvdb_id: Some(81189) });
+    assert!(requests.lock().unwrap()[0].contains("first_air_date_year=2008"));
+}
+
+#[tokio::test]
+async fn test_ambiguous_and_unmatched_titles_are_not_guessed() {
+    let cache = tempdir().unwrap();
+    let (url, requests) = serve(vec![("query=Hamlet&", HAMLET_SEARCH), ("query=The+Matrix&", MATRIX_SEARCH)]).await;
+    let resolver = resolver(cache.path(), url);
+
+    let hamlet = resolver.resolve("Hamlet (1990)", FolderType::Movie).await.unwrap();
+    assert_eq!(hamlet, Resolution::Ambiguous { candidates: vec![10264, 48286] });
+
+    let wrong_year = resolver.resolve("The Matrix (2003)", FolderType::Movie).await.unwrap();
+    assert_eq!(wrong_year, Resolution::NoMatch);
+
+    let unknown = resolver.resolve("Not A Real Film (1999)", FolderType::Movie).await.unwrap();
+    assert_eq!(unknown, Resolution::NoMatch);
+    assert_eq!(request_count(&requests), 3);
+
+    assert_eq!(resolver.resolve("Hamlet (1990)", FolderType::Movie).await.unwrap(), hamlet);
+    assert_eq!(resolver.resolve("No Year", FolderType::Movie).await.unwrap(), Resolution::Unparsable);
+    assert_eq!(request_count(&requests), 3);
+}
+
+#[tokio::test]
+async fn test_untagged_folders_are_only_resolved_when_enabled() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    std::fs::create_dir_all(media.path().join("Movies").join("Hamlet (1990)")).unwrap();
+    std::fs::create_dir_all(media.path().join("Other").join("Hamlet (1990)")).unwrap();
+    let (url, requests) = serve(vec![("/search/movie?", HAMLET_SEARCH)]).await;
+
+    let config = AppConfig::new(cache.path().to_string_lossy())
+        .with_user_agent("TestAgent")
+        .with_tmdb_api_key("key")
+        .with_library(LibraryProfile::new("resolved", media.path().to_string_lossy()).with_movie_folders(["Movies"]).with_resolve_untagged(true))
+        .with_library(LibraryProfile::new("tagged", media.path().to_string_lossy()).with_movie_folders(["Other"]));
+    let config = ConfigurationProvider::validate_config(config).unwrap();
+    let app = App::builder(config).with_tmdb_client(tmdb_client(url)).build().unwrap();
+
+    let outcome = app.refresh_path(&media.path().join("Other").join("Hamlet (1990)")).await.unwrap();
+    assert_eq!(outcome, TitleOutcome::NoId { reason: "no IMDb ID in folder name".to_string() });
+    assert_eq!(request_count(&requests), 0);
+
+    let outcome = app.refresh_path(&media.path().join("Movies").join("Hamlet (1990)")).await.unwrap();
+    assert_eq!(outcome, TitleOutcome::NoId { reason: "folder name matches 2 TMDB titles, tag it by hand".to_string() });
+    assert_eq!(request_count(&requests), 1);
+}
+
+#[test]
+fn test_resolving_untagged_folders_needs_a_tmdb_api_key() {
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    std::fs::create_dir_all(media.path().join("Movies")).unwrap();
+
+    let config = AppConfig::new(cache.path().to_string_lossy())
+        .with_library(LibraryProfile::new("movies", media.path().to_string_lossy()).with_movie_folders(["Movies"]).with_resolve_untagged(true));
+
+    let err = ConfigurationProvider::validate_config(config).unwrap_err();
+    assert!(err.to_string().contains("TRAILERFIN_RESOLVE_UNTAGGED"));
+}
+
+#[tokio::test]
+async fn test_imdb_ids_are_cached_per_media_type() {
+    let cache = tempdir().unwrap();
+    let (url, requests) = serve(vec![("/movie/603/external_ids?", MATRIX_EXTERNAL_IDS), ("/tv/603/external_ids?", r#"{"id":603,"imdb_id":"tt0000603"}"#)]).await;
+    let config = AppConfig::new(cache.path().to_string_lossy());
+    let db = create_database(&config).unwrap();
+    let legacy: TableDefinition<&str, &str> = TableDefinition::new("tmdb_to_imdb");
+    let txn = db.db().begin_write().unwrap();
+    txn.open_table(legacy).unwrap().insert("603", "tt0133093").unwrap();
+    txn.commit().unwrap();
+
+    let tmdb_to_imdb = create_tmdb_to_imdb_cache(db.clone()).unwrap();
+    let client = tmdb_client(url);
+
+    assert!(db.db().begin_read().unwrap().open_table(legacy).unwrap().get("603").unwrap().is_none());
+    assert_eq!(lookup_imdb_id(&client, &tmdb_to_imdb, "603", &FolderType::Movie).await.unwrap().as_deref(), Some("tt0133093"));
+    assert_eq!(lookup_imdb_id(&client, &tmdb_to_imdb, "603", &FolderType::TvShow).await.unwrap().as_deref(), Some("tt0000603"));
+    assert_eq!(tmdb_to_imdb.try_get_imdb_id("movie", "603").unwrap().as_deref(), Some("tt0133093"));
+    assert_eq!(tmdb_to_imdb.try_get_imdb_id("tv", "603").unwrap().as_deref(), Some("tt0000603"));
+    assert_eq!(request_count(&requests), 2);
+}
@@ -0,0 +1,132 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tempfile::tempdir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use trailerfin_rust::app::App;
+use trailerfin_rust::configuration::configuration_provider::{AppConfig, ConfigurationProvider, DataSource, LibraryProfile};
+use trailerfin_rust::request_clients::client_settings::{ClientSettings, Upstream};
+use trailerfin_rust::request_clients::rate_limited_client::RateLimitedClient;
+use trailerfin_rust::request_clients::request_errors::error::Error;
+use trailerfin_rust::request_clients::request_errors::tmdb_status::TmdbStatus;
+use trailerfin_rust::request_clients::retry_policy::RetryPolicy;
+use trailerfin_rust::request_clients::tmdb_client::tmdb_request_client::{ClientBuilder, TmdbRequestClient};
+use trailerfin_rust::scrapers::scrape_error::ScrapeErrorKind;
+
+const INVALID_KEY: &str = r#"{"success":false,"status_code":7,"status_message":"Invalid API key: You must be granted a valid key."}"#;
+
+/// Serves the same response to every connection and counts the requests.
+async fn serve(status: &'static str, content_type: &'static str, body: &'static str) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            counter.fetch_add(1, Ordering::SeqCst);
+            let mut buf = [0u8; 4096];
+            _ = socket.read(&mut buf).await;
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            _ = socket.write_all(response.as_bytes()).await;
+            _ = socket.shutdown().await;
+        }
+    });
+
+    (url, hits)
+}
+
+fn tmdb_client(base_url: String) -> TmdbRequestClient {
+    let executor = RateLimitedClient::from_settings(&ClientSettings::new("TestAgent", "100/second").with_retry_policy(RetryPolicy::none())).unwrap();
+    TmdbRequestClient(
+        ClientBuilder::default()
+            .with_base_url(base_url)
+            .with_api_key("bad-key".to_string())
+            .with_executor(executor)
+            .build()
+            .unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_html_error_pages_keep_a_snippet_of_the_body() {
+    let (url, _) = serve("502 Bad Gateway", "text/html", "<html>\n  <body>\n    <h1>502 Bad Gateway</h1>\n  </body>\n</html>").await;
+
+    let err = tmdb_client(url).external_ids().get_for_movie("603").await.unwrap_err();
+
+    assert!(matches!(&err, Error::UnparsedBody { code: 502, snippet } if snippet == "<html> <body> <h1>502 Bad Gateway</h1> </body> </html>"));
+    assert_eq!(err.to_string(), "unexpected status code 502: <html> <body> <h1>502 Bad Gateway</h1> </body> </html>");
+    assert_eq!(ScrapeErrorKind::of_request_error(&err, Upstream::Tmdb), ScrapeErrorKind::Upstream);
+}
+
+#[tokio::test]
+async fn test_empty_error_bodies_are_reported_by_status() {
+    let (url, _) = serve("429 Too Many Requests", "text/plain", "").await;
+
+    let err = tmdb_client(url).external_ids().get_for_movie("603").await.unwrap_err();
+
+    assert_eq!(err.to_string(), "unexpected status code 429 with an empty body");
+    assert_eq!(ScrapeErrorKind::of_request_error(&err, Upstream::Tmdb), ScrapeErrorKind::Throttled);
+}
+
+#[test]
+fn test_long_bodies_are_cut_short() {
+    let body = "x".repeat(1000);
+
+    let Error::UnparsedBody { snippet, .. } = Error::unparsed_body(500, &body) else {
+        panic!("expected an unparsed body");
+    };
+    assert_eq!(snippet.chars().count(), 201);
+    assert!(snippet.ends_with('…'));
+}
+
+#[tokio::test]
+async fn test_tmdb_status_codes_are_classified() {
+    let (url, _) = serve("401 Unauthorized", "application/json", INVALID_KEY).await;
+    let err = tmdb_client(url).external_ids().get_for_movie("603").await.unwrap_err();
+    assert!(err.is_auth_rejected());
+    assert_eq!(err.as_server_error().unwrap().tmdb_status(), TmdbStatus::AuthRejected);
+    assert_eq!(ScrapeErrorKind::of_request_error(&err, Upstream::Tmdb), ScrapeErrorKind::TmdbAuthInvalid);
+
+    let (url, _) = serve("404 Not Found", "application/json", r#"{"status_code":34,"status_message":"The resource you requested could not be found."}"#).await;
+    let err = tmdb_client(url).external_ids().get_for_movie("0").await.unwrap_err();
+    assert!(!err.is_auth_rejected());
+    assert_eq!(ScrapeErrorKind::of_request_error(&err, Upstream::Tmdb), ScrapeErrorKind::NotFound);
+
+    assert_eq!(TmdbStatus::from_code(25), TmdbStatus::RateLimited);
+    assert_eq!(TmdbStatus::from_code(46), TmdbStatus::Unavailable);
+    assert_eq!(TmdbStatus::from_code(999), TmdbStatus::Other(999));
+}
+
+#[tokio::test]
+async fn test_a_rejected_api_key_stops_the_scan_after_one_request() {
+    let (url, hits) = serve("401 Unauthorized", "application/json", INVALID_KEY).await;
+    let cache = tempdir().unwrap();
+    let media = tempdir().unwrap();
+    for title in ["A {tmdb-1}", "B {tmdb-2}", "C {tmdb-3}", "D {tmdb-4}"] {
+        std::fs::create_dir_all(media.path().join("Movies").join(title)).unwrap();
+    }
+
+    let mut config = AppConfig::new(cache.path().to_string_lossy())
+        .with_library(
+            LibraryProfile::new("movies", media.path().to_string_lossy())
+                .with_movie_folders(["Movies"])
+                .with_data_source(DataSource::Tmdb),
+        )
+        .with_threads(1)
+        .with_tmdb_api_key("bad-key");
+    config.tmdb_base_url = url.into();
+    config.http_cache = false;
+    let app = App::builder(ConfigurationProvider::validate_config(config).unwrap()).build().unwrap();
+
+    let summary = app.scan().await;
+
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+    assert_eq!(summary.errors_of(ScrapeErrorKind::TmdbAuthInvalid), 1);
+    assert_eq!(summary.skipped, 3);
+    assert!(summary.cut_short.unwrap().contains("TMDB rejected the API key"));
+}
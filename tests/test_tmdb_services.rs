@@ -0,0 +1,205 @@
+use std::sync::{Arc, Mutex};
+use chrono::NaiveDate;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use trailerfin_rust::request_clients::client_settings::ClientSettings;
+use trailerfin_rust::request_clients::rate_limited_client::RateLimitedClient;
+use trailerfin_rust::request_clients::retry_policy::RetryPolicy;
+use trailerfin_rust::request_clients::tmdb_client::details_endpoints::AppendToResponse;
+use trailerfin_rust::request_clients::tmdb_client::release_dates_endpoints::ReleaseType;
+use trailerfin_rust::request_clients::tmdb_client::tmdb_request_client::{ClientBuilder, Locale, TmdbRequestClient};
+
+const MOVIE: &str = include_str!("fixtures/tmdb/movie_603.json");
+const TV: &str = include_str!("fixtures/tmdb/tv_1399.json");
+const FIND: &str = include_str!("fixtures/tmdb/find_tt0133093.json");
+const SEARCH_MOVIE_PAGE_1: &str = include_str!("fixtures/tmdb/search_movie_page1.json");
+const SEARCH_MOVIE_PAGE_2: &str = include_str!("fixtures/tmdb/search_movie_page2.json");
+const SEARCH_TV: &str = include_str!("fixtures/tmdb/search_tv.json");
+const VIDEOS: &str = include_str!("fixtures/tmdb/movie_603_videos.json");
+const RELEASE_DATES: &str = include_str!("fixtures/tmdb/movie_603_release_dates.json");
+
+/// Answers each request with the body of the first route whose needle is part of the request
+/// line, or a TMDB 404, and keeps the request lines.
+async fn serve(routes: Vec<(&'static str, &'static str)>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(vec![]));
+    let received = requests.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let read = socket.read(&mut buf).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buf[..read]);
+            let request_line = request.lines().next().unwrap_or_default().to_string();
+            let (status, body) = routes
+                .iter()
+                .find(|(needle, _)| request_line.contains(needle))
+                .map(|(_, body)| ("200 OK", *body))
+                .unwrap_or(("404 Not Found", r#"{"success":false,"status_code":34,"status_message":"The resource you requested could not be found."}"#));
+            received.lock().unwrap().push(request_line);
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            _ = socket.write_all(response.as_bytes()).await;
+            _ = socket.shutdown().await;
+        }
+    });
+
+    (url, requests)
+}
+
+fn tmdb_client(base_url: String, locale: Locale) -> TmdbRequestClient {
+    let executor = RateLimitedClient::from_settings(&ClientSettings::new("TestAgent", "100/second").with_retry_policy(RetryPolicy::none())).unwrap();
+    TmdbRequestClient(
+        ClientBuilder::default()
+            .with_base_url(base_url)
+            .with_api_key("key".to_string())
+            .with_locale(locale)
+            .with_executor(executor)
+            .build()
+            .unwrap(),
+    )
+}
+
+fn last_request(requests: &Arc<Mutex<Vec<String>>>) -> String {
+    requests.lock().unwrap().last().cloned().unwrap()
+}
+
+#[tokio::test]
+async fn test_movie_details_include_appended_responses() {
+    let (url, requests) = serve(vec![("/movie/603?", MOVIE)]).await;
+    let client = tmdb_client(url, Locale::new().with_language("en-US").with_region("US"));
+
+    let movie = client
+        .details()
+        .movie("603", &[AppendToResponse::Videos, AppendToResponse::ReleaseDates, AppendToResponse::ExternalIds])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        last_request(&requests),
+        "GET /movie/603?api_key=key&append_to_response=videos%2Crelease_dates%2Cexternal_ids&language=en-US&include_video_language=en%2Cnull HTTP/1.1"
+    );
+    assert_eq!(movie.title, "The Matrix");
+    assert_eq!(movie.imdb_id.as_deref(), Some("tt0133093"));
+    assert_eq!(movie.release_date, NaiveDate::from_ymd_opt(1999, 3, 31));
+    assert_eq!(movie.runtime, Some(136));
+    assert_eq!(movie.genres[1].name, "Science Fiction");
+    assert_eq!(movie.videos.unwrap().best_trailer().unwrap().key, "vKQi3bBA1y8");
+    assert_eq!(movie.release_dates.unwrap().for_region("US").len(), 3);
+    assert_eq!(movie.external_ids.unwrap().imdb_id.as_deref(), Some("tt0133093"));
+}
+
+#[tokio::test]
+async fn test_details_without_appended_responses() {
+    let (url, requests) = serve(vec![("/tv/1399?", TV)]).await;
+    let client = tmdb_client(url, Locale::new());
+
+    let show = client.details().tv("1399", &[]).await.unwrap();
+
+    assert_eq!(last_request(&requests), "GET /tv/1399?api_key=key HTTP/1.1");
+    assert_eq!(show.name, "Game of Thrones");
+    assert_eq!(show.first_air_date, NaiveDate::from_ymd_opt(2011, 4, 17));
+    assert_eq!(show.number_of_seasons, Some(8));
+    assert_eq!(show.external_ids.unwrap().imdb_id.as_deref(), Some("tt0944947"));
+    assert!(show.videos.is_none());
+}
+
+#[tokio::test]
+async fn test_find_by_imdb_id() {
+    let (url, requests) = serve(vec![("/find/tt0133093?", FIND)]).await;
+    let client = tmdb_client(url, Locale::new().with_language("de-DE"));
+
+    let found = client.find().by_imdb_id("tt0133093").await.unwrap();
+
+    assert_eq!(last_request(&requests), "GET /find/tt0133093?api_key=key&external_source=imdb_id&language=de-DE HTTP/1.1");
+    assert_eq!(found.movie_results[0].id, 603);
+    assert_eq!(found.movie_results[0].year(), Some(1999));
+    assert!(found.tv_results.is_empty());
+}
+
+#[tokio::test]
+async fn test_movie_search_pages_through_results() {
+    let (url, requests) = serve(vec![("page=1&", SEARCH_MOVIE_PAGE_1), ("page=2&", SEARCH_MOVIE_PAGE_2)]).await;
+    let client = tmdb_client(url, Locale::new().with_language("en-US").with_region("GB"));
+
+    let page = client.search().movies("The Matrix", Some(1999), 1).await.unwrap();
+    assert_eq!(
+        last_request(&requests),
+        "GET /search/movie?api_key=key&query=The+Matrix&page=1&include_adult=false&year=1999&language=en-US&region=GB HTTP/1.1"
+    );
+    assert_eq!(page.total_results, 3);
+    assert_eq!(page.next_page(), Some(2));
+    assert_eq!(page.results[1].release_date, None);
+
+    let all = client.search().all_movies("The Matrix", None, 5).await.unwrap();
+    let ids: Vec<u64> = all.iter().map(|movie| movie.id).collect();
+    assert_eq!(ids, [603, 684731, 604]);
+    assert_eq!(requests.lock().unwrap().len(), 3);
+
+    let first_page_only = client.search().all_movies("The Matrix", None, 1).await.unwrap();
+    assert_eq!(first_page_only.len(), 2);
+}
+
+#[tokio::test]
+async fn test_tv_search_and_per_request_locale() {
+    let (url, requests) = serve(vec![("/search/tv?", SEARCH_TV)]).await;
+    let client = tmdb_client(url, Locale::new().with_language("en-US"));
+
+    let page = client
+        .search()
+        .with_locale(Locale::new().with_language("fr-FR"))
+        .tv("Game of Thrones", Some(2011), 1)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        last_request(&requests),
+        "GET /search/tv?api_key=key&query=Game+of+Thrones&page=1&include_adult=false&first_air_date_year=2011&language=fr-FR HTTP/1.1"
+    );
+    assert_eq!(page.results[0].year(), Some(2011));
+    assert_eq!(page.next_page(), None);
+}
+
+#[tokio::test]
+async fn test_videos_prefer_official_trailers() {
+    let (url, requests) = serve(vec![("/movie/603/videos?", VIDEOS)]).await;
+    let client = tmdb_client(url, Locale::new());
+
+    let videos = client.videos().movie("603").await.unwrap();
+
+    assert_eq!(last_request(&requests), "GET /movie/603/videos?api_key=key HTTP/1.1");
+    let trailer = videos.best_trailer().unwrap();
+    assert_eq!(trailer.name, "The Matrix (1999) Official Trailer");
+    assert_eq!(trailer.url().as_deref(), Some("https://www.youtube.com/watch?v=vKQi3bBA1y8"));
+}
+
+#[tokio::test]
+async fn test_release_dates_per_region() {
+    let (url, _) = serve(vec![("/movie/603/release_dates?", RELEASE_DATES)]).await;
+    let client = tmdb_client(url, Locale::new());
+
+    let releases = client.release_dates().movie("603").await.unwrap();
+
+    let us = releases.for_region("us");
+    assert_eq!(us[0].kind, ReleaseType::Premiere);
+    assert_eq!(us[0].note, "Westwood, California");
+    assert_eq!(us[1].certification, "R");
+    assert_eq!(releases.earliest("US", &[ReleaseType::Theatrical, ReleaseType::TheatricalLimited]), NaiveDate::from_ymd_opt(1999, 3, 31));
+    assert_eq!(releases.earliest("DE", &[ReleaseType::Theatrical]), NaiveDate::from_ymd_opt(1999, 6, 17));
+    assert_eq!(releases.earliest("FR", &[ReleaseType::Theatrical]), None);
+    assert!(releases.for_region("FR").is_empty());
+}
+
+#[tokio::test]
+async fn test_unknown_titles_are_tmdb_not_found_errors() {
+    let (url, _) = serve(vec![]).await;
+    let client = tmdb_client(url, Locale::new());
+
+    let err = client.details().movie("0", &[]).await.unwrap_err();
+
+    assert_eq!(err.as_server_error().unwrap().status_code, 34);
+}